@@ -1,9 +1,41 @@
 import type { BaseResponse } from '@/types/base'
-import { authTokenKey } from '@/constants'
+import type { UserLoginRes } from '@/types/user'
+import { authTokenKey, refreshTokenKey } from '@/constants'
 import { toast } from 'sonner'
 
 const base = `${import.meta.env.VITE_ServerUrl}`
 
+let refreshing: Promise<boolean> | undefined
+
+// access token 过期时使用 refresh token 换取新的 token，并发请求共用同一次刷新
+function refreshAuthToken() {
+  refreshing ??= (async () => {
+    const refresh_token = localStorage.getItem(refreshTokenKey)
+    if (!refresh_token)
+      return false
+    try {
+      const res = await fetch(`${base}/api/user/refresh`, {
+        method: 'POST',
+        body: JSON.stringify({ refresh_token }),
+        headers: { 'Content-Type': 'application/json' },
+      })
+      const t = await res.json() as unknown as UserLoginRes
+      if (t.ret !== 0 || !t.data)
+        return false
+      localStorage.setItem(authTokenKey, t.data.auth_token)
+      localStorage.setItem(refreshTokenKey, t.data.refresh_token)
+      return true
+    }
+    catch {
+      return false
+    }
+    finally {
+      refreshing = undefined
+    }
+  })()
+  return refreshing
+}
+
 export function createRequest<Req, Ret extends BaseResponse>({
   url,
   method,
@@ -15,7 +47,7 @@ export function createRequest<Req, Ret extends BaseResponse>({
   headers?: Record<string, string>
   needAuth?: boolean
 }) {
-  return async (data: Req, path?: string) => {
+  const request = async (data: Req, path?: string, retry = true): Promise<Ret | undefined> => {
     if (needAuth) {
       const token = localStorage.getItem(authTokenKey)
       if (!token) {
@@ -32,9 +64,8 @@ export function createRequest<Req, Ret extends BaseResponse>({
           ...(headers ?? {}),
         },
       })
-      if (res.status === 401) {
-        return
-      }
+      if (res.status === 401 && needAuth && retry && await refreshAuthToken())
+        return request(data, path, false)
       const t = await res.json() as unknown as Ret
       if (t.ret !== 0) {
         toast.error(t.msg, { position: 'top-center' })
@@ -47,4 +78,5 @@ export function createRequest<Req, Ret extends BaseResponse>({
       toast.error('网络错误', { position: 'top-center' })
     }
   }
+  return (data: Req, path?: string) => request(data, path)
 }
@@ -0,0 +1,25 @@
+import type { BaseResponse } from '@/types/base'
+import type { FilterPresetListRes, FilterPresetReq, FilterPresetRes } from '@/types/filter'
+import { createRequest } from './base'
+
+export const getFilterPresets = createRequest<void, FilterPresetListRes>({
+  url: '/api/user/filters',
+  method: 'GET',
+})
+
+export const createFilterPreset = createRequest<FilterPresetReq, FilterPresetRes>({
+  url: '/api/user/filters',
+  method: 'POST',
+})
+
+// path: 预设 id
+export const updateFilterPreset = createRequest<FilterPresetReq, FilterPresetRes>({
+  url: '/api/user/filters',
+  method: 'POST',
+})
+
+// path: 预设 id
+export const deleteFilterPreset = createRequest<void, BaseResponse>({
+  url: '/api/user/filters',
+  method: 'DELETE',
+})
@@ -0,0 +1,33 @@
+import type { BaseResponse } from '@/types/base'
+import type { CreateInviteReq, CreateInviteRes, GuestJoinReq, GuestTicketReq, GuestTokenRes, RoomInviteListRes } from '@/types/invite'
+import { createRequest } from './base'
+
+// path: `${room_id}/invite`
+export const createRoomInvite = createRequest<CreateInviteReq, CreateInviteRes>({
+  url: '/api/room',
+  method: 'POST',
+})
+
+// path: `${room_id}/invite`
+export const getRoomInvites = createRequest<void, RoomInviteListRes>({
+  url: '/api/room',
+  method: 'GET',
+})
+
+// path: `${room_id}/invite/${invite_id}`
+export const revokeRoomInvite = createRequest<void, BaseResponse>({
+  url: '/api/room',
+  method: 'DELETE',
+})
+
+export const joinAsGuest = createRequest<GuestJoinReq, GuestTokenRes>({
+  url: '/api/guest/join',
+  method: 'POST',
+  needAuth: false,
+})
+
+export const pollGuestAdmission = createRequest<GuestTicketReq, GuestTokenRes>({
+  url: '/api/guest/lobby',
+  method: 'POST',
+  needAuth: false,
+})
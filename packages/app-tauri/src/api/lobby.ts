@@ -0,0 +1,26 @@
+import type { AdmissionListRes, AdmissionRes } from '@/types/room'
+import { createRequest } from './base'
+
+// path: `${room_id}/lobby`
+export const getRoomAdmissions = createRequest<void, AdmissionListRes>({
+  url: '/api/room',
+  method: 'GET',
+})
+
+// path: `${room_id}/lobby/me`
+export const getMyAdmission = createRequest<void, AdmissionRes>({
+  url: '/api/room',
+  method: 'GET',
+})
+
+// path: `${room_id}/lobby/${admission_id}/admit`
+export const admitParticipant = createRequest<void, AdmissionRes>({
+  url: '/api/room',
+  method: 'POST',
+})
+
+// path: `${room_id}/lobby/${admission_id}/deny`
+export const denyParticipant = createRequest<void, AdmissionRes>({
+  url: '/api/room',
+  method: 'POST',
+})
@@ -0,0 +1,40 @@
+import type { BaseResponse } from '@/types/base'
+import type {
+  ModerationLogListRes,
+  MuteTrackReq,
+  ParticipantListRes,
+  ParticipantRes,
+  TrackRes,
+  UpdateParticipantReq,
+} from '@/types/room'
+import { createRequest } from './base'
+
+// path: `${room_id}/moderation/participants`
+export const getLiveParticipants = createRequest<void, ParticipantListRes>({
+  url: '/api/room',
+  method: 'GET',
+})
+
+// path: `${room_id}/moderation/participants/${identity}/mute`
+export const muteParticipantTrack = createRequest<MuteTrackReq, TrackRes>({
+  url: '/api/room',
+  method: 'POST',
+})
+
+// path: `${room_id}/moderation/participants/${identity}`
+export const removeParticipant = createRequest<void, BaseResponse>({
+  url: '/api/room',
+  method: 'DELETE',
+})
+
+// path: `${room_id}/moderation/participants/${identity}/update`
+export const updateParticipant = createRequest<UpdateParticipantReq, ParticipantRes>({
+  url: '/api/room',
+  method: 'POST',
+})
+
+// path: `${room_id}/moderation/logs`
+export const getModerationLogs = createRequest<void, ModerationLogListRes>({
+  url: '/api/room',
+  method: 'GET',
+})
@@ -1,5 +1,5 @@
 import type { BaseResponse } from '@/types/base'
-import type { CreateRoomReq, LiveKitEgressInfoRes, RoomListRes, RoomTokenRes, UpdateRoomReq } from '@/types/room'
+import type { AvailabilityReq, AvailabilityRes, CreateRoomReq, LiveKitEgressInfoRes, ResolveRoomRes, RoomListRes, RoomTokenRes, SetRoomRoleReq, UpdateRoomReq } from '@/types/room'
 import { createRequest } from './base'
 
 export const createRoom = createRequest<CreateRoomReq, BaseResponse>({
@@ -31,3 +31,26 @@ export const stopRecord = createRequest<void, LiveKitEgressInfoRes>({
   url: '/api/room/stopRecord',
   method: 'POST',
 })
+
+// path: room_id，彻底删除时追加 ?hard=true
+export const deleteRoom = createRequest<void, BaseResponse>({
+  url: '/api/room/delete',
+  method: 'DELETE',
+})
+
+export const setRoomRole = createRequest<SetRoomRoleReq, BaseResponse>({
+  url: '/api/room/role',
+  method: 'POST',
+})
+
+export const getAvailability = createRequest<AvailabilityReq, AvailabilityRes>({
+  url: '/api/room/availability',
+  method: 'POST',
+})
+
+// path: code，可带空格或连字符
+export const resolveRoom = createRequest<void, ResolveRoomRes>({
+  url: '/api/room/resolve',
+  method: 'GET',
+  needAuth: false,
+})
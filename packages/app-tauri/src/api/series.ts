@@ -0,0 +1,19 @@
+import type { CreateSeriesReq, SeriesRes, UpdateSeriesReq } from '@/types/room'
+import { createRequest } from './base'
+
+export const createSeries = createRequest<CreateSeriesReq, SeriesRes>({
+  url: '/api/series/create',
+  method: 'PUT',
+})
+
+// path: `${series_id}`
+export const getSeries = createRequest<void, SeriesRes>({
+  url: '/api/series',
+  method: 'GET',
+})
+
+// path: `${series_id}`
+export const updateSeries = createRequest<UpdateSeriesReq, SeriesRes>({
+  url: '/api/series/update',
+  method: 'POST',
+})
@@ -1,25 +1,78 @@
 import type { BaseResponse } from '@/types/base'
 import type { GptFilterReq } from '@/types/room'
-import type { GptFilterRes, UserLoginRes, UserUpdateReq } from '@/types/user'
+import type { AccountDeletionRes, AccountExportRes, CalendarFeedRes, DeleteUserReq, DisableMfaReq, GptFilterRes, MfaCodeReq, MfaLoginReq, MfaSetupRes, MfaStatusRes, RecoveryCodesRes, RefreshTokenReq, UpdateProfileReq, UserAuthReq, UserLoginRes, UserProfileRes, UserSearchQuery, UserSearchRes, UserUpdateReq } from '@/types/user'
 import { createRequest } from './base'
 
-export const createUser = createRequest<{ id: string, password: string }, BaseResponse>({
+export const createUser = createRequest<UserAuthReq, BaseResponse>({
   url: '/api/user/create',
   method: 'PUT',
   needAuth: false,
 })
 
-export const login = createRequest<{ id: string, password: string }, UserLoginRes>({
+export const login = createRequest<UserAuthReq, UserLoginRes>({
   url: '/api/user/login',
   method: 'POST',
   needAuth: false,
 })
 
-export const deleteUser = createRequest<void, BaseResponse>({
+// 开启两步验证时 login 只返回 mfa 凭证，需再提交验证码或恢复码
+export const loginMfa = createRequest<MfaLoginReq, UserLoginRes>({
+  url: '/api/user/login/mfa',
+  method: 'POST',
+  needAuth: false,
+})
+
+export const getMfaStatus = createRequest<void, MfaStatusRes>({
+  url: '/api/user/mfa',
+  method: 'GET',
+})
+
+export const setupMfa = createRequest<void, MfaSetupRes>({
+  url: '/api/user/mfa/setup',
+  method: 'POST',
+})
+
+export const enableMfa = createRequest<MfaCodeReq, RecoveryCodesRes>({
+  url: '/api/user/mfa/enable',
+  method: 'POST',
+})
+
+export const disableMfa = createRequest<DisableMfaReq, BaseResponse>({
+  url: '/api/user/mfa/disable',
+  method: 'POST',
+})
+
+export const regenerateRecoveryCodes = createRequest<MfaCodeReq, RecoveryCodesRes>({
+  url: '/api/user/mfa/recoveryCodes',
+  method: 'POST',
+})
+
+// 申请注销，宽限期结束后才清除数据
+export const deleteUser = createRequest<DeleteUserReq, AccountDeletionRes>({
   url: '/api/user/delete',
   method: 'DELETE',
 })
 
+export const getAccountDeletion = createRequest<void, AccountDeletionRes>({
+  url: '/api/user/delete',
+  method: 'GET',
+})
+
+export const cancelAccountDeletion = createRequest<void, BaseResponse>({
+  url: '/api/user/delete/cancel',
+  method: 'POST',
+})
+
+export const exportAccount = createRequest<void, AccountExportRes>({
+  url: '/api/user/export',
+  method: 'GET',
+})
+
+// zip 格式的导出需携带登录凭证下载
+export function accountExportZipUrl() {
+  return `${import.meta.env.VITE_ServerUrl}/api/user/export?format=zip`
+}
+
 export const updatePassword = createRequest<UserUpdateReq, BaseResponse>({
   url: '/api/user/update',
   method: 'POST',
@@ -29,3 +82,53 @@ export const getGptFilter = createRequest<GptFilterReq, GptFilterRes>({
   url: '/api/user/getGptFilter',
   method: 'POST',
 })
+
+export const logout = createRequest<RefreshTokenReq, BaseResponse>({
+  url: '/api/user/logout',
+  method: 'POST',
+})
+
+export const logoutAll = createRequest<void, BaseResponse>({
+  url: '/api/user/logoutAll',
+  method: 'POST',
+})
+
+export const getProfile = createRequest<void, UserProfileRes>({
+  url: '/api/user/me',
+  method: 'GET',
+})
+
+export const updateProfile = createRequest<UpdateProfileReq, UserProfileRes>({
+  url: '/api/user/me',
+  method: 'PATCH',
+})
+
+// 生成新的订阅地址，旧地址随即失效
+export const createCalendarFeed = createRequest<void, CalendarFeedRes>({
+  url: '/api/user/calendar',
+  method: 'POST',
+})
+
+export const revokeCalendarFeed = createRequest<void, BaseResponse>({
+  url: '/api/user/calendar',
+  method: 'DELETE',
+})
+
+// 日历客户端订阅使用的完整地址
+export function calendarFeedUrl(path: string) {
+  return `${import.meta.env.VITE_ServerUrl}${path}`
+}
+
+const searchUsersRequest = createRequest<void, UserSearchRes>({
+  url: '/api/user',
+  method: 'GET',
+})
+
+export function searchUsers({ q, page, page_size }: UserSearchQuery) {
+  const params = new URLSearchParams({ q })
+  if (page !== null)
+    params.set('page', `${page}`)
+  if (page_size !== null)
+    params.set('page_size', `${page_size}`)
+  return searchUsersRequest(undefined, `search?${params}`)
+}
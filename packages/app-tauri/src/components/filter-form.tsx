@@ -107,9 +107,7 @@ export function FilterForm({ className, ...props }: React.ComponentPropsWithoutR
     try {
       const res = await getGptFilter({ prompt })
       if (res?.data) {
-        const obj = JSON.parse(res.data.filter)
-        if (Object(obj) !== obj)
-          return
+        const obj = Object.fromEntries(Object.entries(res.data).filter(([, value]) => value !== null))
         setFilterState({ ...defaultFilterState, ...obj })
         toast.success(res?.msg ?? '获取推荐滤镜成功', {
           position: 'top-center',
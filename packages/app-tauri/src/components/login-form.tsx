@@ -9,7 +9,7 @@ import {
 } from '@/components/ui/card'
 import { Input } from '@/components/ui/input'
 import { Label } from '@/components/ui/label'
-import { authTokenKey, userIdKey } from '@/constants'
+import { authTokenKey, refreshTokenKey, userIdKey } from '@/constants'
 import { cn } from '@/lib/utils'
 import { useState } from 'react'
 import { useNavigate } from 'react-router'
@@ -31,6 +31,7 @@ export function LoginForm({
     const res = await login({ id: username, password: password1 })
     if (res?.data?.auth_token) {
       localStorage.setItem(authTokenKey, res.data.auth_token)
+      localStorage.setItem(refreshTokenKey, res.data.refresh_token)
       localStorage.setItem(userIdKey, username)
       toast.success(successTitle, {
         position: 'top-center',
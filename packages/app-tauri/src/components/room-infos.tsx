@@ -52,7 +52,8 @@ export function RoomInfos({
   return (
     <div className={cn('h-full mt-4 pt-4', className)}>
       {
-        data.map(({ id, code, start_time, end_time, users_ids, is_canceled, admin, record_videos, video_base }) => {
+        data.map(({ id, code, start_time, end_time, users, is_canceled, admin, recordings, video_base }) => {
+          const videos = recordings.filter(r => r.status === 'complete' && r.file_path)
           const time = `${format(new Date(start_time * 1000), 'yyyy-MM-dd HH:mm')} ~ ${format(new Date(end_time * 1000), 'yyyy-MM-dd HH:mm')}`
           return (
             <Card className="mb-4" key={id}>
@@ -97,7 +98,7 @@ export function RoomInfos({
                         <EditIcon onClick={async () => {
                           if (is_canceled)
                             return
-                          setCurData({ id, start_time, end_time, users_ids, admin })
+                          setCurData({ id, start_time, end_time, users_ids: users.map(u => u.id), admin })
                           setIsDialogOpen(true)
                         }}
                         />
@@ -112,8 +113,8 @@ export function RoomInfos({
                     <TooltipProvider>
                       <Tooltip>
                         <TooltipTrigger asChild>
-                          <DialogTrigger asChild disabled={!record_videos}>
-                            <Tv2Icon className={record_videos ? '' : 'text-zinc-400'} />
+                          <DialogTrigger asChild disabled={!videos.length}>
+                            <Tv2Icon className={videos.length ? '' : 'text-zinc-400'} />
                           </DialogTrigger>
                         </TooltipTrigger>
                         <TooltipContent>
@@ -125,10 +126,10 @@ export function RoomInfos({
                       <DialogTitle>查看录屏</DialogTitle>
                       <div className="w-full flex flex-col gap-4">
                         {
-                          record_videos.split(';').filter(Boolean).map(r => (
-                            <div key={r}>
-                              <div className="mb-2">{r}</div>
-                              <video src={`${video_base}/${r}`} controls></video>
+                          videos.map(r => (
+                            <div key={r.id}>
+                              <div className="mb-2">{r.file_path}</div>
+                              <video src={`${video_base}/${r.file_path}`} controls></video>
                             </div>
                           ))
                         }
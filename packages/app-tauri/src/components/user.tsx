@@ -1,6 +1,6 @@
 import type { ComponentProps } from 'react'
 
-import { deleteUser } from '@/api/user'
+import { deleteUser, logout as logoutApi } from '@/api/user'
 import {
   Avatar,
   AvatarFallback,
@@ -14,7 +14,7 @@ import {
   DropdownMenuSeparator,
   DropdownMenuTrigger,
 } from '@/components/ui/dropdown-menu'
-import { authTokenKey, userIdKey } from '@/constants'
+import { authTokenKey, refreshTokenKey, userIdKey } from '@/constants'
 import {
   BadgeCheck,
   Bell,
@@ -38,7 +38,11 @@ export function User({
   const navigate = useNavigate()
 
   const logout = () => {
+    const refresh_token = localStorage.getItem(refreshTokenKey)
+    if (refresh_token)
+      logoutApi({ refresh_token })
     localStorage.removeItem(authTokenKey)
+    localStorage.removeItem(refreshTokenKey)
     localStorage.removeItem(userIdKey)
     navigate('/auth')
   }
@@ -1,5 +1,7 @@
 export const authTokenKey = 'authToken'
 
+export const refreshTokenKey = 'refreshToken'
+
 export const userIdKey = 'userId'
 
 export const filterCssKey = 'filterCss'
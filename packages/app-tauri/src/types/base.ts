@@ -1,11 +1,41 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type AuthToken = { auth_token: string, };
+export type AuthToken = { auth_token: string, refresh_token: string, 
+/**
+ * auth_token 有效期，秒
+ */
+expires_in: number, };
 
-export type BaseResponse = { ret: number, msg: string, };
+export type BaseResponse = { ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
 
-export type Filter = { filter: string, };
+/**
+ * 支持的 css 滤镜，未设置的滤镜为 null
+ */
+export type CssFilter = { blur: number | null, brightness: number | null, contrast: number | null, grayscale: number | null, "hue-rotate": number | null, invert: number | null, opacity: number | null, saturate: number | null, sepia: number | null, };
+
+/**
+ * 稳定的错误类型码，客户端据此区分错误，msg 仅用于展示
+ */
+export type ErrorCode = "invalid_input" | "unauthorized" | "forbidden" | "not_found" | "conflict" | "too_many_requests" | "upstream" | "internal";
+
+/**
+ * 出错时的响应体，保留 ret 与 msg 以兼容客户端
+ */
+export type ErrorResponse = { code: ErrorCode, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
 
 export type LiveKitEgressInfo = { egress_id: string, };
 
 export type LiveKitToken = { room_id: string, livekit_token: string, };
+
+/**
+ * 稳定的消息码，客户端可据此自行翻译
+ */
+export type MsgCode = "internal_error" | "record_not_found" | "login_required" | "session_expired" | "user_not_found" | "user_disabled" | "wrong_password" | "user_exists" | "users_not_exist" | "user_created" | "user_deleted" | "password_updated" | "login_succeeded" | "token_refreshed" | "logged_out" | "logged_out_all" | "profile_fetched" | "profile_updated" | "display_name_too_long" | "invalid_avatar_url" | "invalid_email" | "unsupported_locale" | "invalid_search_query" | "users_searched" | "llm_request_failed" | "llm_parse_failed" | "gpt_filter_fetched" | "room_not_found" | "room_canceled" | "not_room_member" | "user_not_room_member" | "role_permission_denied" | "only_host_can_transfer" | "only_host_can_set_co_host" | "use_transfer_host" | "host_must_be_attendee" | "not_enough_attendees" | "rooms_fetched" | "room_created" | "room_updated" | "role_updated" | "room_token_issued" | "room_token_failed" | "room_recording" | "room_not_recording" | "egress_busy" | "record_failed" | "record_started" | "stop_record_failed" | "record_stopped" | "invite_created" | "invites_fetched" | "invite_revoked" | "invite_not_found" | "invite_invalid" | "invalid_invite_expiry" | "invalid_invite_max_uses" | "invalid_guest_name" | "guest_joined" | "lobby_waiting" | "lobby_denied" | "admissions_fetched" | "admission_fetched" | "admission_not_found" | "participant_admitted" | "participant_denied" | "participants_fetched" | "participant_not_found" | "participant_muted" | "participant_unmuted" | "participant_removed" | "participant_updated" | "empty_participant_update" | "cannot_moderate_host" | "moderation_failed" | "moderation_logs_fetched" | "invalid_rrule" | "invalid_series_time" | "series_created" | "series_fetched" | "series_updated" | "series_not_found" | "only_series_admin" | "ics_summary" | "ics_description" | "ics_calendar_name" | "calendar_token_issued" | "calendar_token_invalid" | "room_end_before_start" | "room_start_in_past" | "room_too_long" | "schedule_conflict" | "invalid_availability_range" | "availability_fetched" | "room_resolved" | "room_deleted" | "only_host_can_delete" | "series_occurrence_not_deletable" | "account_deletion_scheduled" | "account_deletion_canceled" | "account_deletion_fetched" | "no_pending_deletion" | "account_data_exported" | "too_many_attempts" | "account_locked" | "invalid_user_id" | "password_too_short" | "password_too_long" | "password_too_weak" | "password_too_common" | "password_contains_user_id" | "invalid_credentials" | "mfa_required" | "mfa_challenge_expired" | "invalid_mfa_code" | "mfa_already_enabled" | "mfa_not_set_up" | "mfa_setup_started" | "mfa_enabled" | "mfa_disabled" | "mfa_status_fetched" | "recovery_codes_regenerated" | "invalid_display_name" | "calendar_token_revoked" | "no_calendar_feed" | "cannot_moderate_co_host" | "user_id_reserved" | "egress_mismatch" | "no_transcript" | "summary_running" | "summary_not_found" | "summary_fetched" | "transcript_batch_too_large" | "invalid_transcript_range" | "transcript_uploaded" | "transcript_fetched" | "unsupported_export_format" | "filter_preset_not_found" | "invalid_preset_name" | "not_member_cannot_share" | "preset_room_required" | "unsupported_visibility" | "filter_presets_fetched" | "filter_preset_created" | "not_preset_owner" | "filter_preset_updated" | "filter_preset_deleted" | "preset_not_pinnable" | "filter_preset_pinned" | "no_pinned_filter" | "pinned_filter_fetched" | "invalid_webhook" | "webhook_received" | "invalid_request";
@@ -0,0 +1,37 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CssFilter } from "./base";
+import type { MsgCode } from "./base";
+
+export type FilterPresetListRes = { data: Array<FilterPresetNode> | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type FilterPresetNode = { id: number, owner: string, name: string, filter: CssFilter, 
+/**
+ * private | room | public
+ */
+visibility: string, room_id: number | null, updated_at: number, };
+
+export type FilterPresetReq = { name: string, filter: CssFilter, 
+/**
+ * private | room | public
+ */
+visibility: string, 
+/**
+ * visibility 为 room 时必填
+ */
+room_id: number | null, };
+
+export type FilterPresetRes = { data: FilterPresetNode | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type PinFilterReq = { 
+/**
+ * 为空时取消置顶
+ */
+preset_id: number | null, };
@@ -0,0 +1,57 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AdmissionStatus } from "./room";
+import type { MsgCode } from "./base";
+
+export type CreateInviteReq = { 
+/**
+ * 有效期，秒，默认一天，最长 30 天
+ */
+expires_in: number | null, max_uses: number | null, 
+/**
+ * 访客需等候主持人准入
+ */
+lobby: boolean | null, };
+
+export type CreateInviteRes = { data: CreatedInvite | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type CreatedInvite = { invite: RoomInviteNode, 
+/**
+ * 仅在创建时返回，由客户端拼接为邀请链接
+ */
+token: string, };
+
+export type GuestJoinReq = { token: string, display_name: string, };
+
+export type GuestTicketReq = { ticket: string, };
+
+export type GuestToken = { room_id: string, identity: string, status: AdmissionStatus, 
+/**
+ * 准入后返回
+ */
+livekit_token: string | null, 
+/**
+ * 等候准入时返回，用于轮询准入状态
+ */
+ticket: string | null, };
+
+export type GuestTokenRes = { data: GuestToken | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type RoomInviteListRes = { data: Array<RoomInviteNode> | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type RoomInviteNode = { id: number, room_id: number, created_by: string, 
+/**
+ * 为 null 时不限次数
+ */
+max_uses: number | null, use_count: number, lobby: boolean, revoked: boolean, expires_at: number, created_at: number, };
@@ -1,17 +1,273 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorCode } from "./base";
 import type { LiveKitEgressInfo } from "./base";
 import type { LiveKitToken } from "./base";
+import type { MsgCode } from "./base";
+import type { UserSummary } from "./user";
 
-export type CreateRoomReq = { start_time: number, end_time: number, users_ids: Array<string>, };
+export type AdmissionListRes = { data: Array<AdmissionNode> | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type AdmissionNode = { id: number, room_id: number, identity: string, 
+/**
+ * 访客为 null
+ */
+user_id: string | null, display_name: string, status: AdmissionStatus, created_at: number, updated_at: number, };
+
+export type AdmissionRes = { data: AdmissionNode | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type AdmissionStatus = "pending" | "admitted" | "denied";
+
+export type Availability = { users: Array<UserAvailability>, 
+/**
+ * 所有用户均空闲的时段
+ */
+free: Array<TimeSlot>, };
+
+export type AvailabilityReq = { user_ids: Array<string>, start_time: number, end_time: number, };
+
+export type AvailabilityRes = { data: Availability | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type ConflictNode = { room_id: number, start_time: number, end_time: number, 
+/**
+ * 在该会议中时间冲突的用户
+ */
+user_ids: Array<string>, };
+
+export type CreateRoomReq = { start_time: number, end_time: number, users_ids: Array<string>, lobby_enabled: boolean | null, 
+/**
+ * 忽略与会人员的时间冲突
+ */
+force: boolean | null, };
+
+export type CreateSeriesReq = { 
+/**
+ * 第一次会议的开始、结束时间
+ */
+start_time: number, end_time: number, 
+/**
+ * 如 FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10，按 UTC 计算
+ */
+rrule: string, users_ids: Array<string>, lobby_enabled: boolean | null, };
+
+export type DeleteRoomQuery = { 
+/**
+ * 为 true 时彻底删除会议及录制、转写等数据，否则仅软删除
+ */
+hard: boolean | null, };
 
 export type GptFilterReq = { prompt: string, };
 
-export type LiveKitEgressInfoRes = { data: LiveKitEgressInfo | null, ret: number, msg: string, };
+export type LiveKitEgressInfoRes = { data: LiveKitEgressInfo | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type ModerationAction = "mute" | "unmute" | "remove" | "update_permission" | "update_metadata";
+
+export type ModerationLogListRes = { data: Array<ModerationLogNode> | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type ModerationLogNode = { id: number, room_id: number, actor: string, 
+/**
+ * 取值见 `ModerationAction`
+ */
+action: string, target: string, 
+/**
+ * 操作参数，json
+ */
+detail: string, created_at: number, };
+
+export type MuteTrackReq = { track_sid: string, muted: boolean, };
+
+export type OccurrenceNode = { room_id: number, code: string, start_time: number, end_time: number, is_canceled: boolean, 
+/**
+ * 在规则中对应的原始开始时间，修改规则后被取消的会议为 null
+ */
+occurrence_start: number | null, };
+
+export type ParticipantListRes = { data: Array<ParticipantNode> | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type ParticipantNode = { sid: string, identity: string, name: string, metadata: string, joined_at: number, is_publisher: boolean, permission: ParticipantPermissionNode | null, tracks: Array<TrackNode>, };
+
+export type ParticipantPermissionNode = { can_subscribe: boolean, can_publish: boolean, can_publish_data: boolean, can_update_metadata: boolean, };
+
+/**
+ * 未设置的字段保持不变
+ */
+export type ParticipantPermissionReq = { can_subscribe: boolean | null, can_publish: boolean | null, can_publish_data: boolean | null, };
+
+export type ParticipantRes = { data: ParticipantNode | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type RecordingNode = { id: number, egress_id: string, started_by: string | null, start_time: number, end_time: number | null, 
+/**
+ * starting | active | ending | complete | failed | aborted | limit_reached
+ */
+status: string, 
+/**
+ * 相对于 `RoomNode.video_base` 的路径
+ */
+file_path: string, 
+/**
+ * 字节
+ */
+size: number, 
+/**
+ * 毫秒
+ */
+duration: number, };
+
+export type ResolveRoomRes = { data: ResolvedRoom | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+/**
+ * 按会议号公开查询到的会议信息，不含与会人员
+ */
+export type ResolvedRoom = { code: string, display_code: string, start_time: number, end_time: number, is_canceled: boolean, lobby_enabled: boolean, };
+
+export type RoomListRes = { data: Array<RoomNode> | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type RoomMemberRole = { user_id: string, role: RoomRole, };
+
+export type RoomNode = { id: number, code: string, 
+/**
+ * 分组显示的会议号，如 `123 456 789`
+ */
+display_code: string, is_canceled: boolean, start_time: number, end_time: number, admin: string, users: Array<UserSummary>, 
+/**
+ * 当前用户在会议中的角色
+ */
+role: RoomRole, member_roles: Array<RoomMemberRole>, recordings: Array<RecordingNode>, video_base: string, 
+/**
+ * 管理员置顶的推荐滤镜预设
+ */
+pinned_filter_id: number | null, 
+/**
+ * 是否开启等候室
+ */
+lobby_enabled: boolean, 
+/**
+ * 系列会议的 id，单次会议为 null
+ */
+series_id: number | null, 
+/**
+ * 在系列规则中对应的原始开始时间，单独改期后与 start_time 不同
+ */
+occurrence_start: number | null, };
+
+/**
+ * 与会者在会议中的角色，会议的 admin 始终为主持人
+ */
+export type RoomRole = "host" | "co_host" | "presenter" | "attendee" | "viewer";
+
+export type RoomTokenRes = { data: LiveKitToken | null, 
+/**
+ * 开启等候室时返回准入状态，尚未准入时 data 为 null
+ */
+admission: AdmissionNode | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+/**
+ * 时间冲突时返回 409，客户端可在确认后携带 `force` 重新提交
+ */
+export type ScheduleConflictRes = { data: Array<ConflictNode>, code: ErrorCode, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type SeriesNode = { id: number, admin: string, rrule: string, 
+/**
+ * 第一次会议的开始时间
+ */
+start_time: number, 
+/**
+ * 每次会议的时长，秒
+ */
+duration: number, lobby_enabled: boolean, is_canceled: boolean, 
+/**
+ * 已生成的各次会议，更晚的会议在临近时生成
+ */
+occurrences: Array<OccurrenceNode>, };
+
+export type SeriesRes = { data: SeriesNode | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type SetRoomRoleReq = { user_id: string, role: RoomRole, };
+
+export type TimeSlot = { start_time: number, end_time: number, };
+
+export type TrackNode = { sid: string, name: string, 
+/**
+ * audio、video、data
+ */
+kind: string, 
+/**
+ * camera、microphone、screen_share 等
+ */
+source: string, muted: boolean, };
+
+export type TrackRes = { data: TrackNode | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
 
-export type RoomListRes = { data: Array<RoomNode> | null, ret: number, msg: string, };
+export type UpdateParticipantReq = { permission: ParticipantPermissionReq | null, 
+/**
+ * LiveKit 不支持清空，传空字符串时保持不变
+ */
+metadata: string | null, };
 
-export type RoomNode = { id: number, code: string, is_canceled: boolean, start_time: number, end_time: number, admin: string, users_ids: Array<string>, record_videos: string, video_base: string, };
+export type UpdateRoomReq = { start_time: number | null, end_time: number | null, admin: string | null, is_canceled: boolean | null, user_ids: Array<string> | null, lobby_enabled: boolean | null, 
+/**
+ * 忽略与会人员的时间冲突
+ */
+force: boolean | null, };
 
-export type RoomTokenRes = { data: LiveKitToken | null, ret: number, msg: string, };
+/**
+ * 修改整个系列，只影响尚未开始的会议；单次会议通过 `/api/room/update` 修改或取消
+ */
+export type UpdateSeriesReq = { start_time: number | null, end_time: number | null, rrule: string | null, user_ids: Array<string> | null, lobby_enabled: boolean | null, 
+/**
+ * 取消后不再生成新的会议，尚未开始的会议一并取消
+ */
+is_canceled: boolean | null, };
 
-export type UpdateRoomReq = { start_time: number | null, end_time: number | null, admin: string | null, is_canceled: boolean | null, user_ids: Array<string> | null, };
+export type UserAvailability = { user_id: string, busy: Array<TimeSlot>, free: Array<TimeSlot>, };
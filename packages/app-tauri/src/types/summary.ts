@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MsgCode } from "./base";
+
+export type ActionItem = { task: string, owner: string | null, };
+
+export type MeetingSummaryNode = { room_id: number, 
+/**
+ * running | done | failed
+ */
+status: string, summary: string, decisions: Array<string>, action_items: Array<ActionItem>, error: string, created_by: string, updated_at: number, };
+
+export type MeetingSummaryRes = { data: MeetingSummaryNode | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
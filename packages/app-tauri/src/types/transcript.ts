@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MsgCode } from "./base";
+
+export type TranscriptListQuery = { page: number | null, page_size: number | null, };
+
+export type TranscriptListRes = { data: TranscriptPage | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type TranscriptPage = { total: number, page: number, page_size: number, segments: Array<TranscriptSegmentNode>, };
+
+export type TranscriptSegmentNode = { id: number, speaker_id: string, start_offset: number, end_offset: number, text: string, language: string, };
+
+export type TranscriptSegmentReq = { 
+/**
+ * 相对会议开始的毫秒偏移
+ */
+start_offset: number, end_offset: number, text: string, language: string | null, };
+
+export type UploadTranscriptReq = { segments: Array<TranscriptSegmentReq>, };
@@ -1,9 +1,202 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AuthToken } from "./base";
-import type { Filter } from "./base";
+import type { CssFilter } from "./base";
+import type { MsgCode } from "./base";
 
-export type GptFilterRes = { data: Filter | null, ret: number, msg: string, };
+export type AccountDeletion = { requested_at: number, 
+/**
+ * 到期后由后台任务清除数据，此前可撤销
+ */
+purge_at: number, rooms: RoomHandover, };
 
-export type UserLoginRes = { data: AuthToken | null, ret: number, msg: string, };
+export type AccountDeletionRes = { 
+/**
+ * 没有待处理的注销申请时为空
+ */
+data: AccountDeletion | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type AccountExport = { profile: UserProfile, rooms: Array<ExportedRoom>, recordings: Array<ExportedRecording>, 
+/**
+ * 本人在各会议中的发言
+ */
+transcripts: Array<ExportedSegment>, };
+
+export type AccountExportRes = { data: AccountExport | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type CalendarFeed = { token: string, 
+/**
+ * 订阅地址的路径，需拼接服务端地址
+ */
+path: string, };
+
+export type CalendarFeedRes = { data: CalendarFeed | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type DeleteUserReq = { 
+/**
+ * 注销前需重新验证密码
+ */
+password: string, 
+/**
+ * 主持的会议的处理方式，默认移交给其他成员
+ */
+rooms: RoomHandover | null, };
+
+export type DisableMfaReq = { password: string, 
+/**
+ * 验证码或恢复码
+ */
+code: string, };
+
+export type ExportFormat = "json" | "zip";
+
+export type ExportQuery = { format: ExportFormat | null, };
+
+export type ExportedRecording = { room_id: number, start_time: number, end_time: number | null, status: string, 
+/**
+ * 完整的下载地址
+ */
+url: string, };
+
+export type ExportedRoom = { id: number, code: string, start_time: number, end_time: number, admin: string, role: string, 
+/**
+ * 会议已被删除（归档）
+ */
+deleted: boolean, };
+
+export type ExportedSegment = { room_id: number, 
+/**
+ * 相对于会议开始的毫秒数
+ */
+start_offset: number, text: string, };
+
+export type GptFilterRes = { data: CssFilter | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type MfaChallenge = { mfa_token: string, 
+/**
+ * 有效期，单位秒
+ */
+expires_in: number, };
+
+export type MfaCodeReq = { 
+/**
+ * 身份验证器中的 6 位验证码
+ */
+code: string, };
+
+export type MfaLoginReq = { mfa_token: string, 
+/**
+ * 验证码或恢复码
+ */
+code: string, };
+
+export type MfaSetup = { 
+/**
+ * base32 编码的密钥，无法扫码时手动输入
+ */
+secret: string, otpauth_uri: string, };
+
+export type MfaSetupRes = { data: MfaSetup, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type MfaStatus = { enabled: boolean, 
+/**
+ * 未使用的恢复码数量
+ */
+recovery_codes_left: number, };
+
+export type MfaStatusRes = { data: MfaStatus, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type RecoveryCodesRes = { 
+/**
+ * 明文只返回这一次
+ */
+data: Array<string>, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type RefreshTokenReq = { refresh_token: string, };
+
+/**
+ * 注销用户时对其主持的会议的处理方式
+ */
+export type RoomHandover = "transfer" | "archive";
+
+export type UpdateProfileReq = { display_name: string | null, 
+/**
+ * http(s) 地址，空字符串表示清除
+ */
+avatar_url: string | null, 
+/**
+ * 空字符串表示清除
+ */
+email: string | null, 
+/**
+ * 如 zh-CN、en，空字符串表示跟随 Accept-Language
+ */
+locale: string | null, };
+
+export type UserAuthReq = { id: string, password: string, };
+
+export type UserLoginRes = { data: AuthToken | null, 
+/**
+ * 开启了两步验证时不返回令牌，需携带此凭证调用 `/login/mfa`
+ */
+mfa: MfaChallenge | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type UserProfile = { id: string, display_name: string, avatar_url: string, email: string, 
+/**
+ * 未设置时为空，按 Accept-Language 返回
+ */
+locale: string | null, };
+
+export type UserProfileRes = { data: UserProfile | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+export type UserSearchPage = { total: number, page: number, page_size: number, users: Array<UserSummary>, };
+
+export type UserSearchQuery = { q: string, page: number | null, page_size: number | null, };
+
+export type UserSearchRes = { data: UserSearchPage | null, ret: number, msg: string, 
+/**
+ * 接入消息码的接口返回，客户端可据此自行翻译
+ */
+msg_code: MsgCode | null, };
+
+/**
+ * 参会人员等列表中展示的用户信息，未设置昵称时使用用户 id
+ */
+export type UserSummary = { id: string, display_name: string, avatar_url: string, };
 
 export type UserUpdateReq = { old_password: string, new_password: string, };
@@ -3,6 +3,26 @@ pub use sea_orm_migration::prelude::*;
 mod m20250120_000001_create_user_table;
 mod m20250202_072600_create_room_table;
 mod m20250202_115557_room_user_table;
+mod m20250412_083000_create_recording_table;
+mod m20250420_091500_create_transcript_segment_table;
+mod m20250428_140000_create_meeting_summary_table;
+mod m20250505_100000_create_filter_preset_table;
+mod m20250512_090000_create_refresh_token_table;
+mod m20250519_100000_add_user_profile;
+mod m20250526_100000_add_user_search_index;
+mod m20250602_100000_add_room_user_role;
+mod m20250609_100000_create_room_invite_table;
+mod m20250616_100000_add_room_lobby;
+mod m20250623_100000_create_moderation_log_table;
+mod m20250630_100000_create_meeting_series_table;
+mod m20250707_100000_add_room_code_index;
+mod m20250714_100000_add_room_deleted_at;
+mod m20250721_100000_create_account_deletion_table;
+mod m20250728_100000_create_login_throttle_tables;
+mod m20250804_100000_create_user_mfa_table;
+mod m20250811_100000_add_room_occurrence_index;
+mod m20250818_100000_create_calendar_token_table;
+mod m20250825_100000_replace_user_search_index;
 
 pub struct Migrator;
 
@@ -13,6 +33,26 @@ impl MigratorTrait for Migrator {
             Box::new(m20250120_000001_create_user_table::Migration),
             Box::new(m20250202_072600_create_room_table::Migration),
             Box::new(m20250202_115557_room_user_table::Migration),
+            Box::new(m20250412_083000_create_recording_table::Migration),
+            Box::new(m20250420_091500_create_transcript_segment_table::Migration),
+            Box::new(m20250428_140000_create_meeting_summary_table::Migration),
+            Box::new(m20250505_100000_create_filter_preset_table::Migration),
+            Box::new(m20250512_090000_create_refresh_token_table::Migration),
+            Box::new(m20250519_100000_add_user_profile::Migration),
+            Box::new(m20250526_100000_add_user_search_index::Migration),
+            Box::new(m20250602_100000_add_room_user_role::Migration),
+            Box::new(m20250609_100000_create_room_invite_table::Migration),
+            Box::new(m20250616_100000_add_room_lobby::Migration),
+            Box::new(m20250623_100000_create_moderation_log_table::Migration),
+            Box::new(m20250630_100000_create_meeting_series_table::Migration),
+            Box::new(m20250707_100000_add_room_code_index::Migration),
+            Box::new(m20250714_100000_add_room_deleted_at::Migration),
+            Box::new(m20250721_100000_create_account_deletion_table::Migration),
+            Box::new(m20250728_100000_create_login_throttle_tables::Migration),
+            Box::new(m20250804_100000_create_user_mfa_table::Migration),
+            Box::new(m20250811_100000_add_room_occurrence_index::Migration),
+            Box::new(m20250818_100000_create_calendar_token_table::Migration),
+            Box::new(m20250825_100000_replace_user_search_index::Migration),
         ]
   }
 }
@@ -30,4 +30,10 @@ pub enum User {
     Table,
     Id,
     Password,
+    TokenVersion,
+    DisplayName,
+    AvatarUrl,
+    Email,
+    Locale,
+    IsDisabled,
 }
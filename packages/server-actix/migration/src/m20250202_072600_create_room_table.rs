@@ -56,4 +56,10 @@ pub enum Room {
   IsCanceled,
   RecordVideos,
   CurEgressId,
+  PinnedFilterId,
+  LobbyEnabled,
+  SeriesId,
+  OccurrenceStart,
+  CodeReleased,
+  DeletedAt,
 }
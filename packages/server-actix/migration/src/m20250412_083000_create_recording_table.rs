@@ -0,0 +1,249 @@
+use sea_orm_migration::{
+  prelude::*,
+  sea_orm::{prelude::DateTime, ConnectionTrait, Statement},
+};
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(Recording::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(Recording::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(
+            ColumnDef::new(Recording::EgressId)
+              .string()
+              .not_null()
+              .unique_key(),
+          )
+          .col(ColumnDef::new(Recording::RoomId).integer().not_null())
+          .col(ColumnDef::new(Recording::StartedBy).string())
+          .col(ColumnDef::new(Recording::StartTime).date_time().not_null())
+          .col(ColumnDef::new(Recording::EndTime).date_time())
+          .col(ColumnDef::new(Recording::Status).string().not_null())
+          .col(
+            ColumnDef::new(Recording::FilePath)
+              .string()
+              .not_null()
+              .default(""),
+          )
+          .col(
+            ColumnDef::new(Recording::Size)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(Recording::Duration)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-Recording-room_id")
+              .from(Recording::Table, Recording::RoomId)
+              .to(Room::Table, Room::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-Recording-started_by")
+              .from(Recording::Table, Recording::StartedBy)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-Recording-room_id")
+          .table(Recording::Table)
+          .col(Recording::RoomId)
+          .to_owned(),
+      )
+      .await?;
+
+    // 将 `room.record_videos` 中以 ; 拼接的录像拆分为 recording 记录
+    let db = manager.get_connection();
+    let builder = db.get_database_backend();
+    let rows = db
+      .query_all(
+        builder.build(
+          Query::select()
+            .columns([
+              Room::Id,
+              Room::Admin,
+              Room::StartTime,
+              Room::EndTime,
+              Room::RecordVideos,
+            ])
+            .from(Room::Table)
+            .and_where(Expr::col(Room::RecordVideos).ne("")),
+        ),
+      )
+      .await?;
+    for row in rows {
+      let room_id: i32 = row.try_get("", "id")?;
+      let admin: String = row.try_get("", "admin")?;
+      let start_time: DateTime = row.try_get("", "start_time")?;
+      let end_time: DateTime = row.try_get("", "end_time")?;
+      let record_videos: String = row.try_get("", "record_videos")?;
+      for (i, file_path) in record_videos
+        .split(';')
+        .filter(|f| !f.is_empty())
+        .enumerate()
+      {
+        db.execute(
+          builder.build(
+            Query::insert()
+              .into_table(Recording::Table)
+              .columns([
+                Recording::EgressId,
+                Recording::RoomId,
+                Recording::StartedBy,
+                Recording::StartTime,
+                Recording::EndTime,
+                Recording::Status,
+                Recording::FilePath,
+              ])
+              .values_panic([
+                format!("legacy-{room_id}-{i}").into(),
+                room_id.into(),
+                admin.clone().into(),
+                start_time.into(),
+                end_time.into(),
+                "complete".into(),
+                file_path.into(),
+              ]),
+          ),
+        )
+        .await?;
+      }
+    }
+
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .drop_column(Room::RecordVideos)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .add_column(
+            ColumnDef::new(Room::RecordVideos)
+              .string()
+              .not_null()
+              .default(""),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    let db = manager.get_connection();
+    db.execute(Statement::from_string(
+      db.get_database_backend(),
+      r#"UPDATE "room" SET "record_videos" = COALESCE((SELECT group_concat("file_path", ';') FROM "recording" WHERE "recording"."room_id" = "room"."id" AND "recording"."file_path" <> ''), '')"#,
+    ))
+    .await?;
+
+    manager
+      .drop_table(Table::drop().table(Recording::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum Recording {
+  Table,
+  Id,
+  EgressId,
+  RoomId,
+  StartedBy,
+  StartTime,
+  EndTime,
+  Status,
+  FilePath,
+  Size,
+  Duration,
+}
+
+#[cfg(test)]
+mod tests {
+  use sea_orm_migration::sea_orm::{Database, DatabaseConnection};
+
+  use crate::Migrator;
+
+  use super::*;
+
+  async fn query(db: &DatabaseConnection, sql: &str) -> Vec<(String, String)> {
+    db.query_all(Statement::from_string(db.get_database_backend(), sql))
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|r| {
+        (
+          r.try_get_by_index(0).unwrap(),
+          r.try_get_by_index(1).unwrap(),
+        )
+      })
+      .collect()
+  }
+
+  #[async_std::test]
+  async fn splits_and_restores_record_videos() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    Migrator::up(&db, Some(3)).await.unwrap();
+    db.execute_unprepared(
+      r#"INSERT INTO "user" (id, password) VALUES ('admin', '');
+      INSERT INTO room (id, code, record_videos, start_time, end_time, admin) VALUES
+        (1, '000000001', 'a.mp4;;b.mp4;', '2025-01-01 00:00:00', '2025-01-01 01:00:00', 'admin'),
+        (2, '000000002', '', '2025-01-01 00:00:00', '2025-01-01 01:00:00', 'admin')"#,
+    )
+    .await
+    .unwrap();
+
+    Migrator::up(&db, Some(1)).await.unwrap();
+    assert_eq!(
+      query(
+        &db,
+        r#"SELECT egress_id, file_path || ':' || room_id || ':' || started_by || ':' || status FROM recording ORDER BY id"#,
+      )
+      .await,
+      [
+        ("legacy-1-0".to_string(), "a.mp4:1:admin:complete".to_string()),
+        ("legacy-1-1".to_string(), "b.mp4:1:admin:complete".to_string()),
+      ]
+    );
+
+    Migrator::down(&db, Some(1)).await.unwrap();
+    assert_eq!(
+      query(&db, "SELECT code, record_videos FROM room ORDER BY id").await,
+      [
+        ("000000001".to_string(), "a.mp4;b.mp4".to_string()),
+        ("000000002".to_string(), String::new()),
+      ]
+    );
+  }
+}
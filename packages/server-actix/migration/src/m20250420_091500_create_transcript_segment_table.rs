@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(TranscriptSegment::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(TranscriptSegment::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(TranscriptSegment::RoomId).integer().not_null())
+          .col(ColumnDef::new(TranscriptSegment::SpeakerId).string().not_null())
+          .col(
+            ColumnDef::new(TranscriptSegment::StartOffset)
+              .big_integer()
+              .not_null(),
+          )
+          .col(
+            ColumnDef::new(TranscriptSegment::EndOffset)
+              .big_integer()
+              .not_null(),
+          )
+          .col(ColumnDef::new(TranscriptSegment::Text).text().not_null())
+          .col(
+            ColumnDef::new(TranscriptSegment::Language)
+              .string()
+              .not_null()
+              .default(""),
+          )
+          .col(
+            ColumnDef::new(TranscriptSegment::CreatedAt)
+              .date_time()
+              .not_null(),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-TranscriptSegment-room_id")
+              .from(TranscriptSegment::Table, TranscriptSegment::RoomId)
+              .to(Room::Table, Room::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-TranscriptSegment-speaker_id")
+              .from(TranscriptSegment::Table, TranscriptSegment::SpeakerId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-TranscriptSegment-room_id-start_offset")
+          .table(TranscriptSegment::Table)
+          .col(TranscriptSegment::RoomId)
+          .col(TranscriptSegment::StartOffset)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(TranscriptSegment::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum TranscriptSegment {
+  Table,
+  Id,
+  RoomId,
+  SpeakerId,
+  StartOffset,
+  EndOffset,
+  Text,
+  Language,
+  CreatedAt,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(MeetingSummary::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(MeetingSummary::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(
+            ColumnDef::new(MeetingSummary::RoomId)
+              .integer()
+              .not_null()
+              .unique_key(),
+          )
+          .col(ColumnDef::new(MeetingSummary::Status).string().not_null())
+          .col(
+            ColumnDef::new(MeetingSummary::Summary)
+              .text()
+              .not_null()
+              .default(""),
+          )
+          .col(
+            ColumnDef::new(MeetingSummary::Decisions)
+              .text()
+              .not_null()
+              .default("[]"),
+          )
+          .col(
+            ColumnDef::new(MeetingSummary::ActionItems)
+              .text()
+              .not_null()
+              .default("[]"),
+          )
+          .col(
+            ColumnDef::new(MeetingSummary::Error)
+              .string()
+              .not_null()
+              .default(""),
+          )
+          .col(ColumnDef::new(MeetingSummary::CreatedBy).string().not_null())
+          .col(
+            ColumnDef::new(MeetingSummary::UpdatedAt)
+              .date_time()
+              .not_null(),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-MeetingSummary-room_id")
+              .from(MeetingSummary::Table, MeetingSummary::RoomId)
+              .to(Room::Table, Room::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-MeetingSummary-created_by")
+              .from(MeetingSummary::Table, MeetingSummary::CreatedBy)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(MeetingSummary::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum MeetingSummary {
+  Table,
+  Id,
+  RoomId,
+  Status,
+  Summary,
+  Decisions,
+  ActionItems,
+  Error,
+  CreatedBy,
+  UpdatedAt,
+}
@@ -0,0 +1,102 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(FilterPreset::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(FilterPreset::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(FilterPreset::Owner).string().not_null())
+          .col(ColumnDef::new(FilterPreset::Name).string().not_null())
+          .col(
+            ColumnDef::new(FilterPreset::Params)
+              .text()
+              .not_null()
+              .default("{}"),
+          )
+          .col(
+            ColumnDef::new(FilterPreset::Visibility)
+              .string()
+              .not_null()
+              .default("private"),
+          )
+          .col(ColumnDef::new(FilterPreset::RoomId).integer().null())
+          .col(ColumnDef::new(FilterPreset::CreatedAt).date_time().not_null())
+          .col(ColumnDef::new(FilterPreset::UpdatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-FilterPreset-owner")
+              .from(FilterPreset::Table, FilterPreset::Owner)
+              .to(User::Table, User::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-FilterPreset-room_id")
+              .from(FilterPreset::Table, FilterPreset::RoomId)
+              .to(Room::Table, Room::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-FilterPreset-owner")
+          .table(FilterPreset::Table)
+          .col(FilterPreset::Owner)
+          .to_owned(),
+      )
+      .await?;
+    // 会议置顶的推荐滤镜，预设删除时由服务端置空
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .add_column(ColumnDef::new(Room::PinnedFilterId).integer().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .drop_column(Room::PinnedFilterId)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_table(Table::drop().table(FilterPreset::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum FilterPreset {
+  Table,
+  Id,
+  Owner,
+  Name,
+  Params,
+  Visibility,
+  RoomId,
+  CreatedAt,
+  UpdatedAt,
+}
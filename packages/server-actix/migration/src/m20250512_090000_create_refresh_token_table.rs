@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 修改密码、注销账号或退出全部设备时递增，使已签发的 access token 失效
+    manager
+      .alter_table(
+        Table::alter()
+          .table(User::Table)
+          .add_column(
+            ColumnDef::new(User::TokenVersion)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_table(
+        Table::create()
+          .table(RefreshToken::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(RefreshToken::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(RefreshToken::UserId).string().not_null())
+          .col(
+            ColumnDef::new(RefreshToken::TokenHash)
+              .string()
+              .not_null()
+              .unique_key(),
+          )
+          .col(
+            ColumnDef::new(RefreshToken::Revoked)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .col(ColumnDef::new(RefreshToken::ExpiresAt).date_time().not_null())
+          .col(ColumnDef::new(RefreshToken::CreatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-RefreshToken-user_id")
+              .from(RefreshToken::Table, RefreshToken::UserId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-RefreshToken-user_id")
+          .table(RefreshToken::Table)
+          .col(RefreshToken::UserId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(RefreshToken::Table).to_owned())
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(User::Table)
+          .drop_column(User::TokenVersion)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum RefreshToken {
+  Table,
+  Id,
+  UserId,
+  TokenHash,
+  Revoked,
+  ExpiresAt,
+  CreatedAt,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// (列, 默认值)，sqlite 每次只能添加一列
+const COLUMNS: [(User, &str); 3] = [
+  (User::DisplayName, ""),
+  (User::AvatarUrl, ""),
+  (User::Email, ""),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    for (col, default) in COLUMNS {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(User::Table)
+            .add_column(ColumnDef::new(col).string().not_null().default(default))
+            .to_owned(),
+        )
+        .await?;
+    }
+    // 为空表示未设置，按请求的 Accept-Language 返回
+    manager
+      .alter_table(
+        Table::alter()
+          .table(User::Table)
+          .add_column(ColumnDef::new(User::Locale).string().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    for col in COLUMNS.map(|x| x.0).into_iter().chain([User::Locale]) {
+      manager
+        .alter_table(
+          Table::alter()
+            .table(User::Table)
+            .drop_column(col)
+            .to_owned(),
+        )
+        .await?;
+    }
+    Ok(())
+  }
+}
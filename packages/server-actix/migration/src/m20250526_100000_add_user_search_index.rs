@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 被禁用的账号无法登录，也不会出现在用户搜索中
+    manager
+      .alter_table(
+        Table::alter()
+          .table(User::Table)
+          .add_column(
+            ColumnDef::new(User::IsDisabled)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-User-display_name")
+          .table(User::Table)
+          .col(User::DisplayName)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx-User-display_name")
+          .table(User::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(User::Table)
+          .drop_column(User::IsDisabled)
+          .to_owned(),
+      )
+      .await
+  }
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250202_072600_create_room_table::Room;
+use super::m20250202_115557_room_user_table::RoomUser;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(RoomUser::Table)
+          .add_column(
+            ColumnDef::new(RoomUserRole::Role)
+              .string()
+              .not_null()
+              .default("attendee"),
+          )
+          .to_owned(),
+      )
+      .await?;
+    // 已有会议的管理员成为主持人
+    manager
+      .exec_stmt(
+        Query::update()
+          .table(RoomUser::Table)
+          .value(RoomUserRole::Role, "host")
+          .and_where(
+            Expr::col(RoomUser::UserId).in_subquery(
+              Query::select()
+                .column(Room::Admin)
+                .from(Room::Table)
+                .and_where(
+                  Expr::col((Room::Table, Room::Id)).equals((RoomUser::Table, RoomUser::RoomId)),
+                )
+                .to_owned(),
+            ),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(RoomUser::Table)
+          .drop_column(RoomUserRole::Role)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum RoomUserRole {
+  Role,
+}
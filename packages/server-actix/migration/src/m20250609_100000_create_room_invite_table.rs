@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(RoomInvite::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(RoomInvite::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(RoomInvite::RoomId).integer().not_null())
+          .col(ColumnDef::new(RoomInvite::CreatedBy).string().not_null())
+          // 为空时不限制使用次数
+          .col(ColumnDef::new(RoomInvite::MaxUses).integer().null())
+          .col(
+            ColumnDef::new(RoomInvite::UseCount)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(RoomInvite::Lobby)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .col(
+            ColumnDef::new(RoomInvite::Revoked)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .col(ColumnDef::new(RoomInvite::ExpiresAt).date_time().not_null())
+          .col(ColumnDef::new(RoomInvite::CreatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-RoomInvite-room_id")
+              .from(RoomInvite::Table, RoomInvite::RoomId)
+              .to(Room::Table, Room::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-RoomInvite-created_by")
+              .from(RoomInvite::Table, RoomInvite::CreatedBy)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-RoomInvite-room_id")
+          .table(RoomInvite::Table)
+          .col(RoomInvite::RoomId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(RoomInvite::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum RoomInvite {
+  Table,
+  Id,
+  RoomId,
+  CreatedBy,
+  MaxUses,
+  UseCount,
+  Lobby,
+  Revoked,
+  ExpiresAt,
+  CreatedAt,
+}
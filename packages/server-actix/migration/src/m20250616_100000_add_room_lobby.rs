@@ -0,0 +1,112 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 开启后非主持人需经准入才能获取 room token
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .add_column(
+            ColumnDef::new(Room::LobbyEnabled)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_table(
+        Table::create()
+          .table(RoomAdmission::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(RoomAdmission::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(RoomAdmission::RoomId).integer().not_null())
+          // 注册用户为用户 id，访客为 guest- 开头的随机标识
+          .col(ColumnDef::new(RoomAdmission::Identity).string().not_null())
+          .col(ColumnDef::new(RoomAdmission::UserId).string().null())
+          .col(
+            ColumnDef::new(RoomAdmission::DisplayName)
+              .string()
+              .not_null()
+              .default(""),
+          )
+          .col(
+            ColumnDef::new(RoomAdmission::Status)
+              .string()
+              .not_null()
+              .default("pending"),
+          )
+          .col(ColumnDef::new(RoomAdmission::DecidedBy).string().null())
+          .col(ColumnDef::new(RoomAdmission::CreatedAt).date_time().not_null())
+          .col(ColumnDef::new(RoomAdmission::UpdatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-RoomAdmission-room_id")
+              .from(RoomAdmission::Table, RoomAdmission::RoomId)
+              .to(Room::Table, Room::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-RoomAdmission-user_id")
+              .from(RoomAdmission::Table, RoomAdmission::UserId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-RoomAdmission-room_id-identity")
+          .table(RoomAdmission::Table)
+          .col(RoomAdmission::RoomId)
+          .col(RoomAdmission::Identity)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(RoomAdmission::Table).to_owned())
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .drop_column(Room::LobbyEnabled)
+          .to_owned(),
+      )
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum RoomAdmission {
+  Table,
+  Id,
+  RoomId,
+  Identity,
+  UserId,
+  DisplayName,
+  Status,
+  DecidedBy,
+  CreatedAt,
+  UpdatedAt,
+}
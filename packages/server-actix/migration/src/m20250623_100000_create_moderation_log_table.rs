@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(ModerationLog::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(ModerationLog::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(ModerationLog::RoomId).integer().not_null())
+          .col(ColumnDef::new(ModerationLog::Actor).string().not_null())
+          .col(ColumnDef::new(ModerationLog::Action).string().not_null())
+          // LiveKit 中的 identity，访客不对应任何用户
+          .col(ColumnDef::new(ModerationLog::Target).string().not_null())
+          // 操作参数，json
+          .col(
+            ColumnDef::new(ModerationLog::Detail)
+              .text()
+              .not_null()
+              .default(""),
+          )
+          .col(ColumnDef::new(ModerationLog::CreatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-ModerationLog-room_id")
+              .from(ModerationLog::Table, ModerationLog::RoomId)
+              .to(Room::Table, Room::Id),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-ModerationLog-actor")
+              .from(ModerationLog::Table, ModerationLog::Actor)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-ModerationLog-room_id")
+          .table(ModerationLog::Table)
+          .col(ModerationLog::RoomId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(ModerationLog::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum ModerationLog {
+  Table,
+  Id,
+  RoomId,
+  Actor,
+  Action,
+  Target,
+  Detail,
+  CreatedAt,
+}
@@ -0,0 +1,123 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .create_table(
+        Table::create()
+          .table(MeetingSeries::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(MeetingSeries::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(MeetingSeries::Admin).string().not_null())
+          // RFC 5545 RRULE，不含 DTSTART
+          .col(ColumnDef::new(MeetingSeries::Rrule).string().not_null())
+          // 第一次会议的开始时间
+          .col(ColumnDef::new(MeetingSeries::StartTime).date_time().not_null())
+          // 每次会议的时长，秒
+          .col(ColumnDef::new(MeetingSeries::Duration).integer().not_null())
+          .col(
+            ColumnDef::new(MeetingSeries::LobbyEnabled)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .col(
+            ColumnDef::new(MeetingSeries::IsCanceled)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .col(ColumnDef::new(MeetingSeries::CreatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-MeetingSeries-admin")
+              .from(MeetingSeries::Table, MeetingSeries::Admin)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    // 每次会议对应一条 room，单独修改时间后仍以 occurrence_start 对应规则中的原始时间
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .add_column(ColumnDef::new(Room::SeriesId).integer().null())
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .add_column(ColumnDef::new(Room::OccurrenceStart).date_time().null())
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-Room-series_id")
+          .table(Room::Table)
+          .col(Room::SeriesId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx-Room-series_id")
+          .table(Room::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .drop_column(Room::OccurrenceStart)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .drop_column(Room::SeriesId)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .drop_table(Table::drop().table(MeetingSeries::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+pub enum MeetingSeries {
+  Table,
+  Id,
+  Admin,
+  Rrule,
+  StartTime,
+  Duration,
+  LobbyEnabled,
+  IsCanceled,
+  CreatedAt,
+}
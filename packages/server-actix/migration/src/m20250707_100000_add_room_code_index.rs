@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 会议取消或结束一段时间后释放会议号，释放后的会议号可再次分配
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .add_column(
+            ColumnDef::new(Room::CodeReleased)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          .to_owned(),
+      )
+      .await?;
+    let db = manager.get_connection();
+    db.execute_unprepared(
+      "UPDATE room SET code_released = 1 \
+       WHERE is_canceled OR end_time < datetime('now', '-1 day')",
+    )
+    .await?;
+    // 旧数据中重复的会议号只保留最新的会议
+    db.execute_unprepared(
+      "UPDATE room SET code_released = 1 WHERE NOT code_released AND id NOT IN \
+       (SELECT MAX(id) FROM room WHERE NOT code_released GROUP BY code)",
+    )
+    .await?;
+    // sea-query 不支持部分索引，直接使用 SQL
+    db.execute_unprepared(
+      "CREATE UNIQUE INDEX \"idx-Room-active_code\" ON room (code) WHERE NOT code_released",
+    )
+    .await
+    .and(Ok(()))
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx-Room-active_code")
+          .table(Room::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .drop_column(Room::CodeReleased)
+          .to_owned(),
+      )
+      .await
+  }
+}
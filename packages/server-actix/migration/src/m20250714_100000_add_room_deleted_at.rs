@@ -0,0 +1,32 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 软删除的时间，软删除的会议不再出现在列表中，但保留记录与转写
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .add_column(ColumnDef::new(Room::DeletedAt).date_time().null())
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .alter_table(
+        Table::alter()
+          .table(Room::Table)
+          .drop_column(Room::DeletedAt)
+          .to_owned(),
+      )
+      .await
+  }
+}
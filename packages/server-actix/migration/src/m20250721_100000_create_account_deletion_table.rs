@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 待注销的账号，宽限期结束后由后台任务清除个人数据
+    manager
+      .create_table(
+        Table::create()
+          .table(AccountDeletion::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(AccountDeletion::UserId)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          // 主持的会议的处理方式：transfer 或 archive
+          .col(
+            ColumnDef::new(AccountDeletion::Rooms)
+              .string()
+              .not_null()
+              .default("transfer"),
+          )
+          .col(
+            ColumnDef::new(AccountDeletion::RequestedAt)
+              .date_time()
+              .not_null(),
+          )
+          .col(ColumnDef::new(AccountDeletion::PurgeAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-AccountDeletion-user_id")
+              .from(AccountDeletion::Table, AccountDeletion::UserId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-AccountDeletion-purge_at")
+          .table(AccountDeletion::Table)
+          .col(AccountDeletion::PurgeAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(AccountDeletion::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum AccountDeletion {
+  Table,
+  UserId,
+  Rooms,
+  RequestedAt,
+  PurgeAt,
+}
@@ -0,0 +1,146 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 登录失败审计，用户 id 为请求中填写的值，不一定存在
+    manager
+      .create_table(
+        Table::create()
+          .table(LoginFailure::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(LoginFailure::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(LoginFailure::UserId).string().not_null())
+          .col(ColumnDef::new(LoginFailure::Ip).string().not_null())
+          // wrong_password | user_not_found | user_disabled | locked | rate_limited
+          .col(ColumnDef::new(LoginFailure::Reason).string().not_null())
+          .col(
+            ColumnDef::new(LoginFailure::CreatedAt)
+              .date_time()
+              .not_null(),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-LoginFailure-user_id-created_at")
+          .table(LoginFailure::Table)
+          .col(LoginFailure::UserId)
+          .col(LoginFailure::CreatedAt)
+          .to_owned(),
+      )
+      .await?;
+
+    // 按用户 id 的连续失败次数与锁定状态，锁定时长随锁定次数增长
+    manager
+      .create_table(
+        Table::create()
+          .table(LoginLockout::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(LoginLockout::UserId)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(
+            ColumnDef::new(LoginLockout::Failures)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .col(
+            ColumnDef::new(LoginLockout::LockCount)
+              .integer()
+              .not_null()
+              .default(0),
+          )
+          .col(ColumnDef::new(LoginLockout::LockedUntil).date_time().null())
+          .to_owned(),
+      )
+      .await?;
+
+    // 滑动窗口计数，配置为数据库存储时使用，多实例部署可共享
+    manager
+      .create_table(
+        Table::create()
+          .table(RateLimitHit::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(RateLimitHit::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(RateLimitHit::Key).string().not_null())
+          .col(
+            ColumnDef::new(RateLimitHit::CreatedAt)
+              .date_time()
+              .not_null(),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-RateLimitHit-key-created_at")
+          .table(RateLimitHit::Table)
+          .col(RateLimitHit::Key)
+          .col(RateLimitHit::CreatedAt)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(RateLimitHit::Table).to_owned())
+      .await?;
+    manager
+      .drop_table(Table::drop().table(LoginLockout::Table).to_owned())
+      .await?;
+    manager
+      .drop_table(Table::drop().table(LoginFailure::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum LoginFailure {
+  Table,
+  Id,
+  UserId,
+  Ip,
+  Reason,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LoginLockout {
+  Table,
+  UserId,
+  Failures,
+  LockCount,
+  LockedUntil,
+}
+
+#[derive(DeriveIden)]
+enum RateLimitHit {
+  Table,
+  Id,
+  Key,
+  CreatedAt,
+}
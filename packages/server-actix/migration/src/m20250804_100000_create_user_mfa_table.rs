@@ -0,0 +1,115 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // TOTP 两步验证，密钥加密保存，校验首个验证码后才启用
+    manager
+      .create_table(
+        Table::create()
+          .table(UserMfa::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(UserMfa::UserId)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(ColumnDef::new(UserMfa::Secret).string().not_null())
+          .col(
+            ColumnDef::new(UserMfa::Enabled)
+              .boolean()
+              .not_null()
+              .default(false),
+          )
+          // 最近一次使用的时间步，防止验证码重放
+          .col(
+            ColumnDef::new(UserMfa::LastStep)
+              .big_integer()
+              .not_null()
+              .default(0),
+          )
+          .col(ColumnDef::new(UserMfa::CreatedAt).date_time().not_null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-UserMfa-user_id")
+              .from(UserMfa::Table, UserMfa::UserId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+
+    // 恢复码只保存摘要，每个只能使用一次
+    manager
+      .create_table(
+        Table::create()
+          .table(MfaRecoveryCode::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(MfaRecoveryCode::Id)
+              .integer()
+              .not_null()
+              .primary_key()
+              .auto_increment(),
+          )
+          .col(ColumnDef::new(MfaRecoveryCode::UserId).string().not_null())
+          .col(
+            ColumnDef::new(MfaRecoveryCode::CodeHash)
+              .string()
+              .not_null(),
+          )
+          .col(ColumnDef::new(MfaRecoveryCode::UsedAt).date_time().null())
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-MfaRecoveryCode-user_id")
+              .from(MfaRecoveryCode::Table, MfaRecoveryCode::UserId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-MfaRecoveryCode-user_id")
+          .table(MfaRecoveryCode::Table)
+          .col(MfaRecoveryCode::UserId)
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(MfaRecoveryCode::Table).to_owned())
+      .await?;
+    manager
+      .drop_table(Table::drop().table(UserMfa::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum UserMfa {
+  Table,
+  UserId,
+  Secret,
+  Enabled,
+  LastStep,
+  CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MfaRecoveryCode {
+  Table,
+  Id,
+  UserId,
+  CodeHash,
+  UsedAt,
+}
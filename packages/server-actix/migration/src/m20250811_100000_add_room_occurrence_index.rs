@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250202_072600_create_room_table::Room;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 并发生成的重复会议只保留最早的一条，其余脱离规则并软删除
+    manager
+      .get_connection()
+      .execute_unprepared(
+        "UPDATE room SET occurrence_start = NULL, deleted_at = datetime('now'), \
+         code_released = 1 WHERE occurrence_start IS NOT NULL AND id NOT IN \
+         (SELECT MIN(id) FROM room WHERE occurrence_start IS NOT NULL \
+         GROUP BY series_id, occurrence_start)",
+      )
+      .await?;
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx-Room-series_id")
+          .table(Room::Table)
+          .to_owned(),
+      )
+      .await?;
+    // 同一系列的每一次会议只对应一条 room，按 series_id 查询也使用此索引
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-Room-series_occurrence")
+          .table(Room::Table)
+          .col(Room::SeriesId)
+          .col(Room::OccurrenceStart)
+          .unique()
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx-Room-series_occurrence")
+          .table(Room::Table)
+          .to_owned(),
+      )
+      .await?;
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-Room-series_id")
+          .table(Room::Table)
+          .col(Room::SeriesId)
+          .to_owned(),
+      )
+      .await
+  }
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 日历订阅地址中的 token，每个用户一个，只保存摘要，可单独重置或吊销
+    manager
+      .create_table(
+        Table::create()
+          .table(CalendarToken::Table)
+          .if_not_exists()
+          .col(
+            ColumnDef::new(CalendarToken::UserId)
+              .string()
+              .not_null()
+              .primary_key(),
+          )
+          .col(
+            ColumnDef::new(CalendarToken::TokenHash)
+              .string()
+              .not_null()
+              .unique_key(),
+          )
+          .col(
+            ColumnDef::new(CalendarToken::CreatedAt)
+              .date_time()
+              .not_null(),
+          )
+          .foreign_key(
+            ForeignKey::create()
+              .name("fk-CalendarToken-user_id")
+              .from(CalendarToken::Table, CalendarToken::UserId)
+              .to(User::Table, User::Id),
+          )
+          .to_owned(),
+      )
+      .await
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    manager
+      .drop_table(Table::drop().table(CalendarToken::Table).to_owned())
+      .await
+  }
+}
+
+#[derive(DeriveIden)]
+enum CalendarToken {
+  Table,
+  UserId,
+  TokenHash,
+  CreatedAt,
+}
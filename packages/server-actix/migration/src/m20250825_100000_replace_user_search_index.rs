@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20250120_000001_create_user_table::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    // 用户搜索改为不区分大小写的前缀匹配，按 lower() 建表达式索引，原索引不再使用
+    manager
+      .drop_index(
+        Index::drop()
+          .name("idx-User-display_name")
+          .table(User::Table)
+          .to_owned(),
+      )
+      .await?;
+    let db = manager.get_connection();
+    db.execute_unprepared("CREATE INDEX \"idx-User-lower_id\" ON \"user\" (lower(id))")
+      .await?;
+    db.execute_unprepared(
+      "CREATE INDEX \"idx-User-lower_display_name\" ON \"user\" (lower(display_name))",
+    )
+    .await
+    .and(Ok(()))
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    for name in ["idx-User-lower_id", "idx-User-lower_display_name"] {
+      manager
+        .drop_index(Index::drop().name(name).table(User::Table).to_owned())
+        .await?;
+    }
+    manager
+      .create_index(
+        Index::create()
+          .name("idx-User-display_name")
+          .table(User::Table)
+          .col(User::DisplayName)
+          .to_owned(),
+      )
+      .await
+  }
+}
@@ -0,0 +1,402 @@
+use std::io::{Cursor, Write};
+
+use actix_web::{
+  delete, get, http::header, post, web, HttpRequest, HttpResponse, Responder, Result,
+};
+use chrono::NaiveDateTime;
+use sea_orm::{sqlx::types::chrono::Utc, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use ts_rs::TS;
+use zip::{result::ZipResult, write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::api::user::{verify_user, UserProfile};
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::entities::{account_deletion, room, room_user, transcript_segment};
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::account::{AccountService, RoomHandover};
+use crate::services::recording::RecordingService;
+use crate::services::transcript::{to_plain_text, TranscriptService};
+use crate::services::user::UserService;
+
+fn to_timestamp(time: NaiveDateTime) -> f64 {
+  time.and_utc().timestamp() as f64
+}
+
+fn to_json<T: serde::Serialize>(x: &T) -> Vec<u8> {
+  serde_json::to_vec_pretty(x).unwrap_or_default()
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct DeleteUserReq {
+  /// 注销前需重新验证密码
+  pub password: String,
+  /// 主持的会议的处理方式，默认移交给其他成员
+  pub rooms: Option<RoomHandover>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct AccountDeletion {
+  pub requested_at: f64,
+  /// 到期后由后台任务清除数据，此前可撤销
+  pub purge_at: f64,
+  pub rooms: RoomHandover,
+}
+
+impl From<account_deletion::Model> for AccountDeletion {
+  fn from(x: account_deletion::Model) -> Self {
+    AccountDeletion {
+      requested_at: to_timestamp(x.requested_at),
+      purge_at: to_timestamp(x.purge_at),
+      rooms: RoomHandover::parse(&x.rooms),
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct AccountDeletionRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  /// 没有待处理的注销申请时为空
+  pub data: Option<AccountDeletion>,
+}
+
+/// 申请注销账号，宽限期结束后清除数据
+#[delete("/delete")]
+pub async fn delete_user(
+  req: HttpRequest,
+  body: web::Json<DeleteUserReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  verify_user(user_id.clone(), body.password.clone(), &data.db_conn).await?;
+  let deletion = AccountService::schedule_deletion(
+    &data.db_conn,
+    &user_id,
+    body.rooms.unwrap_or_default(),
+    Utc::now().naive_utc(),
+  )
+  .await?;
+  Ok(web::Json(AccountDeletionRes {
+    base: BaseResponse::success(locale, MsgCode::AccountDeletionScheduled),
+    data: Some(deletion.into()),
+  }))
+}
+
+#[get("/delete")]
+pub async fn get_account_deletion(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let deletion = AccountService::get_deletion(&data.db_conn, &user_id).await?;
+  Ok(web::Json(AccountDeletionRes {
+    base: BaseResponse::success(locale, MsgCode::AccountDeletionFetched),
+    data: deletion.map(AccountDeletion::from),
+  }))
+}
+
+#[post("/delete/cancel")]
+pub async fn cancel_account_deletion(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  if !AccountService::cancel_deletion(&data.db_conn, &user_id).await? {
+    return Err(AppError::NotFound(MsgCode::NoPendingDeletion.into()));
+  }
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::AccountDeletionCanceled,
+  )))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub enum ExportFormat {
+  #[default]
+  Json,
+  Zip,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct ExportQuery {
+  pub format: Option<ExportFormat>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct ExportedRoom {
+  pub id: i32,
+  pub code: String,
+  pub start_time: f64,
+  pub end_time: f64,
+  pub admin: String,
+  pub role: String,
+  /// 会议已被删除（归档）
+  pub deleted: bool,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct ExportedRecording {
+  pub room_id: i32,
+  pub start_time: f64,
+  pub end_time: Option<f64>,
+  pub status: String,
+  /// 完整的下载地址
+  pub url: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct ExportedSegment {
+  pub room_id: i32,
+  /// 相对于会议开始的毫秒数
+  #[ts(type = "number")]
+  pub start_offset: i64,
+  pub text: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct AccountExport {
+  pub profile: UserProfile,
+  pub rooms: Vec<ExportedRoom>,
+  pub recordings: Vec<ExportedRecording>,
+  /// 本人在各会议中的发言
+  pub transcripts: Vec<ExportedSegment>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct AccountExportRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<AccountExport>,
+}
+
+/// 将文件打包为 zip，返回压缩后的内容
+fn build_zip(files: Vec<(String, Vec<u8>)>) -> ZipResult<Vec<u8>> {
+  let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+  let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+  for (name, content) in files {
+    writer.start_file(name, options)?;
+    writer.write_all(&content)?;
+  }
+  Ok(writer.finish()?.into_inner())
+}
+
+/// 收集用户的个人数据：资料、参加过的会议（含已归档）、这些会议的录制与本人的转写发言
+async fn collect_export(
+  data: &AppState,
+  user_id: &str,
+) -> Result<(AccountExport, Vec<transcript_segment::Model>), AppError> {
+  let user = UserService::get_user(&data.db_conn, user_id.to_string())
+    .await
+    .map_err(|_| AppError::user_not_found())?;
+  let memberships = room_user::Entity::find()
+    .find_also_related(room::Entity)
+    .filter(room_user::Column::UserId.eq(user_id))
+    .order_by_asc(room::Column::StartTime)
+    .all(&data.db_conn)
+    .await?;
+  let mut rooms = vec![];
+  let mut recordings = vec![];
+  for (member, room) in memberships {
+    let Some(room) = room else {
+      continue;
+    };
+    for x in RecordingService::get_recordings_by_room_id(&data.db_conn, room.id).await? {
+      recordings.push(ExportedRecording {
+        room_id: x.room_id,
+        start_time: to_timestamp(x.start_time),
+        end_time: x.end_time.map(to_timestamp),
+        status: x.status,
+        url: format!(
+          "{}/{}",
+          data.s3_public_url.trim_end_matches('/'),
+          x.file_path
+        ),
+      });
+    }
+    rooms.push(ExportedRoom {
+      id: room.id,
+      code: room.code,
+      start_time: to_timestamp(room.start_time),
+      end_time: to_timestamp(room.end_time),
+      admin: room.admin,
+      role: member.role,
+      deleted: room.deleted_at.is_some(),
+    });
+  }
+  let segments = TranscriptService::get_segments_by_speaker(&data.db_conn, user_id).await?;
+  let export = AccountExport {
+    profile: UserProfile::from(user),
+    rooms,
+    recordings,
+    transcripts: segments
+      .iter()
+      .map(|x| ExportedSegment {
+        room_id: x.room_id,
+        start_offset: x.start_offset,
+        text: x.text.clone(),
+      })
+      .collect(),
+  };
+  Ok((export, segments))
+}
+
+/// 导出个人数据，`format=zip` 时以附件形式返回，转写按会议分为纯文本文件
+#[get("/export")]
+pub async fn export_account(
+  req: HttpRequest,
+  query: web::Query<ExportQuery>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<HttpResponse, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let (export, segments) = collect_export(&data, &user_id).await?;
+  if query.format.unwrap_or_default() == ExportFormat::Json {
+    return Ok(HttpResponse::Ok().json(AccountExportRes {
+      base: BaseResponse::success(locale, MsgCode::AccountDataExported),
+      data: Some(export),
+    }));
+  }
+
+  let mut files = vec![
+    ("profile.json".to_string(), to_json(&export.profile)),
+    ("rooms.json".to_string(), to_json(&export.rooms)),
+    ("recordings.json".to_string(), to_json(&export.recordings)),
+  ];
+  // 发言已按会议排序
+  for chunk in segments.chunk_by(|a, b| a.room_id == b.room_id) {
+    files.push((
+      format!("transcripts/room-{}.txt", chunk[0].room_id),
+      to_plain_text(chunk).into_bytes(),
+    ));
+  }
+  let body =
+    build_zip(files).map_err(|e| AppError::Internal(format!("build export zip failed: {e}")))?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/zip")
+      .insert_header((
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{user_id}-export.zip\""),
+      ))
+      .body(body),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use chrono::Duration;
+  use sea_orm::ActiveValue;
+  use serde_json::{json, Value};
+
+  use super::*;
+  use crate::api::user::get_user_scope;
+  use crate::services::account::DELETION_GRACE_DAYS;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+  };
+
+  #[actix_web::test]
+  async fn schedules_exports_and_purges_account() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    add_room_users(&db, 1, &["admin1", "alice"]).await;
+    UserService::change_password(&db, "alice", "secret")
+      .await
+      .unwrap();
+    TranscriptService::create_segments(
+      &db,
+      vec![transcript_segment::ActiveModel {
+        room_id: ActiveValue::Set(1),
+        speaker_id: ActiveValue::Set("alice".to_string()),
+        start_offset: ActiveValue::Set(0),
+        end_offset: ActiveValue::Set(1000),
+        text: ActiveValue::Set("大家好".to_string()),
+        language: ActiveValue::Set("zh".to_string()),
+        created_at: ActiveValue::Set(Utc::now().naive_utc()),
+        ..Default::default()
+      }],
+    )
+    .await
+    .unwrap();
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .wrap(from_fn(test_auth))
+        .service(get_user_scope()),
+    )
+    .await;
+    let request = |req: test::TestRequest| as_user(req, "alice").to_request();
+    let delete = |password: &str| {
+      request(
+        test::TestRequest::delete()
+          .uri("/api/user/delete")
+          .set_json(json!({ "password": password })),
+      )
+    };
+
+    let res = test::call_service(&app, delete("wrong")).await;
+    assert_eq!(res.status(), 400);
+    let res: Value = test::call_and_read_body_json(&app, delete("secret")).await;
+    let grace =
+      res["data"]["purge_at"].as_f64().unwrap() - res["data"]["requested_at"].as_f64().unwrap();
+    assert_eq!(grace, (DELETION_GRACE_DAYS * 86400) as f64);
+    assert_eq!(res["data"]["rooms"], "transfer");
+
+    // 宽限期内仍可导出数据
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      request(test::TestRequest::get().uri("/api/user/export")),
+    )
+    .await;
+    assert_eq!(res["data"]["rooms"][0]["id"], 1);
+    assert_eq!(res["data"]["transcripts"][0]["text"], "大家好");
+    let res = test::call_service(
+      &app,
+      request(test::TestRequest::get().uri("/api/user/export?format=zip")),
+    )
+    .await;
+    assert_eq!(
+      res.headers().get(header::CONTENT_TYPE).unwrap(),
+      "application/zip"
+    );
+    assert!(test::read_body(res).await.starts_with(b"PK"));
+
+    let cancel = || request(test::TestRequest::post().uri("/api/user/delete/cancel"));
+    assert_eq!(test::call_service(&app, cancel()).await.status(), 200);
+    assert_eq!(test::call_service(&app, cancel()).await.status(), 404);
+
+    test::call_service(&app, delete("secret")).await;
+    let now = Utc::now().naive_utc();
+    assert_eq!(AccountService::purge_due(&db, now).await.unwrap(), 0);
+    let later = now + Duration::days(DELETION_GRACE_DAYS + 1);
+    assert_eq!(AccountService::purge_due(&db, later).await.unwrap(), 1);
+    let user = UserService::get_user(&db, "alice".to_string())
+      .await
+      .unwrap();
+    assert!(user.is_disabled && user.password.is_empty());
+    assert!(TranscriptService::get_segments_by_speaker(&db, "alice")
+      .await
+      .unwrap()
+      .is_empty());
+    assert!(AccountService::get_deletion(&db, "alice")
+      .await
+      .unwrap()
+      .is_none());
+  }
+}
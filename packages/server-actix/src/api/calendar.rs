@@ -0,0 +1,282 @@
+use actix_web::{
+  delete, get, http::header, post, web, HttpRequest, HttpResponse, Responder, Result,
+};
+use sea_orm::{sqlx::types::chrono::Utc, LoaderTrait};
+use ts_rs::TS;
+
+use crate::api::room::authorize_room;
+use crate::api::user::UserSummary;
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::entities::{room, user};
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::auth::AuthService;
+use crate::services::calendar::{render_calendar, IcsEvent, IcsPerson};
+use crate::services::room_user::{RoomPermission, RoomUserService};
+use crate::services::user::UserService;
+
+async fn build_events(
+  data: &AppState,
+  locale: Locale,
+  rooms: Vec<room::Model>,
+) -> Result<Vec<IcsEvent>, AppError> {
+  let mut events = vec![];
+  for x in rooms {
+    let user_ids = RoomUserService::get_users_by_room_id(&data.db_conn, x.id)
+      .await?
+      .into_iter()
+      .map(|u| u.user_id)
+      .collect::<Vec<_>>();
+    let users = UserService::get_users(&data.db_conn, &user_ids).await?;
+    let person =
+      |u: &user::Model| IcsPerson::new(&u.id, &UserSummary::from(u.clone()).display_name, &u.email);
+    events.push(IcsEvent {
+      room_id: x.id,
+      start_time: x.start_time,
+      end_time: x.end_time,
+      summary: MsgCode::IcsSummary.render(locale, std::slice::from_ref(&x.code)),
+      description: MsgCode::IcsDescription.render(locale, std::slice::from_ref(&x.code)),
+      organizer: users.iter().find(|u| u.id == x.admin).map(person),
+      attendees: user_ids
+        .iter()
+        .filter_map(|id| users.iter().find(|u| u.id == *id))
+        .map(person)
+        .collect(),
+      canceled: x.is_canceled,
+    });
+  }
+  Ok(events)
+}
+
+fn ics_response(filename: &str, body: String) -> HttpResponse {
+  HttpResponse::Ok()
+    .content_type("text/calendar; charset=utf-8")
+    .insert_header((
+      header::CONTENT_DISPOSITION,
+      format!("attachment; filename=\"{filename}\""),
+    ))
+    .body(body)
+}
+
+/// 单次会议的 VEVENT，注册在 `/api/room` 下
+#[get("/{room_id}/ics")]
+pub async fn get_room_ics(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, _) = authorize_room(&data, room_id, &user_id, RoomPermission::Join).await?;
+  let filename = format!("meeting-{}.ics", room.code);
+  let events = build_events(&data, locale, vec![room]).await?;
+  Ok(ics_response(
+    &filename,
+    render_calendar(None, &events, Utc::now().naive_utc()),
+  ))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct CalendarFeed {
+  pub token: String,
+  /// 订阅地址的路径，需拼接服务端地址
+  pub path: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct CalendarFeedRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<CalendarFeed>,
+}
+
+/// 生成新的日历订阅地址，旧地址随即失效；退出全部设备后同样失效。注册在 `/api/user` 下
+#[post("/calendar")]
+pub async fn create_calendar_feed(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let token = AuthService::issue_calendar_token(&data.db_conn, &user_id).await?;
+  Ok(web::Json(CalendarFeedRes {
+    base: BaseResponse::success(locale, MsgCode::CalendarTokenIssued),
+    data: Some(CalendarFeed {
+      path: format!("/api/user/calendar.ics?token={token}"),
+      token,
+    }),
+  }))
+}
+
+#[delete("/calendar")]
+pub async fn revoke_calendar_feed(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  if !AuthService::revoke_calendar_token(&data.db_conn, &user_id).await? {
+    return Err(AppError::NotFound(MsgCode::NoCalendarFeed.into()));
+  }
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::CalendarTokenRevoked,
+  )))
+}
+
+#[derive(serde::Deserialize)]
+struct CalendarQuery {
+  token: String,
+}
+
+/// 用户参加的全部会议，供日历客户端订阅，无需登录
+#[get("/calendar.ics")]
+pub async fn get_calendar_ics(
+  query: web::Query<CalendarQuery>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let Some(user) = AuthService::verify_calendar_token(&data.db_conn, &query.token).await? else {
+    return Err(AppError::Unauthorized(MsgCode::CalendarTokenInvalid.into()));
+  };
+  let locale = user
+    .locale
+    .as_deref()
+    .and_then(Locale::from_tag)
+    .unwrap_or(locale);
+  let rooms = RoomUserService::get_rooms_by_user_id(&data.db_conn, user.id.clone())
+    .await?
+    .load_one(room::Entity, &data.db_conn)
+    .await?
+    .into_iter()
+    .flatten()
+    .filter(|x| x.deleted_at.is_none())
+    .collect::<Vec<_>>();
+  let events = build_events(&data, locale, rooms).await?;
+  Ok(ics_response(
+    "calendar.ics",
+    render_calendar(
+      Some(&MsgCode::IcsCalendarName.render(locale, &[])),
+      &events,
+      Utc::now().naive_utc(),
+    ),
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use serde_json::Value;
+
+  use super::*;
+  use crate::api::room::get_room_scope;
+  use crate::api::user::get_user_scope;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+  };
+
+  #[actix_web::test]
+  async fn exports_room_and_user_calendar() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_room(&db, 2, "").await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    add_room_users(&db, 1, &["admin1", "alice"]).await;
+    add_room_users(&db, 2, &["admin2", "alice"]).await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db)))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope())
+        .service(get_user_scope()),
+    )
+    .await;
+    let get = |uri: &str, user: &str| as_user(test::TestRequest::get().uri(uri), user).to_request();
+
+    let res = test::call_service(&app, get("/api/room/1/ics", "bob")).await;
+    assert_eq!(res.status(), 403);
+    let res = test::call_service(&app, get("/api/room/1/ics", "alice")).await;
+    assert_eq!(res.status(), 200);
+    assert!(res
+      .headers()
+      .get(header::CONTENT_TYPE)
+      .unwrap()
+      .to_str()
+      .unwrap()
+      .starts_with("text/calendar"));
+    let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+    assert_eq!(body.matches("BEGIN:VEVENT").count(), 1);
+    assert!(body.contains("ATTENDEE;CN=alice;"));
+    assert!(body.contains("000000001"));
+
+    let feed =
+      |method: test::TestRequest| as_user(method.uri("/api/user/calendar"), "alice").to_request();
+    let res: Value = test::call_and_read_body_json(&app, feed(test::TestRequest::post())).await;
+    let old_path = res["data"]["path"].as_str().unwrap().to_string();
+    let res: Value = test::call_and_read_body_json(&app, feed(test::TestRequest::post())).await;
+    let path = res["data"]["path"].as_str().unwrap().to_string();
+    let ics = |path: &str| test::TestRequest::get().uri(path).to_request();
+    let res = test::call_service(&app, ics(&path)).await;
+    assert_eq!(res.status(), 200);
+    let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+    assert_eq!(body.matches("BEGIN:VEVENT").count(), 2);
+    // 重新生成后旧地址失效，吊销后新地址也失效
+    let res = test::call_service(&app, ics(&old_path)).await;
+    assert_eq!(res.status(), 401);
+    let res = test::call_service(&app, feed(test::TestRequest::delete())).await;
+    assert_eq!(res.status(), 200);
+    let res = test::call_service(&app, ics(&path)).await;
+    assert_eq!(res.status(), 401);
+    let res = test::call_service(&app, feed(test::TestRequest::delete())).await;
+    assert_eq!(res.status(), 404);
+    let res: Value = test::call_and_read_body_json(&app, feed(test::TestRequest::post())).await;
+    let path = res["data"]["path"].as_str().unwrap().to_string();
+
+    // 未设置资料语言时按 Accept-Language，设置后以资料为准
+    let ics_in = |lang: &str| {
+      test::TestRequest::get()
+        .uri(&path)
+        .insert_header((header::ACCEPT_LANGUAGE, lang))
+        .to_request()
+    };
+    let body = test::call_and_read_body(&app, ics_in("en-US")).await;
+    assert!(String::from_utf8(body.to_vec())
+      .unwrap()
+      .contains("My meetings"));
+    let set_locale = |locale: &str| {
+      as_user(test::TestRequest::patch().uri("/api/user/me"), "alice")
+        .set_json(serde_json::json!({ "locale": locale }))
+        .to_request()
+    };
+    let res = test::call_service(&app, set_locale("zh-CN")).await;
+    assert_eq!(res.status(), 200);
+    let body = test::call_and_read_body(&app, ics_in("en-US")).await;
+    assert!(String::from_utf8(body.to_vec())
+      .unwrap()
+      .contains("我的会议"));
+    let res: Value = test::call_and_read_body_json(&app, set_locale("")).await;
+    assert!(res["data"]["locale"].is_null());
+    let body = test::call_and_read_body(&app, ics_in("en-US")).await;
+    assert!(String::from_utf8(body.to_vec())
+      .unwrap()
+      .contains("My meetings"));
+
+    // 退出全部设备后订阅地址失效
+    let res = test::call_service(
+      &app,
+      as_user(
+        test::TestRequest::post().uri("/api/user/logoutAll"),
+        "alice",
+      )
+      .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res = test::call_service(&app, ics(&path)).await;
+    assert_eq!(res.status(), 401);
+  }
+}
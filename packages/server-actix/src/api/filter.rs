@@ -0,0 +1,375 @@
+use actix_web::{delete, get, post, web, HttpRequest, Responder, Result, Scope};
+use sea_orm::{sqlx::types::chrono::Utc, ActiveValue};
+use ts_rs::TS;
+
+use crate::api::room::{authorize_room, find_room};
+use crate::common::{AppState, AuthClaims, BaseResponse, CssFilter};
+use crate::entities::filter_preset;
+use crate::error::AppError;
+use crate::i18n::{Locale, Message, MsgCode};
+use crate::services::filter_preset::{
+  visible_in_room, FilterPresetService, VISIBILITY_PRIVATE, VISIBILITY_PUBLIC, VISIBILITY_ROOM,
+};
+use crate::services::room_user::{RoomPermission, RoomUserService};
+
+const MAX_NAME_CHARS: usize = 32;
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/filter.ts")]
+pub struct FilterPresetNode {
+  pub id: i32,
+  pub owner: String,
+  pub name: String,
+  pub filter: CssFilter,
+  /// private | room | public
+  pub visibility: String,
+  pub room_id: Option<i32>,
+  pub updated_at: f64,
+}
+
+impl From<filter_preset::Model> for FilterPresetNode {
+  fn from(x: filter_preset::Model) -> Self {
+    FilterPresetNode {
+      id: x.id,
+      owner: x.owner,
+      name: x.name,
+      filter: serde_json::from_str(&x.params).unwrap_or_default(),
+      visibility: x.visibility,
+      room_id: x.room_id,
+      updated_at: x.updated_at.and_utc().timestamp() as f64,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/filter.ts")]
+pub struct FilterPresetRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<FilterPresetNode>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/filter.ts")]
+pub struct FilterPresetListRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<Vec<FilterPresetNode>>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/filter.ts")]
+pub struct FilterPresetReq {
+  pub name: String,
+  pub filter: CssFilter,
+  /// private | room | public
+  pub visibility: String,
+  /// visibility 为 room 时必填
+  pub room_id: Option<i32>,
+}
+
+fn preset_res(
+  locale: Locale,
+  code: MsgCode,
+  data: Option<filter_preset::Model>,
+) -> web::Json<FilterPresetRes> {
+  web::Json(FilterPresetRes {
+    base: BaseResponse::success(locale, code),
+    data: data.map(FilterPresetNode::from),
+  })
+}
+
+fn preset_not_found() -> AppError {
+  AppError::NotFound(MsgCode::FilterPresetNotFound.into())
+}
+
+/// 校验预设参数，返回规范化后的名称和共享的会议
+async fn check_preset_req(
+  data: &AppState,
+  user_id: &str,
+  body: &FilterPresetReq,
+) -> Result<(String, Option<i32>), AppError> {
+  let name = body.name.trim().to_string();
+  if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+    return Err(AppError::InvalidInput(Message::with_args(
+      MsgCode::InvalidPresetName,
+      vec![MAX_NAME_CHARS.to_string()],
+    )));
+  }
+  match (body.visibility.as_str(), body.room_id) {
+    (VISIBILITY_PRIVATE | VISIBILITY_PUBLIC, _) => Ok((name, None)),
+    (VISIBILITY_ROOM, Some(room_id)) => {
+      if !RoomUserService::is_room_member(&data.db_conn, room_id, user_id).await? {
+        return Err(AppError::Forbidden(MsgCode::NotMemberCannotShare.into()));
+      }
+      Ok((name, Some(room_id)))
+    }
+    (VISIBILITY_ROOM, None) => Err(AppError::InvalidInput(MsgCode::PresetRoomRequired.into())),
+    _ => Err(AppError::InvalidInput(
+      MsgCode::UnsupportedVisibility.into(),
+    )),
+  }
+}
+
+#[get("")]
+async fn get_presets(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let presets = FilterPresetService::get_visible_presets(&data.db_conn, &user_id).await?;
+  Ok(web::Json(FilterPresetListRes {
+    base: BaseResponse::success(locale, MsgCode::FilterPresetsFetched),
+    data: Some(presets.into_iter().map(FilterPresetNode::from).collect()),
+  }))
+}
+
+#[post("")]
+async fn create_preset(
+  body: web::Json<FilterPresetReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let (name, room_id) = check_preset_req(&data, &user_id, &body).await?;
+  let now = Utc::now().naive_utc();
+  let preset = FilterPresetService::create_preset(
+    &data.db_conn,
+    filter_preset::ActiveModel {
+      owner: ActiveValue::Set(user_id),
+      name: ActiveValue::Set(name),
+      params: ActiveValue::Set(serde_json::json!(body.filter.clone().clamp()).to_string()),
+      visibility: ActiveValue::Set(body.visibility.clone()),
+      room_id: ActiveValue::Set(room_id),
+      created_at: ActiveValue::Set(now),
+      updated_at: ActiveValue::Set(now),
+      ..Default::default()
+    },
+  )
+  .await?;
+  Ok(preset_res(
+    locale,
+    MsgCode::FilterPresetCreated,
+    Some(preset),
+  ))
+}
+
+/// 获取当前用户拥有的预设
+async fn get_own_preset(
+  data: &AppState,
+  user_id: &str,
+  id: i32,
+) -> Result<filter_preset::Model, AppError> {
+  match FilterPresetService::get_preset_by_id(&data.db_conn, id).await? {
+    Some(preset) if preset.owner == user_id => Ok(preset),
+    Some(_) => Err(AppError::Forbidden(MsgCode::NotPresetOwner.into())),
+    None => Err(preset_not_found()),
+  }
+}
+
+#[post("/{id}")]
+async fn update_preset(
+  path: web::Path<i32>,
+  body: web::Json<FilterPresetReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let preset = get_own_preset(&data, &user_id, path.into_inner()).await?;
+  let (name, room_id) = check_preset_req(&data, &user_id, &body).await?;
+  let preset = FilterPresetService::update_preset(
+    &data.db_conn,
+    filter_preset::ActiveModel {
+      id: ActiveValue::Set(preset.id),
+      name: ActiveValue::Set(name),
+      params: ActiveValue::Set(serde_json::json!(body.filter.clone().clamp()).to_string()),
+      visibility: ActiveValue::Set(body.visibility.clone()),
+      room_id: ActiveValue::Set(room_id),
+      updated_at: ActiveValue::Set(Utc::now().naive_utc()),
+      ..Default::default()
+    },
+  )
+  .await?;
+  FilterPresetService::unpin_if_hidden(&data.db_conn, &preset).await?;
+  Ok(preset_res(
+    locale,
+    MsgCode::FilterPresetUpdated,
+    Some(preset),
+  ))
+}
+
+#[delete("/{id}")]
+async fn delete_preset(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let preset = get_own_preset(&data, &user_id, path.into_inner()).await?;
+  FilterPresetService::delete_preset(&data.db_conn, preset.id).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::FilterPresetDeleted,
+  )))
+}
+
+pub fn get_filter_scope() -> Scope {
+  web::scope("/filters")
+    .service(get_presets)
+    .service(create_preset)
+    .service(update_preset)
+    .service(delete_preset)
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/filter.ts")]
+pub struct PinFilterReq {
+  /// 为空时取消置顶
+  pub preset_id: Option<i32>,
+}
+
+#[post("")]
+async fn pin_room_filter(
+  path: web::Path<i32>,
+  body: web::Json<PinFilterReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  authorize_room(&data, room_id, &user_id, RoomPermission::EditSchedule).await?;
+  let preset = match body.preset_id {
+    None => None,
+    Some(id) => match FilterPresetService::get_preset_by_id(&data.db_conn, id).await? {
+      Some(preset) if visible_in_room(&preset, room_id) => Some(preset),
+      Some(_) => {
+        return Err(AppError::Conflict(MsgCode::PresetNotPinnable.into()));
+      }
+      None => return Err(preset_not_found()),
+    },
+  };
+  FilterPresetService::pin_preset(&data.db_conn, room_id, body.preset_id).await?;
+  Ok(preset_res(locale, MsgCode::FilterPresetPinned, preset))
+}
+
+#[get("")]
+async fn get_room_filter(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  if !RoomUserService::is_room_member(&data.db_conn, room_id, &user_id).await? {
+    return Err(AppError::not_room_member());
+  }
+  let room = find_room(&data, room_id).await?;
+  let Some(preset_id) = room.pinned_filter_id else {
+    return Ok(preset_res(locale, MsgCode::NoPinnedFilter, None));
+  };
+  let preset = FilterPresetService::get_preset_by_id(&data.db_conn, preset_id).await?;
+  Ok(preset_res(locale, MsgCode::PinnedFilterFetched, preset))
+}
+
+pub fn get_room_filter_scope() -> Scope {
+  web::scope("/{room_id}/filter")
+    .service(pin_room_filter)
+    .service(get_room_filter)
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use serde_json::Value;
+
+  use super::*;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+  };
+
+  fn preset_req(visibility: &str, room_id: Option<i32>) -> FilterPresetReq {
+    FilterPresetReq {
+      name: " 怀旧 ".to_string(),
+      filter: CssFilter {
+        sepia: Some(2.0),
+        ..Default::default()
+      },
+      visibility: visibility.to_string(),
+      room_id,
+    }
+  }
+
+  #[actix_web::test]
+  async fn preset_visibility_and_room_pin() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    add_room_users(&db, 1, &["admin1", "alice"]).await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db)))
+        .wrap(from_fn(test_auth))
+        .service(get_filter_scope())
+        .service(get_room_filter_scope()),
+    )
+    .await;
+    let create = |user: &str, visibility: &str, room_id: Option<i32>| {
+      as_user(test::TestRequest::post().uri("/filters"), user)
+        .set_json(preset_req(visibility, room_id))
+        .to_request()
+    };
+    let pin = |preset_id: i64| {
+      as_user(test::TestRequest::post().uri("/1/filter"), "admin1")
+        .set_json(PinFilterReq {
+          preset_id: Some(preset_id as i32),
+        })
+        .to_request()
+    };
+    let get = |uri: &str, user: &str| as_user(test::TestRequest::get().uri(uri), user).to_request();
+    let delete = |id: i64, user: &str| {
+      as_user(
+        test::TestRequest::delete().uri(&format!("/filters/{id}")),
+        user,
+      )
+      .to_request()
+    };
+
+    let res: Value =
+      test::call_and_read_body_json(&app, create("bob", VISIBILITY_ROOM, Some(1))).await;
+    assert_eq!(res["ret"], -401);
+    let res: Value =
+      test::call_and_read_body_json(&app, create("alice", VISIBILITY_PRIVATE, None)).await;
+    assert_eq!(res["data"]["name"], "怀旧");
+    assert_eq!(res["data"]["filter"]["sepia"], 1.0);
+    let private_id = res["data"]["id"].as_i64().unwrap();
+    let res: Value =
+      test::call_and_read_body_json(&app, create("alice", VISIBILITY_ROOM, Some(1))).await;
+    let shared_id = res["data"]["id"].as_i64().unwrap();
+
+    let res: Value = test::call_and_read_body_json(&app, get("/filters", "admin1")).await;
+    assert_eq!(res["data"].as_array().unwrap().len(), 1);
+    let res: Value = test::call_and_read_body_json(&app, get("/filters", "bob")).await;
+    assert!(res["data"].as_array().unwrap().is_empty());
+
+    let res: Value = test::call_and_read_body_json(&app, pin(private_id)).await;
+    assert_eq!(res["ret"], -400);
+    let res: Value = test::call_and_read_body_json(&app, pin(shared_id)).await;
+    assert_eq!(res["ret"], 0);
+    let res: Value = test::call_and_read_body_json(&app, get("/1/filter", "alice")).await;
+    assert_eq!(res["data"]["id"], shared_id);
+
+    let res: Value = test::call_and_read_body_json(&app, delete(shared_id, "admin1")).await;
+    assert_eq!(res["ret"], -401);
+    let res: Value = test::call_and_read_body_json(&app, delete(shared_id, "alice")).await;
+    assert_eq!(res["ret"], 0);
+    let res: Value = test::call_and_read_body_json(&app, get("/1/filter", "alice")).await;
+    assert!(res["data"].is_null());
+  }
+}
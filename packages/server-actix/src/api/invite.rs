@@ -0,0 +1,462 @@
+use std::time::Duration;
+
+use actix_web::{delete, get, post, web, HttpRequest, Responder, Result, Scope};
+use livekit_api::access_token;
+use log::debug;
+use sea_orm::{sqlx::types::chrono::Utc, ActiveValue};
+use ts_rs::TS;
+
+use crate::api::room::{authorize_room, find_room};
+use crate::api::user::MAX_DISPLAY_NAME_CHARS;
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::entities::{room_admission, room_invite};
+use crate::error::AppError;
+use crate::i18n::{Locale, Message, MsgCode};
+use crate::services::credential::GUEST_ID_PREFIX;
+use crate::services::room_admission::{AdmissionStatus, RoomAdmissionService};
+use crate::services::room_invite::RoomInviteService;
+use crate::services::room_user::RoomPermission;
+
+/// 邀请默认有效期
+const DEFAULT_INVITE_TTL_SECS: u64 = 24 * 60 * 60;
+const MAX_INVITE_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct RoomInviteNode {
+  pub id: i32,
+  pub room_id: i32,
+  pub created_by: String,
+  /// 为 null 时不限次数
+  pub max_uses: Option<i32>,
+  pub use_count: i32,
+  pub lobby: bool,
+  pub revoked: bool,
+  pub expires_at: f64,
+  pub created_at: f64,
+}
+
+impl From<room_invite::Model> for RoomInviteNode {
+  fn from(x: room_invite::Model) -> Self {
+    RoomInviteNode {
+      id: x.id,
+      room_id: x.room_id,
+      created_by: x.created_by,
+      max_uses: x.max_uses,
+      use_count: x.use_count,
+      lobby: x.lobby,
+      revoked: x.revoked,
+      expires_at: x.expires_at.and_utc().timestamp() as f64,
+      created_at: x.created_at.and_utc().timestamp() as f64,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct CreateInviteReq {
+  /// 有效期，秒，默认一天，最长 30 天
+  #[ts(type = "number | null")]
+  pub expires_in: Option<u64>,
+  pub max_uses: Option<i32>,
+  /// 访客需等候主持人准入
+  pub lobby: Option<bool>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct CreatedInvite {
+  pub invite: RoomInviteNode,
+  /// 仅在创建时返回，由客户端拼接为邀请链接
+  pub token: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct CreateInviteRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<CreatedInvite>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct RoomInviteListRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<Vec<RoomInviteNode>>,
+}
+
+#[post("")]
+async fn create_invite(
+  path: web::Path<i32>,
+  body: web::Json<CreateInviteReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, _) =
+    authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  if room.is_canceled {
+    return Err(AppError::Conflict(MsgCode::RoomCanceled.into()));
+  }
+  let expires_in = body.expires_in.unwrap_or(DEFAULT_INVITE_TTL_SECS);
+  if !(60..=MAX_INVITE_TTL_SECS).contains(&expires_in) {
+    return Err(AppError::InvalidInput(Message::with_args(
+      MsgCode::InvalidInviteExpiry,
+      vec![(MAX_INVITE_TTL_SECS / 86400).to_string()],
+    )));
+  }
+  if body.max_uses.is_some_and(|x| x < 1) {
+    return Err(AppError::InvalidInput(MsgCode::InvalidInviteMaxUses.into()));
+  }
+  let now = Utc::now().naive_utc();
+  let invite = RoomInviteService::create_invite(
+    &data.db_conn,
+    room_invite::ActiveModel {
+      room_id: ActiveValue::Set(room.id),
+      created_by: ActiveValue::Set(user_id),
+      max_uses: ActiveValue::Set(body.max_uses),
+      use_count: ActiveValue::Set(0),
+      lobby: ActiveValue::Set(body.lobby.unwrap_or(false)),
+      revoked: ActiveValue::Set(false),
+      expires_at: ActiveValue::Set(now + Duration::from_secs(expires_in)),
+      created_at: ActiveValue::Set(now),
+      ..Default::default()
+    },
+  )
+  .await?;
+  let token = RoomInviteService::sign(&data.jwt_auth_secret, &invite)?;
+  Ok(web::Json(CreateInviteRes {
+    base: BaseResponse::success(locale, MsgCode::InviteCreated),
+    data: Some(CreatedInvite {
+      invite: RoomInviteNode::from(invite),
+      token,
+    }),
+  }))
+}
+
+#[get("")]
+async fn get_invites(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  let invites = RoomInviteService::get_invites_by_room_id(&data.db_conn, room_id).await?;
+  Ok(web::Json(RoomInviteListRes {
+    base: BaseResponse::success(locale, MsgCode::InvitesFetched),
+    data: Some(invites.into_iter().map(RoomInviteNode::from).collect()),
+  }))
+}
+
+#[delete("/{invite_id}")]
+async fn revoke_invite(
+  path: web::Path<(i32, i32)>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let (room_id, invite_id) = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  if !RoomInviteService::revoke_invite(&data.db_conn, room_id, invite_id).await? {
+    return Err(AppError::NotFound(MsgCode::InviteNotFound.into()));
+  }
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::InviteRevoked,
+  )))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct GuestJoinReq {
+  pub token: String,
+  pub display_name: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct GuestToken {
+  pub room_id: String,
+  pub identity: String,
+  pub status: AdmissionStatus,
+  /// 准入后返回
+  pub livekit_token: Option<String>,
+  /// 等候准入时返回，用于轮询准入状态
+  pub ticket: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct GuestTokenRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<GuestToken>,
+}
+
+fn guest_token_res(
+  data: &AppState,
+  locale: Locale,
+  room_id: i32,
+  identity: String,
+  display_name: &str,
+) -> Result<web::Json<GuestTokenRes>, AppError> {
+  let metadata = serde_json::json!({ "guest": true }).to_string();
+  let livekit_token =
+    access_token::AccessToken::with_api_key(&data.livekit_key, &data.livekit_secret)
+      .with_identity(&identity)
+      .with_name(display_name)
+      .with_metadata(&metadata)
+      .with_grants(RoomInviteService::guest_grants(room_id.to_string()))
+      .to_jwt()
+      .map_err(|e| {
+        debug!("livekit to_jwt err: {:?}", e);
+        AppError::Upstream(MsgCode::RoomTokenFailed.into())
+      })?;
+  Ok(web::Json(GuestTokenRes {
+    base: BaseResponse::success(locale, MsgCode::GuestJoined),
+    data: Some(GuestToken {
+      room_id: room_id.to_string(),
+      identity,
+      status: AdmissionStatus::Admitted,
+      livekit_token: Some(livekit_token),
+      ticket: None,
+    }),
+  }))
+}
+
+fn guest_waiting_res(
+  locale: Locale,
+  admission: room_admission::Model,
+  ticket: String,
+) -> web::Json<GuestTokenRes> {
+  web::Json(GuestTokenRes {
+    base: BaseResponse::success(locale, MsgCode::LobbyWaiting),
+    data: Some(GuestToken {
+      room_id: admission.room_id.to_string(),
+      identity: admission.identity,
+      status: AdmissionStatus::Pending,
+      livekit_token: None,
+      ticket: Some(ticket),
+    }),
+  })
+}
+
+/// 访客凭邀请链接换取 LiveKit token，无需登录；需等候时返回轮询凭证
+#[post("/join")]
+async fn join_as_guest(
+  body: web::Json<GuestJoinReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let display_name = body.display_name.trim();
+  if display_name.is_empty() || display_name.chars().count() > MAX_DISPLAY_NAME_CHARS {
+    return Err(AppError::InvalidInput(Message::with_args(
+      MsgCode::InvalidGuestName,
+      vec![MAX_DISPLAY_NAME_CHARS.to_string()],
+    )));
+  }
+  let Some(claims) = RoomInviteService::verify(&data.jwt_auth_secret, &body.token) else {
+    return Err(AppError::Forbidden(MsgCode::InviteInvalid.into()));
+  };
+  let room = find_room(&data, claims.room).await?;
+  if room.is_canceled {
+    return Err(AppError::Conflict(MsgCode::RoomCanceled.into()));
+  }
+  let Some(invite) = RoomInviteService::consume_invite(&data.db_conn, &claims).await? else {
+    return Err(AppError::Forbidden(MsgCode::InviteInvalid.into()));
+  };
+  let identity = format!(
+    "{GUEST_ID_PREFIX}{}",
+    rand::random::<[u8; 8]>()
+      .iter()
+      .map(|b| format!("{b:02x}"))
+      .collect::<String>()
+  );
+  if !(invite.lobby || room.lobby_enabled) {
+    return guest_token_res(&data, locale, room.id, identity, display_name);
+  }
+  let admission =
+    RoomAdmissionService::request(&data.db_conn, room.id, &identity, None, display_name).await?;
+  let ticket = RoomAdmissionService::sign_ticket(&data.jwt_auth_secret, &admission)?;
+  Ok(guest_waiting_res(locale, admission, ticket))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/invite.ts")]
+pub struct GuestTicketReq {
+  pub ticket: String,
+}
+
+/// 等候中的访客轮询准入状态，准入后返回 LiveKit token
+#[post("/lobby")]
+async fn poll_guest_admission(
+  body: web::Json<GuestTicketReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let Some(claims) = RoomAdmissionService::verify_ticket(&data.jwt_auth_secret, &body.ticket)
+  else {
+    return Err(AppError::Forbidden(MsgCode::InviteInvalid.into()));
+  };
+  let room = find_room(&data, claims.room).await?;
+  if room.is_canceled {
+    return Err(AppError::Conflict(MsgCode::RoomCanceled.into()));
+  }
+  let Some(admission) =
+    RoomAdmissionService::get_admission(&data.db_conn, room.id, &claims.identity)
+      .await?
+      .filter(|x| x.id == claims.admission)
+  else {
+    return Err(AppError::NotFound(MsgCode::AdmissionNotFound.into()));
+  };
+  match AdmissionStatus::parse(&admission.status) {
+    AdmissionStatus::Pending => Ok(guest_waiting_res(locale, admission, body.ticket.clone())),
+    AdmissionStatus::Denied => Err(AppError::Forbidden(MsgCode::LobbyDenied.into())),
+    AdmissionStatus::Admitted => {
+      guest_token_res(&data, locale, room.id, claims.identity, &claims.name)
+    }
+  }
+}
+
+pub fn get_invite_scope() -> Scope {
+  web::scope("/{room_id}/invite")
+    .service(create_invite)
+    .service(get_invites)
+    .service(revoke_invite)
+}
+
+pub fn get_guest_scope() -> Scope {
+  web::scope("/api/guest")
+    .service(join_as_guest)
+    .service(poll_guest_admission)
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use livekit_api::access_token::TokenVerifier;
+  use serde_json::{json, Value};
+
+  use super::*;
+  use crate::api::room::get_room_scope;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+    TEST_LIVEKIT_KEY, TEST_LIVEKIT_SECRET,
+  };
+
+  #[actix_web::test]
+  async fn guest_invites_respect_limits_and_revocation() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    add_room_users(&db, 1, &["admin1", "alice"]).await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db)))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope())
+        .service(get_guest_scope()),
+    )
+    .await;
+    let create = |user: &str, body: Value| {
+      as_user(test::TestRequest::post().uri("/api/room/1/invite"), user)
+        .set_json(body)
+        .to_request()
+    };
+    let join = |token: &Value| {
+      test::TestRequest::post()
+        .uri("/api/guest/join")
+        .set_json(json!({ "token": token, "display_name": " Guest " }))
+        .to_request()
+    };
+
+    let res: Value = test::call_and_read_body_json(&app, create("alice", json!({}))).await;
+    assert_eq!(res["ret"], -401);
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      create("admin1", json!({ "max_uses": 1, "lobby": true })),
+    )
+    .await;
+    assert_eq!(res["ret"], 0);
+    let token = res["data"]["token"].clone();
+
+    let res: Value = test::call_and_read_body_json(&app, join(&token)).await;
+    assert_eq!(res["ret"], 0);
+    assert_eq!(res["data"]["status"], "pending");
+    assert!(res["data"]["livekit_token"].is_null());
+    let ticket = res["data"]["ticket"].clone();
+    let poll = || {
+      test::TestRequest::post()
+        .uri("/api/guest/lobby")
+        .set_json(json!({ "ticket": ticket }))
+        .to_request()
+    };
+    let res: Value = test::call_and_read_body_json(&app, poll()).await;
+    assert_eq!(res["data"]["status"], "pending");
+
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      as_user(test::TestRequest::get().uri("/api/room/1/lobby"), "admin1").to_request(),
+    )
+    .await;
+    let admission_id = res["data"][0]["id"].as_i64().unwrap();
+    let res = test::call_service(
+      &app,
+      as_user(
+        test::TestRequest::post().uri(&format!("/api/room/1/lobby/{admission_id}/admit")),
+        "admin1",
+      )
+      .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res: Value = test::call_and_read_body_json(&app, poll()).await;
+    assert_eq!(res["data"]["status"], "admitted");
+    let claims = TokenVerifier::with_api_key(TEST_LIVEKIT_KEY, TEST_LIVEKIT_SECRET)
+      .verify(res["data"]["livekit_token"].as_str().unwrap())
+      .unwrap();
+    assert!(claims.sub.starts_with("guest-"));
+    assert_eq!(claims.name, "Guest");
+    assert!(claims.video.room_join && claims.video.can_publish);
+    assert!(!claims.video.room_admin && !claims.video.can_update_own_metadata);
+
+    // 次数用尽
+    let res = test::call_service(&app, join(&token)).await;
+    assert_eq!(res.status(), 403);
+
+    let res: Value = test::call_and_read_body_json(&app, create("admin1", json!({}))).await;
+    let token = res["data"]["token"].clone();
+    let invite_id = res["data"]["invite"]["id"].as_i64().unwrap();
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      as_user(
+        test::TestRequest::delete().uri(&format!("/api/room/1/invite/{invite_id}")),
+        "admin1",
+      )
+      .to_request(),
+    )
+    .await;
+    assert_eq!(res["ret"], 0);
+    let res = test::call_service(&app, join(&token)).await;
+    assert_eq!(res.status(), 403);
+    let res = test::call_service(&app, join(&json!("not-a-token"))).await;
+    assert_eq!(res.status(), 403);
+
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      as_user(test::TestRequest::get().uri("/api/room/1/invite"), "admin1").to_request(),
+    )
+    .await;
+    assert_eq!(res["data"].as_array().unwrap().len(), 2);
+    assert_eq!(res["data"][0]["use_count"], 0);
+    assert_eq!(res["data"][1]["use_count"], 1);
+  }
+}
@@ -0,0 +1,265 @@
+use actix_web::{http::header, post, web, HttpRequest, Responder, Scope};
+use livekit_api::access_token::TokenVerifier;
+use livekit_api::webhooks::WebhookReceiver;
+use livekit_protocol::{EgressInfo, EgressStatus, WebhookEvent};
+use log::{debug, info};
+use sea_orm::{DatabaseConnection, DbErr};
+
+use crate::common::{AppState, BaseResponse};
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::recording::RecordingService;
+use crate::services::room::RoomService;
+
+/// LiveKit 中 room 的 name 即为会议 id，见 `record_room` 与 `get_room_token`
+fn parse_room_id(room_name: &str) -> Option<i32> {
+  room_name.parse().ok()
+}
+
+fn is_egress_stopped(status: i32) -> bool {
+  matches!(
+    EgressStatus::try_from(status),
+    Ok(
+      EgressStatus::EgressEnding
+        | EgressStatus::EgressComplete
+        | EgressStatus::EgressFailed
+        | EgressStatus::EgressAborted
+        | EgressStatus::EgressLimitReached
+    )
+  )
+}
+
+async fn handle_egress(
+  dbconn: &DatabaseConnection,
+  event: &str,
+  egress: EgressInfo,
+) -> Result<(), DbErr> {
+  let Some(room_id) = parse_room_id(&egress.room_name) else {
+    debug!(
+      "egress {} of unknown room {}",
+      egress.egress_id, egress.room_name
+    );
+    return Ok(());
+  };
+  RecordingService::sync_egress(dbconn, room_id, &egress).await?;
+  if event == "egress_ended" || is_egress_stopped(egress.status) {
+    if !egress.error.is_empty() {
+      info!(
+        "egress {} of room {room_id} ended: {}",
+        egress.egress_id, egress.error
+      );
+    }
+    return RoomService::end_egress(dbconn, room_id, &egress.egress_id).await;
+  }
+  RoomService::start_egress(dbconn, room_id, &egress.egress_id).await
+}
+
+pub async fn handle_webhook_event(
+  dbconn: &DatabaseConnection,
+  event: WebhookEvent,
+) -> Result<(), DbErr> {
+  debug!("livekit webhook: {} {}", event.event, event.id);
+  match event.event.as_str() {
+    "egress_started" | "egress_updated" | "egress_ended" => {
+      let Some(egress) = event.egress_info else {
+        return Ok(());
+      };
+      handle_egress(dbconn, &event.event, egress).await
+    }
+    // 会议结束时 LiveKit 会停止所有 egress，但不保证逐个发送 egress_ended
+    "room_finished" => {
+      let Some(room_id) = event.room.and_then(|r| parse_room_id(&r.name)) else {
+        return Ok(());
+      };
+      RoomService::clear_egress(dbconn, room_id).await
+    }
+    "room_started" | "participant_joined" | "participant_left" => {
+      debug!(
+        "room {:?} participant {:?}: {}",
+        event.room.map(|r| r.name),
+        event.participant.map(|p| p.identity),
+        event.event
+      );
+      Ok(())
+    }
+    _ => Ok(()),
+  }
+}
+
+#[post("/webhook")]
+async fn receive_webhook(
+  body: String,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let auth_token = req
+    .headers()
+    .get(header::AUTHORIZATION)
+    .and_then(|v| v.to_str().ok())
+    .unwrap_or_default();
+  let receiver = WebhookReceiver::new(TokenVerifier::with_api_key(
+    &data.livekit_key,
+    &data.livekit_secret,
+  ));
+  let event = match receiver.receive(&body, auth_token) {
+    Ok(event) => event,
+    Err(e) => {
+      debug!("invalid livekit webhook: {:?}", e);
+      return Err(AppError::Unauthorized(MsgCode::InvalidWebhook.into()));
+    }
+  };
+  // 返回非 2xx 时 LiveKit 会重试投递
+  handle_webhook_event(&data.db_conn, event).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::WebhookReceived,
+  )))
+}
+
+pub fn get_livekit_scope() -> Scope {
+  web::scope("/api/livekit").service(receive_webhook)
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{http::StatusCode, test, App};
+  use livekit_api::access_token::AccessToken;
+  use openssl::{base64::encode_block, sha::sha256};
+
+  use super::*;
+  use crate::test_utils::{
+    create_room, setup_db, test_state, TEST_LIVEKIT_KEY, TEST_LIVEKIT_SECRET,
+  };
+
+  const EGRESS_STARTED: &str = include_str!("../../tests/fixtures/livekit/egress_started.json");
+  const EGRESS_UPDATED: &str = include_str!("../../tests/fixtures/livekit/egress_updated.json");
+  const EGRESS_ENDED: &str = include_str!("../../tests/fixtures/livekit/egress_ended.json");
+  const EGRESS_FAILED: &str = include_str!("../../tests/fixtures/livekit/egress_failed.json");
+  const ROOM_FINISHED: &str = include_str!("../../tests/fixtures/livekit/room_finished.json");
+  const PARTICIPANT_JOINED: &str =
+    include_str!("../../tests/fixtures/livekit/participant_joined.json");
+
+  const EGRESS_ID: &str = "EG_k8Fq2ZwYdPhv";
+
+  fn sign(body: &str) -> String {
+    AccessToken::with_api_key(TEST_LIVEKIT_KEY, TEST_LIVEKIT_SECRET)
+      .with_sha256(&encode_block(&sha256(body.as_bytes())))
+      .to_jwt()
+      .unwrap()
+  }
+
+  async fn replay(dbconn: &DatabaseConnection, payload: &str) {
+    let event: WebhookEvent = serde_json::from_str(payload).unwrap();
+    handle_webhook_event(dbconn, event).await.unwrap();
+  }
+
+  #[actix_web::test]
+  async fn egress_lifecycle_syncs_room() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+
+    replay(&db, EGRESS_STARTED).await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, EGRESS_ID);
+
+    replay(&db, EGRESS_UPDATED).await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, EGRESS_ID);
+
+    replay(&db, EGRESS_ENDED).await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, "");
+
+    // 重复投递不会重复记录录像
+    replay(&db, EGRESS_ENDED).await;
+    let recordings = RecordingService::get_recordings_by_room_id(&db, 1)
+      .await
+      .unwrap();
+    assert_eq!(recordings.len(), 1);
+    assert_eq!(recordings[0].egress_id, EGRESS_ID);
+    assert_eq!(recordings[0].status, "complete");
+    assert_eq!(recordings[0].file_path, "1/2025-04-12T083012.mp4");
+    assert_eq!(recordings[0].size, 96531877);
+    assert_eq!(recordings[0].duration, 603634);
+    assert!(recordings[0].end_time.is_some());
+  }
+
+  #[actix_web::test]
+  async fn failed_egress_unlocks_room() {
+    let db = setup_db().await;
+    create_room(&db, 1, EGRESS_ID).await;
+
+    replay(&db, EGRESS_FAILED).await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, "");
+    let recording = RecordingService::get_recording_by_egress_id(&db, EGRESS_ID)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(recording.status, "failed");
+    assert_eq!(recording.file_path, "");
+  }
+
+  #[actix_web::test]
+  async fn stale_egress_does_not_clear_current() {
+    let db = setup_db().await;
+    create_room(&db, 1, "EG_newer").await;
+
+    replay(&db, EGRESS_ENDED).await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, "EG_newer");
+  }
+
+  #[actix_web::test]
+  async fn room_finished_clears_egress() {
+    let db = setup_db().await;
+    create_room(&db, 1, EGRESS_ID).await;
+
+    replay(&db, PARTICIPANT_JOINED).await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, EGRESS_ID);
+
+    replay(&db, ROOM_FINISHED).await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, "");
+  }
+
+  #[actix_web::test]
+  async fn webhook_requires_valid_signature() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .service(get_livekit_scope()),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+      .uri("/api/livekit/webhook")
+      .insert_header((header::AUTHORIZATION, sign(EGRESS_ENDED)))
+      .set_payload(EGRESS_STARTED)
+      .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+      .uri("/api/livekit/webhook")
+      .set_payload(EGRESS_STARTED)
+      .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+      .uri("/api/livekit/webhook")
+      .insert_header((header::AUTHORIZATION, sign(EGRESS_STARTED)))
+      .insert_header((header::CONTENT_TYPE, "application/webhook+json"))
+      .set_payload(EGRESS_STARTED)
+      .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, EGRESS_ID);
+  }
+}
@@ -0,0 +1,235 @@
+use actix_web::{get, post, web, HttpRequest, Responder, Result, Scope};
+use ts_rs::TS;
+
+use crate::api::room::{authorize_room, find_room};
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::entities::room_admission;
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::room_admission::{AdmissionStatus, RoomAdmissionService};
+use crate::services::room_user::RoomPermission;
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct AdmissionNode {
+  pub id: i32,
+  pub room_id: i32,
+  pub identity: String,
+  /// 访客为 null
+  pub user_id: Option<String>,
+  pub display_name: String,
+  pub status: AdmissionStatus,
+  pub created_at: f64,
+  pub updated_at: f64,
+}
+
+impl From<room_admission::Model> for AdmissionNode {
+  fn from(x: room_admission::Model) -> Self {
+    AdmissionNode {
+      id: x.id,
+      room_id: x.room_id,
+      identity: x.identity,
+      user_id: x.user_id,
+      display_name: x.display_name,
+      status: AdmissionStatus::parse(&x.status),
+      created_at: x.created_at.and_utc().timestamp() as f64,
+      updated_at: x.updated_at.and_utc().timestamp() as f64,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct AdmissionRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<AdmissionNode>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct AdmissionListRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<Vec<AdmissionNode>>,
+}
+
+#[get("")]
+async fn get_admissions(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  let admissions = RoomAdmissionService::get_admissions_by_room_id(&data.db_conn, room_id).await?;
+  Ok(web::Json(AdmissionListRes {
+    base: BaseResponse::success(locale, MsgCode::AdmissionsFetched),
+    data: Some(admissions.into_iter().map(AdmissionNode::from).collect()),
+  }))
+}
+
+/// 等候中的与会者轮询自己的准入状态
+#[get("/me")]
+async fn get_my_admission(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let room = find_room(&data, room_id).await?;
+  let Some(admission) =
+    RoomAdmissionService::get_admission(&data.db_conn, room.id, &user_id).await?
+  else {
+    return Err(AppError::NotFound(MsgCode::AdmissionNotFound.into()));
+  };
+  Ok(web::Json(AdmissionRes {
+    base: BaseResponse::success(locale, MsgCode::AdmissionFetched),
+    data: Some(AdmissionNode::from(admission)),
+  }))
+}
+
+async fn decide(
+  data: &AppState,
+  req: &HttpRequest,
+  path: (i32, i32),
+  status: AdmissionStatus,
+) -> Result<room_admission::Model, AppError> {
+  let (room_id, admission_id) = path;
+  let user_id = AuthClaims::user_id(req)?;
+  authorize_room(data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  RoomAdmissionService::decide(&data.db_conn, room_id, admission_id, status, &user_id)
+    .await?
+    .ok_or(AppError::NotFound(MsgCode::AdmissionNotFound.into()))
+}
+
+#[post("/{admission_id}/admit")]
+async fn admit(
+  path: web::Path<(i32, i32)>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let admission = decide(&data, &req, path.into_inner(), AdmissionStatus::Admitted).await?;
+  Ok(web::Json(AdmissionRes {
+    base: BaseResponse::success(locale, MsgCode::ParticipantAdmitted),
+    data: Some(AdmissionNode::from(admission)),
+  }))
+}
+
+#[post("/{admission_id}/deny")]
+async fn deny(
+  path: web::Path<(i32, i32)>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let admission = decide(&data, &req, path.into_inner(), AdmissionStatus::Denied).await?;
+  Ok(web::Json(AdmissionRes {
+    base: BaseResponse::success(locale, MsgCode::ParticipantDenied),
+    data: Some(AdmissionNode::from(admission)),
+  }))
+}
+
+pub fn get_lobby_scope() -> Scope {
+  web::scope("/{room_id}/lobby")
+    .service(get_admissions)
+    .service(get_my_admission)
+    .service(admit)
+    .service(deny)
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use serde_json::{json, Value};
+
+  use crate::api::room::get_room_scope;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+  };
+
+  use super::*;
+
+  #[actix_web::test]
+  async fn lobby_holds_members_until_admitted() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    add_room_users(&db, 1, &["admin1", "alice", "bob"]).await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db)))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope()),
+    )
+    .await;
+    let get = |uri: &str, user: &str| as_user(test::TestRequest::get().uri(uri), user).to_request();
+    let post = |uri: &str, user: &str| as_user(test::TestRequest::post().uri(uri), user);
+
+    // 普通与会者不能开启等候室
+    let res = test::call_service(
+      &app,
+      post("/api/room/update/1", "alice")
+        .set_json(json!({ "lobby_enabled": true }))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 403);
+    let res = test::call_service(
+      &app,
+      post("/api/room/update/1", "admin1")
+        .set_json(json!({ "lobby_enabled": true }))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+
+    let token = "/api/room/roomToken/000000001";
+    let res: Value = test::call_and_read_body_json(&app, get(token, "admin1")).await;
+    assert!(res["data"]["livekit_token"].is_string());
+    for user in ["alice", "bob"] {
+      let res: Value = test::call_and_read_body_json(&app, get(token, user)).await;
+      assert_eq!(res["ret"], 0);
+      assert!(res["data"].is_null());
+      assert_eq!(res["admission"]["status"], "pending");
+    }
+
+    let res = test::call_service(&app, get("/api/room/1/lobby", "alice")).await;
+    assert_eq!(res.status(), 403);
+    let res: Value = test::call_and_read_body_json(&app, get("/api/room/1/lobby", "admin1")).await;
+    let ids: Vec<i64> = res["data"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|x| x["id"].as_i64().unwrap())
+      .collect();
+    assert_eq!(ids.len(), 2);
+    let res = test::call_service(
+      &app,
+      post(&format!("/api/room/1/lobby/{}/admit", ids[0]), "admin1").to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res = test::call_service(
+      &app,
+      post(&format!("/api/room/1/lobby/{}/deny", ids[1]), "admin1").to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+
+    let res: Value =
+      test::call_and_read_body_json(&app, get("/api/room/1/lobby/me", "alice")).await;
+    assert_eq!(res["data"]["status"], "admitted");
+    let res: Value = test::call_and_read_body_json(&app, get(token, "alice")).await;
+    assert!(res["data"]["livekit_token"].is_string());
+    assert_eq!(res["admission"]["status"], "admitted");
+    let res = test::call_service(&app, get(token, "bob")).await;
+    assert_eq!(res.status(), 403);
+  }
+}
@@ -0,0 +1,378 @@
+use actix_web::{get, post, web, HttpRequest, Responder, Result};
+use sea_orm::sqlx::types::chrono::Utc;
+use ts_rs::TS;
+
+use crate::api::user::{client_ip, login_res, throttled, verify_user};
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::auth::AuthService;
+use crate::services::mfa::{base32_encode, otpauth_uri, MfaService};
+use crate::services::rate_limit::REASON_WRONG_MFA_CODE;
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct MfaStatus {
+  pub enabled: bool,
+  /// 未使用的恢复码数量
+  #[ts(type = "number")]
+  pub recovery_codes_left: u64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct MfaStatusRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: MfaStatus,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct MfaSetup {
+  /// base32 编码的密钥，无法扫码时手动输入
+  pub secret: String,
+  pub otpauth_uri: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct MfaSetupRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: MfaSetup,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct MfaCodeReq {
+  /// 身份验证器中的 6 位验证码
+  pub code: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct RecoveryCodesRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  /// 明文只返回这一次
+  pub data: Vec<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct DisableMfaReq {
+  pub password: String,
+  /// 验证码或恢复码
+  pub code: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct MfaLoginReq {
+  pub mfa_token: String,
+  /// 验证码或恢复码
+  pub code: String,
+}
+
+fn invalid_code() -> AppError {
+  AppError::InvalidInput(MsgCode::InvalidMfaCode.into())
+}
+
+#[get("/mfa")]
+pub async fn get_mfa_status(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let enabled = MfaService::is_enabled(&data.db_conn, &user_id).await?;
+  let recovery_codes_left = MfaService::recovery_codes_left(&data.db_conn, &user_id).await?;
+  Ok(web::Json(MfaStatusRes {
+    base: BaseResponse::success(locale, MsgCode::MfaStatusFetched),
+    data: MfaStatus {
+      enabled,
+      recovery_codes_left,
+    },
+  }))
+}
+
+/// 生成新密钥，需调用 `/mfa/enable` 校验验证码后才生效
+#[post("/mfa/setup")]
+pub async fn setup_mfa(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let Some(secret) = MfaService::setup(
+    &data.db_conn,
+    &data.mfa_key,
+    &user_id,
+    Utc::now().naive_utc(),
+  )
+  .await?
+  else {
+    return Err(AppError::Conflict(MsgCode::MfaAlreadyEnabled.into()));
+  };
+  Ok(web::Json(MfaSetupRes {
+    base: BaseResponse::success(locale, MsgCode::MfaSetupStarted),
+    data: MfaSetup {
+      secret: base32_encode(&secret),
+      otpauth_uri: otpauth_uri(&user_id, &secret),
+    },
+  }))
+}
+
+#[post("/mfa/enable")]
+pub async fn enable_mfa(
+  req: HttpRequest,
+  body: web::Json<MfaCodeReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let Some(mfa) = MfaService::get(&data.db_conn, &user_id).await? else {
+    return Err(AppError::NotFound(MsgCode::MfaNotSetUp.into()));
+  };
+  if mfa.enabled {
+    return Err(AppError::Conflict(MsgCode::MfaAlreadyEnabled.into()));
+  }
+  let codes = MfaService::enable(
+    &data.db_conn,
+    &data.mfa_key,
+    &mfa,
+    &body.code,
+    Utc::now().naive_utc(),
+  )
+  .await?
+  .ok_or_else(invalid_code)?;
+  Ok(web::Json(RecoveryCodesRes {
+    base: BaseResponse::success(locale, MsgCode::MfaEnabled),
+    data: codes,
+  }))
+}
+
+#[post("/mfa/disable")]
+pub async fn disable_mfa(
+  req: HttpRequest,
+  body: web::Json<DisableMfaReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  verify_user(user_id.clone(), body.password.clone(), &data.db_conn).await?;
+  let now = Utc::now().naive_utc();
+  if !MfaService::verify(&data.db_conn, &data.mfa_key, &user_id, &body.code, now).await? {
+    return Err(invalid_code());
+  }
+  MfaService::disable(&data.db_conn, &user_id).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::MfaDisabled,
+  )))
+}
+
+#[post("/mfa/recoveryCodes")]
+pub async fn regenerate_recovery_codes(
+  req: HttpRequest,
+  body: web::Json<MfaCodeReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let now = Utc::now().naive_utc();
+  if !MfaService::verify(&data.db_conn, &data.mfa_key, &user_id, &body.code, now).await? {
+    return Err(invalid_code());
+  }
+  let codes = MfaService::regenerate_recovery_codes(&data.db_conn, &user_id).await?;
+  Ok(web::Json(RecoveryCodesRes {
+    base: BaseResponse::success(locale, MsgCode::RecoveryCodesRegenerated),
+    data: codes,
+  }))
+}
+
+/// 登录第二步，校验 `/login` 返回的凭证与验证码后签发令牌
+#[post("/login/mfa")]
+pub async fn login_mfa(
+  req: HttpRequest,
+  body: web::Json<MfaLoginReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let Some(user_model) =
+    AuthService::verify_mfa_token(&data.db_conn, &data.jwt_auth_secret, &body.mfa_token).await
+  else {
+    return Err(AppError::Unauthorized(MsgCode::MfaChallengeExpired.into()));
+  };
+  let (ip, now) = (
+    client_ip(&req, &data.trusted_proxies),
+    Utc::now().naive_utc(),
+  );
+  let guard = &data.login_guard;
+  if let Some(x) = guard
+    .check_login(&data.db_conn, &ip, &user_model.id, now)
+    .await?
+  {
+    return Err(throttled(x));
+  }
+  if !MfaService::verify(
+    &data.db_conn,
+    &data.mfa_key,
+    &user_model.id,
+    &body.code,
+    now,
+  )
+  .await?
+  {
+    guard
+      .login_failed(
+        &data.db_conn,
+        &ip,
+        &user_model.id,
+        REASON_WRONG_MFA_CODE,
+        now,
+      )
+      .await?;
+    return Err(invalid_code());
+  }
+  guard.login_succeeded(&data.db_conn, &user_model.id).await?;
+  let token = AuthService::issue_tokens(&data.db_conn, &data.jwt_auth_secret, &user_model).await?;
+  Ok(login_res(token, locale, MsgCode::LoginSucceeded))
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{
+    middleware::from_fn,
+    test::{call_service, init_service, read_body_json, TestRequest},
+    App,
+  };
+  use serde_json::{json, Value};
+
+  use super::*;
+  use crate::api::user::get_user_scope;
+  use crate::services::mfa::{decrypt_secret, totp, RECOVERY_CODE_COUNT, TOTP_STEP_SECS};
+  use crate::test_utils::{as_user, setup_db, test_auth, test_state};
+
+  #[actix_web::test]
+  async fn enables_mfa_and_requires_code_on_login() {
+    let db = setup_db().await;
+    let state = test_state(db.clone());
+    let key = state.mfa_key;
+    let app = init_service(
+      App::new()
+        .app_data(web::Data::new(state))
+        .wrap(from_fn(test_auth))
+        .service(get_user_scope()),
+    )
+    .await;
+    let post = |uri: &str, body: Value| {
+      as_user(TestRequest::post().uri(uri), "alice")
+        .set_json(body)
+        .to_request()
+    };
+    let res = call_service(
+      &app,
+      TestRequest::put()
+        .uri("/api/user/create")
+        .set_json(json!({ "id": "alice", "password": "Secret-123" }))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+
+    let res = call_service(&app, post("/api/user/mfa/setup", json!({}))).await;
+    assert_eq!(res.status(), 200);
+    let res: Value = read_body_json(res).await;
+    assert!(res["data"]["otpauth_uri"]
+      .as_str()
+      .unwrap()
+      .starts_with("otpauth://totp/omeeting:alice?"));
+    // 数据库中只保存密文
+    let mfa = MfaService::get(&db, "alice").await.unwrap().unwrap();
+    assert_ne!(mfa.secret, res["data"]["secret"]);
+    let secret = decrypt_secret(&key, &mfa.secret).unwrap();
+    let code = |step: i64| format!("{:06}", totp(&secret, step).unwrap());
+    let step = Utc::now().timestamp() / TOTP_STEP_SECS;
+
+    let res = call_service(
+      &app,
+      post("/api/user/mfa/enable", json!({ "code": "000000x" })),
+    )
+    .await;
+    assert_eq!(res.status(), 400);
+    let res = call_service(
+      &app,
+      post("/api/user/mfa/enable", json!({ "code": code(step) })),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res: Value = read_body_json(res).await;
+    let codes = res["data"].as_array().unwrap().clone();
+    assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+
+    let login = call_service(
+      &app,
+      TestRequest::post()
+        .uri("/api/user/login")
+        .set_json(json!({ "id": "alice", "password": "Secret-123" }))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(login.status(), 200);
+    let login: Value = read_body_json(login).await;
+    assert!(login["data"].is_null());
+    assert_eq!(login["msg_code"], "mfa_required");
+    let mfa_token = login["mfa"]["mfa_token"].as_str().unwrap();
+    let second_step = |code: &Value| {
+      TestRequest::post()
+        .uri("/api/user/login/mfa")
+        .set_json(json!({ "mfa_token": mfa_token, "code": code }))
+        .to_request()
+    };
+    // 启用时用过的验证码不能再次使用
+    let used = MfaService::get(&db, "alice")
+      .await
+      .unwrap()
+      .unwrap()
+      .last_step;
+    let res = call_service(&app, second_step(&json!(code(used)))).await;
+    assert_eq!(res.status(), 400);
+    let res = call_service(&app, second_step(&codes[0])).await;
+    assert_eq!(res.status(), 200);
+    let res: Value = read_body_json(res).await;
+    assert!(res["data"]["auth_token"].is_string());
+    let res = call_service(&app, second_step(&codes[0])).await;
+    assert_eq!(res.status(), 400);
+    let res = call_service(
+      &app,
+      TestRequest::post()
+        .uri("/api/user/login/mfa")
+        .set_json(json!({ "mfa_token": "bad", "code": codes[1] }))
+        .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 401);
+
+    let res = call_service(
+      &app,
+      as_user(TestRequest::get().uri("/api/user/mfa"), "alice").to_request(),
+    )
+    .await;
+    let res: Value = read_body_json(res).await;
+    assert_eq!(res["data"]["enabled"], true);
+    assert_eq!(res["data"]["recovery_codes_left"], RECOVERY_CODE_COUNT - 1);
+
+    let res = call_service(
+      &app,
+      post(
+        "/api/user/mfa/disable",
+        json!({ "password": "Secret-123", "code": codes[1].as_str().unwrap().to_uppercase() }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    assert!(!MfaService::is_enabled(&db, "alice").await.unwrap());
+  }
+}
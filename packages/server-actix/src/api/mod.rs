@@ -1,2 +1,14 @@
+pub mod account;
+pub mod calendar;
+pub mod filter;
+pub mod invite;
+pub mod livekit;
+pub mod lobby;
+pub mod mfa;
+pub mod moderation;
 pub mod room;
+pub mod schedule;
+pub mod series;
+pub mod summary;
+pub mod transcript;
 pub mod user;
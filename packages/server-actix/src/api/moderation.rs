@@ -0,0 +1,640 @@
+use actix_web::{delete, get, post, web, HttpRequest, Responder, Result, Scope};
+use livekit_api::services::room::UpdateParticipantOptions;
+use livekit_api::services::{ServiceError, TwirpError, TwirpErrorCode};
+use livekit_protocol::{ParticipantInfo, TrackInfo, TrackSource, TrackType};
+use log::debug;
+use ts_rs::TS;
+
+use crate::api::room::authorize_room;
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::entities::{moderation_log, room};
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::moderation::{ModerationAction, ModerationService};
+use crate::services::room_user::{RoomPermission, RoomRole, RoomUserService};
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct TrackNode {
+  pub sid: String,
+  pub name: String,
+  /// audio、video、data
+  pub kind: String,
+  /// camera、microphone、screen_share 等
+  pub source: String,
+  pub muted: bool,
+}
+
+impl From<TrackInfo> for TrackNode {
+  fn from(x: TrackInfo) -> Self {
+    TrackNode {
+      kind: TrackType::try_from(x.r#type)
+        .map(|t| t.as_str_name().to_lowercase())
+        .unwrap_or_default(),
+      source: TrackSource::try_from(x.source)
+        .map(|s| s.as_str_name().to_lowercase())
+        .unwrap_or_default(),
+      sid: x.sid,
+      name: x.name,
+      muted: x.muted,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ParticipantPermissionNode {
+  pub can_subscribe: bool,
+  pub can_publish: bool,
+  pub can_publish_data: bool,
+  pub can_update_metadata: bool,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ParticipantNode {
+  pub sid: String,
+  pub identity: String,
+  pub name: String,
+  pub metadata: String,
+  pub joined_at: f64,
+  pub is_publisher: bool,
+  pub permission: Option<ParticipantPermissionNode>,
+  pub tracks: Vec<TrackNode>,
+}
+
+impl From<ParticipantInfo> for ParticipantNode {
+  fn from(x: ParticipantInfo) -> Self {
+    ParticipantNode {
+      sid: x.sid,
+      identity: x.identity,
+      name: x.name,
+      metadata: x.metadata,
+      joined_at: x.joined_at as f64,
+      is_publisher: x.is_publisher,
+      permission: x.permission.map(|p| ParticipantPermissionNode {
+        can_subscribe: p.can_subscribe,
+        can_publish: p.can_publish,
+        can_publish_data: p.can_publish_data,
+        can_update_metadata: p.can_update_metadata,
+      }),
+      tracks: x.tracks.into_iter().map(TrackNode::from).collect(),
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ParticipantListRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<Vec<ParticipantNode>>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ParticipantRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<ParticipantNode>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct TrackRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<TrackNode>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ModerationLogNode {
+  pub id: i32,
+  pub room_id: i32,
+  pub actor: String,
+  /// 取值见 `ModerationAction`
+  pub action: String,
+  pub target: String,
+  /// 操作参数，json
+  pub detail: String,
+  pub created_at: f64,
+}
+
+impl From<moderation_log::Model> for ModerationLogNode {
+  fn from(x: moderation_log::Model) -> Self {
+    ModerationLogNode {
+      id: x.id,
+      room_id: x.room_id,
+      actor: x.actor,
+      action: x.action,
+      target: x.target,
+      detail: x.detail,
+      created_at: x.created_at.and_utc().timestamp() as f64,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ModerationLogListRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<Vec<ModerationLogNode>>,
+}
+
+fn livekit_err(e: ServiceError) -> AppError {
+  match e {
+    ServiceError::Twirp(TwirpError::Twirp(code)) if code.code == TwirpErrorCode::NOT_FOUND => {
+      AppError::NotFound(MsgCode::ParticipantNotFound.into())
+    }
+    e => {
+      debug!("livekit room service err: {:?}", e);
+      AppError::Upstream(MsgCode::ModerationFailed.into())
+    }
+  }
+}
+
+/// 可以管理自己；主持人只能由本人管理，联席主持人只能由主持人管理
+pub(crate) async fn check_target(
+  data: &AppState,
+  room: &room::Model,
+  user_id: &str,
+  role: RoomRole,
+  identity: &str,
+) -> Result<(), AppError> {
+  if identity == user_id {
+    return Ok(());
+  }
+  match RoomUserService::get_role(&data.db_conn, room, identity).await? {
+    Some(RoomRole::Host) => Err(AppError::Forbidden(MsgCode::CannotModerateHost.into())),
+    Some(RoomRole::CoHost) if role != RoomRole::Host => {
+      Err(AppError::Forbidden(MsgCode::CannotModerateCoHost.into()))
+    }
+    _ => Ok(()),
+  }
+}
+
+/// 在调用 LiveKit 之前写入审计记录，写入失败时不执行操作，返回记录 id
+async fn audit(
+  data: &AppState,
+  room_id: i32,
+  actor: &str,
+  target: &str,
+  entries: Vec<(ModerationAction, serde_json::Value)>,
+) -> Result<Vec<i32>, AppError> {
+  Ok(ModerationService::log_all(&data.db_conn, room_id, actor, target, entries).await?)
+}
+
+/// LiveKit 操作失败时撤销已写入的审计记录
+async fn settle<T>(
+  data: &AppState,
+  log_ids: &[i32],
+  res: Result<T, ServiceError>,
+) -> Result<T, AppError> {
+  if res.is_err() {
+    if let Err(e) = ModerationService::discard(&data.db_conn, log_ids).await {
+      debug!("discard moderation log err: {:?}", e);
+    }
+  }
+  res.map_err(livekit_err)
+}
+
+#[get("/participants")]
+async fn get_participants(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, _) = authorize_room(&data, room_id, &user_id, RoomPermission::Join).await?;
+  let participants = data
+    .livekit_room_client
+    .list_participants(&room.id.to_string())
+    .await
+    .map_err(livekit_err)?;
+  Ok(web::Json(ParticipantListRes {
+    base: BaseResponse::success(locale, MsgCode::ParticipantsFetched),
+    data: Some(
+      participants
+        .into_iter()
+        .map(ParticipantNode::from)
+        .collect(),
+    ),
+  }))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct MuteTrackReq {
+  pub track_sid: String,
+  pub muted: bool,
+}
+
+#[post("/participants/{identity}/mute")]
+async fn mute_track(
+  path: web::Path<(i32, String)>,
+  body: web::Json<MuteTrackReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let (room_id, identity) = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, role) =
+    authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  check_target(&data, &room, &user_id, role, &identity).await?;
+  let (action, code) = if body.muted {
+    (ModerationAction::Mute, MsgCode::ParticipantMuted)
+  } else {
+    (ModerationAction::Unmute, MsgCode::ParticipantUnmuted)
+  };
+  let log_ids = audit(
+    &data,
+    room.id,
+    &user_id,
+    &identity,
+    vec![(action, serde_json::json!({ "track_sid": body.track_sid }))],
+  )
+  .await?;
+  let res = data
+    .livekit_room_client
+    .mute_published_track(&room.id.to_string(), &identity, &body.track_sid, body.muted)
+    .await;
+  let track = settle(&data, &log_ids, res).await?;
+  Ok(web::Json(TrackRes {
+    base: BaseResponse::success(locale, code),
+    data: Some(TrackNode::from(track)),
+  }))
+}
+
+#[delete("/participants/{identity}")]
+async fn remove_participant(
+  path: web::Path<(i32, String)>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let (room_id, identity) = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, role) =
+    authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  check_target(&data, &room, &user_id, role, &identity).await?;
+  let log_ids = audit(
+    &data,
+    room.id,
+    &user_id,
+    &identity,
+    vec![(ModerationAction::Remove, serde_json::json!({}))],
+  )
+  .await?;
+  let res = data
+    .livekit_room_client
+    .remove_participant(&room.id.to_string(), &identity)
+    .await;
+  settle(&data, &log_ids, res).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::ParticipantRemoved,
+  )))
+}
+
+/// 未设置的字段保持不变
+#[derive(serde::Deserialize, serde::Serialize, TS, Default)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ParticipantPermissionReq {
+  pub can_subscribe: Option<bool>,
+  pub can_publish: Option<bool>,
+  pub can_publish_data: Option<bool>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct UpdateParticipantReq {
+  pub permission: Option<ParticipantPermissionReq>,
+  /// LiveKit 不支持清空，传空字符串时保持不变
+  pub metadata: Option<String>,
+}
+
+#[post("/participants/{identity}/update")]
+async fn update_participant(
+  path: web::Path<(i32, String)>,
+  body: web::Json<UpdateParticipantReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let (room_id, identity) = path.into_inner();
+  if body.permission.is_none() && body.metadata.as_deref().unwrap_or("").is_empty() {
+    return Err(AppError::InvalidInput(
+      MsgCode::EmptyParticipantUpdate.into(),
+    ));
+  }
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, role) =
+    authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  check_target(&data, &room, &user_id, role, &identity).await?;
+  let room_name = room.id.to_string();
+  // LiveKit 会整体替换权限，先取当前权限再合并
+  let permission = match &body.permission {
+    Some(req) => {
+      let current = data
+        .livekit_room_client
+        .get_participant(&room_name, &identity)
+        .await
+        .map_err(livekit_err)?;
+      let mut permission = current.permission.unwrap_or_default();
+      if let Some(x) = req.can_subscribe {
+        permission.can_subscribe = x;
+      }
+      if let Some(x) = req.can_publish {
+        permission.can_publish = x;
+      }
+      if let Some(x) = req.can_publish_data {
+        permission.can_publish_data = x;
+      }
+      Some(permission)
+    }
+    None => None,
+  };
+  let mut entries = vec![];
+  if let Some(permission) = &body.permission {
+    entries.push((
+      ModerationAction::UpdatePermission,
+      serde_json::to_value(permission).unwrap_or_default(),
+    ));
+  }
+  if let Some(metadata) = body.metadata.as_deref().filter(|x| !x.is_empty()) {
+    entries.push((
+      ModerationAction::UpdateMetadata,
+      serde_json::json!({ "metadata": metadata }),
+    ));
+  }
+  let log_ids = audit(&data, room.id, &user_id, &identity, entries).await?;
+  let res = data
+    .livekit_room_client
+    .update_participant(
+      &room_name,
+      &identity,
+      UpdateParticipantOptions {
+        metadata: body.metadata.clone().unwrap_or_default(),
+        permission,
+        ..Default::default()
+      },
+    )
+    .await;
+  let participant = settle(&data, &log_ids, res).await?;
+  Ok(web::Json(ParticipantRes {
+    base: BaseResponse::success(locale, MsgCode::ParticipantUpdated),
+    data: Some(ParticipantNode::from(participant)),
+  }))
+}
+
+#[get("/logs")]
+async fn get_moderation_logs(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  let logs = ModerationService::get_logs_by_room_id(&data.db_conn, room_id).await?;
+  Ok(web::Json(ModerationLogListRes {
+    base: BaseResponse::success(locale, MsgCode::ModerationLogsFetched),
+    data: Some(logs.into_iter().map(ModerationLogNode::from).collect()),
+  }))
+}
+
+pub fn get_moderation_scope() -> Scope {
+  web::scope("/{room_id}/moderation")
+    .service(get_participants)
+    .service(mute_track)
+    .service(remove_participant)
+    .service(update_participant)
+    .service(get_moderation_logs)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::{middleware::from_fn, test, App};
+  use serde_json::{json, Value};
+
+  use crate::api::room::get_room_scope;
+  use crate::services::livekit::MockRoomClient;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+  };
+
+  use super::*;
+
+  #[actix_web::test]
+  async fn moderation_requires_role_and_is_audited() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    add_room_users(&db, 1, &["admin1", "alice", "bob"]).await;
+    let mut state = test_state(db);
+    let livekit = Arc::new(MockRoomClient::with_participants(
+      "1",
+      &["admin1", "alice", "bob", "carol"],
+    ));
+    state.livekit_room_client = livekit.clone();
+    let db = state.db_conn.clone();
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(state))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope()),
+    )
+    .await;
+    let post = |uri: &str, user: &str, body: Value| {
+      as_user(test::TestRequest::post().uri(uri), user)
+        .set_json(body)
+        .to_request()
+    };
+    let mute = json!({ "track_sid": "TR_x", "muted": true });
+
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/1/moderation/participants/bob/mute",
+        "alice",
+        mute.clone(),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 403);
+    let res = test::call_service(
+      &app,
+      as_user(
+        test::TestRequest::get().uri("/api/room/1/moderation/logs"),
+        "alice",
+      )
+      .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 403);
+
+    // 联席主持人不能管理主持人
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/role/1",
+        "admin1",
+        json!({ "user_id": "alice", "role": "co_host" }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res = test::call_service(
+      &app,
+      as_user(
+        test::TestRequest::delete().uri("/api/room/1/moderation/participants/admin1"),
+        "alice",
+      )
+      .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 403);
+    // 联席主持人之间不能互相管理
+    create_user(&db, "carol").await;
+    add_room_users(&db, 1, &["carol"]).await;
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/role/1",
+        "admin1",
+        json!({ "user_id": "carol", "role": "co_host" }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/1/moderation/participants/carol/mute",
+        "alice",
+        mute.clone(),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 403);
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/1/moderation/participants/bob/update",
+        "alice",
+        json!({}),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 400);
+
+    // 操作失败时不记录
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/1/moderation/participants/dave/mute",
+        "alice",
+        mute.clone(),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 404);
+    let logs = ModerationService::get_logs_by_room_id(&db, 1)
+      .await
+      .unwrap();
+    assert!(logs.is_empty());
+
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      post(
+        "/api/room/1/moderation/participants/bob/mute",
+        "alice",
+        json!({ "track_sid": "TR_bob", "muted": true }),
+      ),
+    )
+    .await;
+    assert_eq!(res["data"]["muted"], true);
+    assert_eq!(res["data"]["source"], "microphone");
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      post(
+        "/api/room/1/moderation/participants/bob/update",
+        "alice",
+        json!({ "permission": { "can_publish": false }, "metadata": "muted by host" }),
+      ),
+    )
+    .await;
+    assert_eq!(res["data"]["metadata"], "muted by host");
+    assert_eq!(res["data"]["permission"]["can_publish"], false);
+    assert_eq!(res["data"]["permission"]["can_subscribe"], true);
+    let res = test::call_service(
+      &app,
+      as_user(
+        test::TestRequest::delete().uri("/api/room/1/moderation/participants/bob"),
+        "alice",
+      )
+      .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let get = |uri: &str| as_user(test::TestRequest::get().uri(uri), "admin1").to_request();
+    let res: Value =
+      test::call_and_read_body_json(&app, get("/api/room/1/moderation/participants")).await;
+    let identities = res["data"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|x| x["identity"].as_str().unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(identities, ["admin1", "alice", "carol"]);
+
+    // 主持人可以管理联席主持人
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/1/moderation/participants/carol/mute",
+        "admin1",
+        json!({ "track_sid": "TR_carol", "muted": true }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+
+    let res: Value = test::call_and_read_body_json(&app, get("/api/room/1/moderation/logs")).await;
+    let actions = res["data"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|x| {
+        format!(
+          "{} {} {}",
+          x["actor"].as_str().unwrap(),
+          x["action"].as_str().unwrap(),
+          x["target"].as_str().unwrap()
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      actions,
+      [
+        "admin1 mute carol",
+        "alice remove bob",
+        "alice update_metadata bob",
+        "alice update_permission bob",
+        "alice mute bob",
+      ]
+    );
+    assert!(livekit.rooms.lock().unwrap()["1"]
+      .iter()
+      .all(|x| x.identity != "bob"));
+  }
+}
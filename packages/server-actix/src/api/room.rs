@@ -1,23 +1,67 @@
-use actix_web::{
-  delete, error, get, post, put, web, HttpMessage, HttpRequest, Responder, Result, Scope,
-};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse, Responder, Result, Scope};
 use livekit_api::access_token;
 use livekit_api::services::egress::encoding::H264_1080P_30;
 use livekit_api::services::egress::{EgressOutput, RoomCompositeOptions};
 use livekit_protocol::encoded_file_output::Output;
 use livekit_protocol::{EncodedFileOutput, S3Upload};
 use log::debug;
-use sea_orm::sqlx::types::chrono::NaiveDateTime;
-use sea_orm::{ActiveValue, LoaderTrait};
+use sea_orm::{
+  sqlx::types::chrono::{NaiveDateTime, Utc},
+  ActiveValue, DbErr, LoaderTrait, TransactionTrait,
+};
 use ts_rs::TS;
 
-use crate::common::{AppState, AuthClaims, BaseResponse, LiveKitEgressInfo, LiveKitToken};
+use crate::common::{
+  timestamp_to_datetime, AppState, AuthClaims, BaseResponse, LiveKitEgressInfo, LiveKitToken,
+};
 
-use crate::entities::{room, room_user};
-use crate::services::room::RoomService;
-use crate::services::room_user::RoomUserService;
+use crate::api::calendar::get_room_ics;
+use crate::api::filter::get_room_filter_scope;
+use crate::api::invite::get_invite_scope;
+use crate::api::lobby::{get_lobby_scope, AdmissionNode};
+use crate::api::moderation::{check_target, get_moderation_scope};
+use crate::api::schedule::{check_conflicts, get_availability, parse_room_time};
+use crate::api::summary::get_summary_scope;
+use crate::api::transcript::get_transcript_scope;
+use crate::api::user::UserSummary;
+use crate::entities::{recording, room, room_user};
+use crate::error::AppError;
+use crate::i18n::{Locale, Message, MsgCode};
+use crate::services::recording::RecordingService;
+use crate::services::room::{format_code, normalize_code, RoomService};
+use crate::services::room_admission::{AdmissionStatus, RoomAdmissionService};
+use crate::services::room_user::{RoomPermission, RoomRole, RoomUserService};
 use crate::services::user::UserService;
 
+/// 软删除的会议视为不存在
+pub async fn find_room(data: &AppState, room_id: i32) -> Result<room::Model, AppError> {
+  RoomService::get_room_by_id(&data.db_conn, room_id)
+    .await
+    .map_err(|e| match e {
+      DbErr::RecordNotFound(_) => AppError::room_not_found(),
+      e => e.into(),
+    })
+    .and_then(|x| match x.deleted_at {
+      Some(_) => Err(AppError::room_not_found()),
+      None => Ok(x),
+    })
+}
+
+/// 校验用户在会议中的角色是否具备对应权限，通过时返回会议与角色
+pub async fn authorize_room(
+  data: &AppState,
+  room_id: i32,
+  user_id: &str,
+  permission: RoomPermission,
+) -> Result<(room::Model, RoomRole), AppError> {
+  let room = find_room(data, room_id).await?;
+  match RoomUserService::get_role(&data.db_conn, &room, user_id).await? {
+    Some(role) if role.allows(permission) => Ok((room, role)),
+    Some(_) => Err(AppError::Forbidden(MsgCode::RolePermissionDenied.into())),
+    None => Err(AppError::not_room_member()),
+  }
+}
+
 #[derive(serde::Deserialize, serde::Serialize, TS)]
 #[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
 pub struct LiveKitEgressInfoRes {
@@ -31,47 +75,18 @@ async fn record_room(
   path: web::Path<i32>,
   data: web::Data<AppState>,
   req: HttpRequest,
-) -> Result<impl Responder> {
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
   let room_id = path.into_inner();
-  let Ok(room) = RoomService::get_room_by_id(&data.db_conn, room_id).await else {
-    return Ok(web::Json(LiveKitEgressInfoRes {
-      base: BaseResponse {
-        ret: -404,
-        msg: "找不到对应会议".to_string(),
-      },
-      data: None,
-    }));
-  };
-  if room.admin != req.extensions().get::<AuthClaims>().unwrap().id {
-    return Ok(web::Json(LiveKitEgressInfoRes {
-      base: BaseResponse {
-        ret: -401,
-        msg: "非管理员无权操作".to_string(),
-      },
-      data: None,
-    }));
-  }
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, _) = authorize_room(&data, room_id, &user_id, RoomPermission::Record).await?;
   if !room.cur_egress_id.is_empty() {
-    return Ok(web::Json(LiveKitEgressInfoRes {
-      base: BaseResponse {
-        ret: -400,
-        msg: "会议已在录制中".to_string(),
-      },
-      data: Some(LiveKitEgressInfo {
-        egress_id: room.cur_egress_id,
-      }),
-    }));
+    return Err(AppError::Conflict(MsgCode::RoomRecording.into()));
   }
-  let Some(mut client) = data.livekit_egress_client.try_lock() else {
-    return Ok(web::Json(LiveKitEgressInfoRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "获取 egress 失败".to_string(),
-      },
-      data: None,
-    }));
+  let Some(client) = data.livekit_egress_client.try_lock() else {
+    return Err(AppError::Conflict(MsgCode::EgressBusy.into()));
   };
-  let Ok(info) = client
+  let info = client
     .start_room_composite_egress(
       &room_id.to_string(),
       vec![EgressOutput::File(EncodedFileOutput {
@@ -94,57 +109,34 @@ async fn record_room(
       },
     )
     .await
-  else {
-    return Ok(web::Json(LiveKitEgressInfoRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "录制会议失败".to_string(),
-      },
-      data: None,
-    }));
-  };
+    .map_err(|e| {
+      debug!("start_room_composite_egress err: {:?}", e);
+      AppError::Upstream(MsgCode::RecordFailed.into())
+    })?;
   if info.file_results.is_empty() {
-    return Ok(web::Json(LiveKitEgressInfoRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "录制会议失败".to_string(),
-      },
-      data: None,
-    }));
+    return Err(AppError::Upstream(MsgCode::RecordFailed.into()));
   }
-  let file_name = info.file_results[0].filename.clone();
-  let mut record_videos = room.record_videos.clone();
-  if !record_videos.is_empty() {
-    record_videos.push_str(";");
+  // 任一记录写入失败都停止录制，避免出现无记录的录像
+  let res = async {
+    RecordingService::start_recording(&data.db_conn, room.id, &info, &user_id).await?;
+    RoomService::update_room(
+      &data.db_conn,
+      room::ActiveModel {
+        id: ActiveValue::Set(room.id),
+        cur_egress_id: ActiveValue::Set(info.egress_id.clone()),
+        ..Default::default()
+      },
+    )
+    .await
   }
-  record_videos.push_str(&file_name);
-  
-  let Ok(_) = RoomService::update_room(
-    &data.db_conn,
-    room::ActiveModel {
-      id: ActiveValue::Set(room.id),
-      cur_egress_id: ActiveValue::Set(info.egress_id.clone()),
-      record_videos: ActiveValue::Set(record_videos),
-      ..Default::default()
-    },
-  )
-  .await
-  else {
+  .await;
+  if let Err(e) = res {
     let _ = client.stop_egress(&info.egress_id).await;
-    return Ok(web::Json(LiveKitEgressInfoRes {
-      base: BaseResponse {
-        ret: -2,
-        msg: "录制会议失败".to_string(),
-      },
-      data: None,
-    }));
+    return Err(e.into());
   };
   drop(client);
   Ok(web::Json(LiveKitEgressInfoRes {
-    base: BaseResponse {
-      ret: 0,
-      msg: "会议录制进行中".to_string(),
-    },
+    base: BaseResponse::success(locale, MsgCode::RecordStarted),
     data: Some(LiveKitEgressInfo {
       egress_id: info.egress_id,
     }),
@@ -155,39 +147,30 @@ async fn stop_record(
   path: web::Path<(i32, String)>,
   data: web::Data<AppState>,
   req: HttpRequest,
-) -> Result<impl Responder> {
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
   let (room_id, egress_id) = path.into_inner();
-  let Ok(room) = RoomService::get_room_by_id(&data.db_conn, room_id).await else {
-    return Ok(web::Json(BaseResponse {
-      ret: -404,
-      msg: "找不到对应会议".to_string(),
-    }));
-  };
-  if room.admin != req.extensions().get::<AuthClaims>().unwrap().id {
-    return Ok(web::Json(BaseResponse {
-      ret: -401,
-      msg: "非管理员无权操作".to_string(),
-    }));
-  }
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, _) = authorize_room(&data, room_id, &user_id, RoomPermission::Record).await?;
   if room.cur_egress_id.is_empty() {
-    return Ok(web::Json(BaseResponse {
-      ret: -400,
-      msg: "会议未在录制中".to_string(),
-    }));
+    return Err(AppError::Conflict(MsgCode::RoomNotRecording.into()));
   }
-  let Some(mut client) = data.livekit_egress_client.try_lock() else {
-    return Ok(web::Json(BaseResponse {
-      ret: -1,
-      msg: "获取 egress 失败".to_string(),
-    }));
-  };
-  let Ok(_) = client.stop_egress(&egress_id).await else {
-    return Ok(web::Json(BaseResponse {
-      ret: -1,
-      msg: "停止会议录制失败".to_string(),
-    }));
+  // 只能停止本会议当前的录制，避免借此停止其他会议的录制
+  if egress_id != room.cur_egress_id {
+    return Err(AppError::Conflict(MsgCode::EgressMismatch.into()));
+  }
+  let Some(client) = data.livekit_egress_client.try_lock() else {
+    return Err(AppError::Conflict(MsgCode::EgressBusy.into()));
   };
-  let Ok(_) = RoomService::update_room(
+  let info = client.stop_egress(&egress_id).await.map_err(|e| {
+    debug!("stop_egress err: {:?}", e);
+    AppError::Upstream(MsgCode::StopRecordFailed.into())
+  })?;
+  drop(client);
+  if let Err(e) = RecordingService::sync_egress(&data.db_conn, room.id, &info).await {
+    debug!("sync_egress err: {:?}", e);
+  }
+  RoomService::update_room(
     &data.db_conn,
     room::ActiveModel {
       id: ActiveValue::Set(room.id),
@@ -195,17 +178,11 @@ async fn stop_record(
       ..Default::default()
     },
   )
-  .await
-  else {
-    return Ok(web::Json(BaseResponse {
-      ret: -2,
-      msg: "停止会议录制失败".to_string(),
-    }));
-  };
-  Ok(web::Json(BaseResponse {
-    ret: 0,
-    msg: "会议录制已停止".to_string(),
-  }))
+  .await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::RecordStopped,
+  )))
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
@@ -214,6 +191,8 @@ pub struct RoomTokenRes {
   #[serde(flatten)]
   pub base: BaseResponse,
   pub data: Option<LiveKitToken>,
+  /// 开启等候室时返回准入状态，尚未准入时 data 为 null
+  pub admission: Option<AdmissionNode>,
 }
 
 #[get("/roomToken/{room_code}")]
@@ -221,83 +200,194 @@ async fn get_room_token(
   path: web::Path<String>,
   req: HttpRequest,
   data: web::Data<AppState>,
-) -> Result<impl Responder> {
-  let user_id = req.extensions().get::<AuthClaims>().unwrap().id.clone();
-
-  let rooms = RoomUserService::get_rooms_by_user_id(&data.db_conn, user_id.clone())
-    .await
-    .unwrap();
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
 
   let room_code = path.into_inner();
-  let Some(room) = rooms
-    .load_one(room::Entity, &data.db_conn)
-    .await
-    .unwrap()
-    .into_iter()
-    .filter_map(|x| x)
-    .find(|x| x.code == room_code)
-  else {
-    return Ok(web::Json(RoomTokenRes {
-      base: BaseResponse {
-        ret: -404,
-        msg: "找不到对应会议".to_string(),
-      },
-      data: None,
-    }));
+  let room = match RoomService::get_room_by_code(&data.db_conn, &room_code).await? {
+    Some(room) => room,
+    // 会议号已释放后，与会人员仍可通过该会议号进入自己参加过的会议
+    None => {
+      let code = normalize_code(&room_code);
+      RoomUserService::get_rooms_by_user_id(&data.db_conn, user_id.clone())
+        .await?
+        .load_one(room::Entity, &data.db_conn)
+        .await?
+        .into_iter()
+        .flatten()
+        .filter(|x| x.deleted_at.is_none() && code.as_ref() == Some(&x.code))
+        .max_by_key(|x| x.id)
+        .ok_or(AppError::room_not_found())?
+    }
   };
+  let (room, role) = authorize_room(&data, room.id, &user_id, RoomPermission::Join).await?;
 
   if room.is_canceled {
-    return Ok(web::Json(RoomTokenRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "会议已取消".to_string(),
-      },
-      data: None,
-    }));
+    return Err(AppError::Conflict(MsgCode::RoomCanceled.into()));
   }
 
-  let Ok(livekit_token) =
+  let user = UserService::get_user(&data.db_conn, user_id.clone())
+    .await
+    .map_err(|_| AppError::user_not_found())?;
+  let profile = UserSummary::from(user);
+  // 主持人与联席主持人无需等候
+  let admission = if room.lobby_enabled && !role.allows(RoomPermission::ManageParticipants) {
+    let admission = RoomAdmissionService::request(
+      &data.db_conn,
+      room.id,
+      &user_id,
+      Some(user_id.clone()),
+      &profile.display_name,
+    )
+    .await?;
+    match AdmissionStatus::parse(&admission.status) {
+      AdmissionStatus::Admitted => Some(AdmissionNode::from(admission)),
+      AdmissionStatus::Pending => {
+        return Ok(web::Json(RoomTokenRes {
+          base: BaseResponse::success(locale, MsgCode::LobbyWaiting),
+          data: None,
+          admission: Some(AdmissionNode::from(admission)),
+        }));
+      }
+      AdmissionStatus::Denied => return Err(AppError::Forbidden(MsgCode::LobbyDenied.into())),
+    }
+  } else {
+    None
+  };
+  let metadata = serde_json::json!({ "avatar_url": profile.avatar_url }).to_string();
+  let livekit_token =
     access_token::AccessToken::with_api_key(&data.livekit_key, &data.livekit_secret)
       .with_identity(user_id.as_str())
-      .with_grants(access_token::VideoGrants {
-        room_join: true,
-        room: room.id.to_string(),
-        ..Default::default()
-      })
+      .with_name(&profile.display_name)
+      .with_metadata(&metadata)
+      .with_grants(role.video_grants(room.id.to_string()))
       .to_jwt()
-  else {
-    return Ok(web::Json(RoomTokenRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "获取 room token 失败".to_string(),
-      },
-      data: None,
-    }));
-  };
+      .map_err(|e| {
+        debug!("livekit to_jwt err: {:?}", e);
+        AppError::Upstream(MsgCode::RoomTokenFailed.into())
+      })?;
   Ok(web::Json(RoomTokenRes {
-    base: BaseResponse {
-      ret: 0,
-      msg: "获取 room token 成功".to_string(),
-    },
+    base: BaseResponse::success(locale, MsgCode::RoomTokenIssued),
     data: Some(LiveKitToken {
       livekit_token,
       room_id: room.id.to_string(),
     }),
+    admission,
+  }))
+}
+
+/// 按会议号公开查询到的会议信息，不含与会人员
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ResolvedRoom {
+  pub code: String,
+  pub display_code: String,
+  pub start_time: f64,
+  pub end_time: f64,
+  pub is_canceled: bool,
+  pub lobby_enabled: bool,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ResolveRoomRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<ResolvedRoom>,
+}
+
+/// 入会前按会议号查询会议，无需登录，会议号可带空格或连字符
+#[get("/resolve/{room_code}")]
+async fn resolve_room(
+  path: web::Path<String>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let Some(room) = RoomService::get_room_by_code(&data.db_conn, &path.into_inner()).await? else {
+    return Err(AppError::room_not_found());
+  };
+  Ok(web::Json(ResolveRoomRes {
+    base: BaseResponse::success(locale, MsgCode::RoomResolved),
+    data: Some(ResolvedRoom {
+      display_code: format_code(&room.code),
+      code: room.code,
+      start_time: room.start_time.and_utc().timestamp() as f64,
+      end_time: room.end_time.and_utc().timestamp() as f64,
+      is_canceled: room.is_canceled,
+      lobby_enabled: room.lobby_enabled,
+    }),
   }))
 }
 
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct RecordingNode {
+  pub id: i32,
+  pub egress_id: String,
+  pub started_by: Option<String>,
+  pub start_time: f64,
+  pub end_time: Option<f64>,
+  /// starting | active | ending | complete | failed | aborted | limit_reached
+  pub status: String,
+  /// 相对于 `RoomNode.video_base` 的路径
+  pub file_path: String,
+  /// 字节
+  #[ts(type = "number")]
+  pub size: i64,
+  /// 毫秒
+  #[ts(type = "number")]
+  pub duration: i64,
+}
+
+impl From<recording::Model> for RecordingNode {
+  fn from(x: recording::Model) -> Self {
+    RecordingNode {
+      id: x.id,
+      egress_id: x.egress_id,
+      started_by: x.started_by,
+      start_time: x.start_time.and_utc().timestamp() as f64,
+      end_time: x.end_time.map(|t| t.and_utc().timestamp() as f64),
+      status: x.status,
+      file_path: x.file_path,
+      size: x.size,
+      duration: x.duration,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct RoomMemberRole {
+  pub user_id: String,
+  pub role: RoomRole,
+}
+
 #[derive(serde::Deserialize, serde::Serialize, TS)]
 #[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
 pub struct RoomNode {
   pub id: i32,
   pub code: String,
+  /// 分组显示的会议号，如 `123 456 789`
+  pub display_code: String,
   pub is_canceled: bool,
   pub start_time: f64,
   pub end_time: f64,
   pub admin: String,
-  pub users_ids: Vec<String>,
-  pub record_videos: String,
+  pub users: Vec<UserSummary>,
+  /// 当前用户在会议中的角色
+  pub role: RoomRole,
+  pub member_roles: Vec<RoomMemberRole>,
+  pub recordings: Vec<RecordingNode>,
   pub video_base: String,
+  /// 管理员置顶的推荐滤镜预设
+  pub pinned_filter_id: Option<i32>,
+  /// 是否开启等候室
+  pub lobby_enabled: bool,
+  /// 系列会议的 id，单次会议为 null
+  pub series_id: Option<i32>,
+  /// 在系列规则中对应的原始开始时间，单独改期后与 start_time 不同
+  pub occurrence_start: Option<f64>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
@@ -309,43 +399,72 @@ pub struct RoomListRes {
 }
 
 #[get("/rooms")]
-async fn get_rooms(req: HttpRequest, data: web::Data<AppState>) -> Result<impl Responder> {
-  let user_id = req.extensions().get::<AuthClaims>().unwrap().id.clone();
+async fn get_rooms(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
 
-  let room_users = RoomUserService::get_rooms_by_user_id(&data.db_conn, user_id.clone())
-    .await
-    .unwrap();
+  let room_users = RoomUserService::get_rooms_by_user_id(&data.db_conn, user_id.clone()).await?;
 
   let mut rooms = vec![];
-  for r in room_users
+  for x in room_users
     .load_one(room::Entity, &data.db_conn)
-    .await
-    .unwrap()
+    .await?
+    .into_iter()
+    .flatten()
+    .filter(|x| x.deleted_at.is_none())
   {
-    let Some(x) = r else {
-      continue;
-    };
-    let Ok(t) = RoomUserService::get_users_by_room_id(&data.db_conn, x.id).await else {
-      continue;
-    };
+    let member_roles = RoomUserService::get_users_by_room_id(&data.db_conn, x.id)
+      .await?
+      .into_iter()
+      .map(|u| RoomMemberRole {
+        role: if u.user_id == x.admin {
+          RoomRole::Host
+        } else {
+          RoomRole::parse(&u.role)
+        },
+        user_id: u.user_id,
+      })
+      .collect::<Vec<_>>();
+    let user_ids = member_roles
+      .iter()
+      .map(|x| x.user_id.clone())
+      .collect::<Vec<_>>();
+    let role = member_roles
+      .iter()
+      .find(|x| x.user_id == user_id)
+      .map_or(RoomRole::Attendee, |x| x.role);
+    let users = UserService::get_users(&data.db_conn, &user_ids).await?;
+    let recordings = RecordingService::get_recordings_by_room_id(&data.db_conn, x.id).await?;
     rooms.push(RoomNode {
       id: x.id,
+      display_code: format_code(&x.code),
       code: x.code,
       is_canceled: x.is_canceled,
-      start_time: x.start_time.timestamp() as f64,
-      end_time: x.end_time.timestamp() as f64,
+      start_time: x.start_time.and_utc().timestamp() as f64,
+      end_time: x.end_time.and_utc().timestamp() as f64,
       admin: x.admin,
-      users_ids: t.into_iter().map(|x| x.user_id).collect(),
-      record_videos: x.record_videos,
+      users: user_ids
+        .iter()
+        .filter_map(|id| users.iter().find(|u| u.id == *id))
+        .cloned()
+        .map(UserSummary::from)
+        .collect(),
+      role,
+      member_roles,
+      recordings: recordings.into_iter().map(RecordingNode::from).collect(),
       video_base: data.s3_public_url.clone(),
+      pinned_filter_id: x.pinned_filter_id,
+      lobby_enabled: x.lobby_enabled,
+      series_id: x.series_id,
+      occurrence_start: x.occurrence_start.map(|t| t.and_utc().timestamp() as f64),
     });
   }
 
   Ok(web::Json(RoomListRes {
-    base: BaseResponse {
-      ret: 0,
-      msg: "获取 room token 成功".to_string(),
-    },
+    base: BaseResponse::success(locale, MsgCode::RoomsFetched),
     data: Some(rooms),
   }))
 }
@@ -356,6 +475,32 @@ pub struct CreateRoomReq {
   pub start_time: f64,
   pub end_time: f64,
   pub users_ids: Vec<String>,
+  pub lobby_enabled: Option<bool>,
+  /// 忽略与会人员的时间冲突
+  pub force: Option<bool>,
+}
+
+/// 与会人员需至少两人且均已注册
+pub async fn check_room_users(data: &AppState, user_ids: &[String]) -> Result<(), AppError> {
+  if user_ids.len() < 2 {
+    return Err(AppError::InvalidInput(MsgCode::NotEnoughAttendees.into()));
+  }
+  let users = UserService::get_users(&data.db_conn, &user_ids.to_vec()).await?;
+  let not_exists_users = user_ids
+    .iter()
+    .filter(|&id| users.iter().find(|u| u.id == *id).is_none())
+    .collect::<Vec<_>>();
+  if !not_exists_users.is_empty() {
+    return Err(AppError::InvalidInput(Message::with_args(
+      MsgCode::UsersNotExist,
+      vec![not_exists_users
+        .iter()
+        .map(|x| x.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")],
+    )));
+  }
+  Ok(())
 }
 
 #[put("/create")]
@@ -363,115 +508,126 @@ async fn create_room(
   req: HttpRequest,
   body: web::Json<CreateRoomReq>,
   data: web::Data<AppState>,
-) -> Result<impl Responder> {
-  if body.users_ids.len() < 2 {
-    return Ok(web::Json(BaseResponse {
-      ret: -1,
-      msg: "会议创建失败, 与会人数不足".to_string(),
-    }));
+  locale: Locale,
+) -> Result<HttpResponse, AppError> {
+  let admin = AuthClaims::user_id(&req)?;
+  let mut body = body.into_inner();
+  if !body.users_ids.contains(&admin) {
+    body.users_ids.push(admin.clone());
   }
-  let Ok(users) = UserService::get_users(&data.db_conn, &body.users_ids).await else {
-    return Ok(web::Json(BaseResponse {
-      ret: -1,
-      msg: "会议创建失败".to_string(),
-    }));
-  };
-  let not_exists_users = body
-    .users_ids
-    .iter()
-    .filter(|&id| users.iter().find(|u| u.id == *id).is_none())
-    .collect::<Vec<_>>();
-  if !not_exists_users.is_empty() {
-    return Ok(web::Json(BaseResponse {
-      ret: -1,
-      msg: format!("会议创建失败, 用户 {:?} 不存在", not_exists_users),
-    }));
+  let (start_time, end_time) = parse_room_time(body.start_time, body.end_time, true)?;
+  check_room_users(&data, &body.users_ids).await?;
+  if !body.force.unwrap_or(false) {
+    if let Some(res) =
+      check_conflicts(&data, locale, &body.users_ids, (start_time, end_time), None).await?
+    {
+      return Ok(res);
+    }
   }
-  let Ok(code) = RoomService::get_no_dup_code(&data.db_conn).await else {
-    return Ok(web::Json(BaseResponse {
-      ret: -1,
-      msg: "会议创建失败".to_string(),
-    }));
-  };
-  let create_res = RoomService::create_room(
+  RoomService::create_room(
     &data.db_conn,
     room::ActiveModel {
-      code: ActiveValue::Set(code),
-      start_time: ActiveValue::Set(NaiveDateTime::from_timestamp(body.start_time as i64, 0)),
-      end_time: ActiveValue::Set(NaiveDateTime::from_timestamp(body.end_time as i64, 0)),
-      admin: ActiveValue::Set(req.extensions().get::<AuthClaims>().unwrap().id.clone()),
+      is_canceled: ActiveValue::Set(false),
+      cur_egress_id: ActiveValue::Set(String::new()),
+      start_time: ActiveValue::Set(start_time),
+      end_time: ActiveValue::Set(end_time),
+      admin: ActiveValue::Set(admin.clone()),
+      lobby_enabled: ActiveValue::Set(body.lobby_enabled.unwrap_or(false)),
       ..Default::default()
     },
-  )
-  .await
-  .unwrap();
-  RoomUserService::create_room_user(
-    &data.db_conn,
     body
       .users_ids
       .iter()
       .map(|u| room_user::ActiveModel {
-        room_id: ActiveValue::Set(create_res.last_insert_id),
         user_id: ActiveValue::Set(u.clone()),
+        role: ActiveValue::Set(
+          if *u == admin {
+            RoomRole::Host
+          } else {
+            RoomRole::Attendee
+          }
+          .as_str()
+          .to_string(),
+        ),
         ..Default::default()
       })
       .collect(),
   )
-  .await
-  .map_or_else(
-    |x| {
-      debug!("create_room_user err: {:?}", x);
-      Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "会议创建失败".to_string(),
-      }))
-    },
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: 0,
-        msg: "会议创建成功".to_string(),
-      }))
-    },
-  )
+  .await?;
+  Ok(HttpResponse::Ok().json(BaseResponse::success(locale, MsgCode::RoomCreated)))
+}
+
+/// 结束 LiveKit 中的会议：停止正在进行的录制并断开所有参与者，失败时仅记录日志
+async fn close_livekit_room(data: &AppState, room: &room::Model) {
+  if !room.cur_egress_id.is_empty() {
+    let info = data
+      .livekit_egress_client
+      .lock()
+      .await
+      .stop_egress(&room.cur_egress_id)
+      .await;
+    match info {
+      Ok(info) => {
+        if let Err(e) = RecordingService::sync_egress(&data.db_conn, room.id, &info).await {
+          debug!("sync_egress err: {:?}", e);
+        }
+      }
+      Err(e) => debug!("stop_egress err: {:?}", e),
+    }
+  }
+  if let Err(e) = data
+    .livekit_room_client
+    .delete_room(&room.id.to_string())
+    .await
+  {
+    debug!("delete_room err: {:?}", e);
+  }
 }
 
-// #[delete("/delete/{room_id}")]
-// async fn delete_user(
-//   path: web::Path<i32>,
-//   req: HttpRequest,
-//   data: web::Data<AppState>,
-// ) -> Result<impl Responder> {
-//   let room_id = path.into_inner();
-//   let Ok(room) = RoomService::get_room_by_id(&data.db_conn, room_id).await else {
-//     return Ok(web::Json(BaseResponse {
-//       ret: -404,
-//       msg: "找不到对应会议".to_string(),
-//     }));
-//   };
-//   if room.admin != req.extensions().get::<AuthClaims>().unwrap().id {
-//     return Ok(web::Json(BaseResponse {
-//       ret: -401,
-//       msg: "非管理员无权操作".to_string(),
-//     }));
-//   }
-
-//   RoomService::delete_room(&data.db_conn, room.id)
-//     .await
-//     .map_or_else(
-//       |_| {
-//         Ok(web::Json(BaseResponse {
-//           ret: -1,
-//           msg: "会议删除失败".to_string(),
-//         }))
-//       },
-//       |_| {
-//         Ok(web::Json(BaseResponse {
-//           ret: 0,
-//           msg: "会议删除成功".to_string(),
-//         }))
-//       },
-//     )
-// }
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct DeleteRoomQuery {
+  /// 为 true 时彻底删除会议及录制、转写等数据，否则仅软删除
+  pub hard: Option<bool>,
+}
+
+/// 仅主持人可删除会议，已软删除的会议仍可彻底删除
+#[delete("/delete/{room_id}")]
+async fn delete_room(
+  path: web::Path<i32>,
+  query: web::Query<DeleteRoomQuery>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let room = RoomService::get_room_by_id(&data.db_conn, room_id)
+    .await
+    .map_err(|_| AppError::room_not_found())?;
+  match RoomUserService::get_role(&data.db_conn, &room, &user_id).await? {
+    Some(RoomRole::Host) => {}
+    Some(_) => return Err(AppError::Forbidden(MsgCode::OnlyHostCanDelete.into())),
+    None => return Err(AppError::not_room_member()),
+  }
+  let now = Utc::now().naive_utc();
+  // 系列中尚未结束的会议删除后会被重新生成
+  if room.series_id.is_some() && room.occurrence_start.is_some() && room.end_time > now {
+    return Err(AppError::Conflict(
+      MsgCode::SeriesOccurrenceNotDeletable.into(),
+    ));
+  }
+  close_livekit_room(&data, &room).await;
+  if query.hard.unwrap_or(false) {
+    RoomService::hard_delete(&data.db_conn, room.id).await?;
+  } else if room.deleted_at.is_none() {
+    RoomService::soft_delete(&data.db_conn, room.id, now).await?;
+  }
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::RoomDeleted,
+  )))
+}
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
 #[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
@@ -481,6 +637,9 @@ pub struct UpdateRoomReq {
   admin: Option<String>,
   is_canceled: Option<bool>,
   user_ids: Option<Vec<String>>,
+  lobby_enabled: Option<bool>,
+  /// 忽略与会人员的时间冲突
+  force: Option<bool>,
 }
 
 #[post("/update/{room_id}")]
@@ -489,91 +648,142 @@ async fn update_room(
   body: web::Json<UpdateRoomReq>,
   req: HttpRequest,
   data: web::Data<AppState>,
-) -> Result<impl Responder> {
+  locale: Locale,
+) -> Result<HttpResponse, AppError> {
   let room_id = path.into_inner();
-  let Ok(room) = RoomService::get_room_by_id(&data.db_conn, room_id).await else {
-    return Ok(web::Json(BaseResponse {
-      ret: -404,
-      msg: "找不到对应会议".to_string(),
-    }));
-  };
-  if room.admin != req.extensions().get::<AuthClaims>().unwrap().id {
-    return Ok(web::Json(BaseResponse {
-      ret: -401,
-      msg: "非管理员无权操作".to_string(),
-    }));
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, role) = authorize_room(&data, room_id, &user_id, RoomPermission::Join).await?;
+  let new_admin = body.admin.clone().filter(|x| *x != room.admin);
+  let edit_schedule =
+    body.start_time.is_some() || body.end_time.is_some() || body.is_canceled.is_some();
+  if (edit_schedule && !role.allows(RoomPermission::EditSchedule))
+    || ((body.user_ids.is_some() || body.lobby_enabled.is_some())
+      && !role.allows(RoomPermission::ManageParticipants))
+  {
+    return Err(AppError::Forbidden(MsgCode::RolePermissionDenied.into()));
+  }
+  if new_admin.is_some() && role != RoomRole::Host {
+    return Err(AppError::Forbidden(MsgCode::OnlyHostCanTransfer.into()));
   }
+  let host = new_admin.as_ref().unwrap_or(&room.admin);
 
+  let reschedule = body.start_time.is_some() || body.end_time.is_some();
+  let schedule = if reschedule {
+    let timestamp = |x: NaiveDateTime| x.and_utc().timestamp() as f64;
+    // 未修改开始时间时允许调整进行中会议的结束时间
+    let check_past = body
+      .start_time
+      .is_some_and(|x| timestamp_to_datetime(x) != room.start_time);
+    parse_room_time(
+      body.start_time.unwrap_or(timestamp(room.start_time)),
+      body.end_time.unwrap_or(timestamp(room.end_time)),
+      check_past,
+    )?
+  } else {
+    (room.start_time, room.end_time)
+  };
   if let Some(user_ids) = &body.user_ids {
-    if user_ids.len() < 2 {
-      return Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "会议更新失败, 与会人数不足".to_string(),
-      }));
+    if !user_ids.contains(host) {
+      return Err(AppError::InvalidInput(MsgCode::HostMustBeAttendee.into()));
     }
-    let Ok(users) = UserService::get_users(&data.db_conn, user_ids).await else {
-      return Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "会议更新失败，获取用户异常".to_string(),
-      }));
-    };
-    let not_exists_users = user_ids
-      .iter()
-      .filter(|&id| users.iter().find(|u| u.id == *id).is_none())
-      .collect::<Vec<_>>();
-    if !not_exists_users.is_empty() {
-      return Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: format!("会议更新失败, 用户 {:?} 不存在", not_exists_users),
-      }));
+    check_room_users(&data, user_ids).await?;
+    // 移除成员与管理成员规则一致，联席主持人只能由主持人移除
+    for x in RoomUserService::get_users_by_room_id(&data.db_conn, room_id).await? {
+      if !user_ids.contains(&x.user_id) {
+        check_target(&data, &room, &user_id, role, &x.user_id).await?;
+      }
     }
-    if let Err(e) = RoomUserService::update_room_user(&data.db_conn, room_id, user_ids).await {
-      return Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "会议更新失败，更新与会人员异常".to_string(),
-      }));
+  }
+  let canceled = body.is_canceled.unwrap_or(room.is_canceled);
+  if (reschedule || body.user_ids.is_some()) && !canceled && !body.force.unwrap_or(false) {
+    let user_ids = match &body.user_ids {
+      Some(user_ids) => user_ids.clone(),
+      None => RoomUserService::get_users_by_room_id(&data.db_conn, room_id)
+        .await?
+        .into_iter()
+        .map(|x| x.user_id)
+        .collect(),
     };
+    if let Some(res) = check_conflicts(&data, locale, &user_ids, schedule, Some(room_id)).await? {
+      return Ok(res);
+    }
+  }
+  // 成员、主持人与会议信息同时生效，任一步失败时全部回滚
+  let txn = data.db_conn.begin().await?;
+  if let Some(user_ids) = &body.user_ids {
+    RoomUserService::update_room_user(&txn, room_id, user_ids).await?;
+  }
+
+  if let Some(new_admin) = &new_admin {
+    if !RoomUserService::is_room_member(&txn, room_id, new_admin).await? {
+      return Err(AppError::InvalidInput(MsgCode::HostMustBeAttendee.into()));
+    }
+    RoomUserService::transfer_host(&txn, &room, new_admin).await?;
   }
 
   RoomService::update_room(
-    &data.db_conn,
+    &txn,
     room::ActiveModel {
       id: ActiveValue::Set(room.id),
       start_time: body
         .start_time
-        .map(|x| ActiveValue::Set(NaiveDateTime::from_timestamp(x as i64, 0)))
+        .map(|_| ActiveValue::Set(schedule.0))
         .unwrap_or(ActiveValue::NotSet),
       end_time: body
         .end_time
-        .map(|x| ActiveValue::Set(NaiveDateTime::from_timestamp(x as i64, 0)))
-        .unwrap_or(ActiveValue::NotSet),
-      admin: body
-        .admin
-        .clone()
-        .map(|x| ActiveValue::Set(x))
+        .map(|_| ActiveValue::Set(schedule.1))
         .unwrap_or(ActiveValue::NotSet),
       is_canceled: body
         .is_canceled
-        .map(|x| ActiveValue::Set(x))
+        .map(ActiveValue::Set)
+        .unwrap_or(ActiveValue::NotSet),
+      lobby_enabled: body
+        .lobby_enabled
+        .map(ActiveValue::Set)
         .unwrap_or(ActiveValue::NotSet),
       ..Default::default()
     },
   )
-  .await
-  .map_or_else(
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "会议更新失败".to_string(),
-      }))
-    },
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: 0,
-        msg: "会议更新成功".to_string(),
-      }))
-    },
-  )
+  .await?;
+  txn.commit().await?;
+  Ok(HttpResponse::Ok().json(BaseResponse::success(locale, MsgCode::RoomUpdated)))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct SetRoomRoleReq {
+  pub user_id: String,
+  pub role: RoomRole,
+}
+
+#[post("/role/{room_id}")]
+async fn set_room_role(
+  path: web::Path<i32>,
+  body: web::Json<SetRoomRoleReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  let (room, role) =
+    authorize_room(&data, room_id, &user_id, RoomPermission::ManageParticipants).await?;
+  if body.role == RoomRole::Host || body.user_id == room.admin {
+    return Err(AppError::Conflict(MsgCode::UseTransferHost.into()));
+  }
+  let Some(target_role) = RoomUserService::get_role(&data.db_conn, &room, &body.user_id).await?
+  else {
+    return Err(AppError::NotFound(MsgCode::UserNotRoomMember.into()));
+  };
+  // 联席主持人的任免仅限主持人
+  if role != RoomRole::Host && (body.role == RoomRole::CoHost || target_role == RoomRole::CoHost) {
+    return Err(AppError::Forbidden(MsgCode::OnlyHostCanSetCoHost.into()));
+  }
+  RoomUserService::set_role(&data.db_conn, room_id, &body.user_id, body.role).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::RoleUpdated,
+  )))
 }
 
 pub fn get_room_scope() -> Scope {
@@ -581,7 +791,304 @@ pub fn get_room_scope() -> Scope {
     .service(record_room)
     .service(stop_record)
     .service(get_room_token)
+    .service(resolve_room)
     .service(get_rooms)
     .service(create_room)
+    .service(delete_room)
     .service(update_room)
+    .service(set_room_role)
+    .service(get_room_ics)
+    .service(get_availability)
+    .service(get_transcript_scope())
+    .service(get_summary_scope())
+    .service(get_room_filter_scope())
+    .service(get_invite_scope())
+    .service(get_lobby_scope())
+    .service(get_moderation_scope())
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use livekit_api::access_token::TokenVerifier;
+  use serde_json::{json, Value};
+
+  use super::*;
+  use crate::entities::transcript_segment;
+  use crate::services::transcript::TranscriptService;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+    TEST_LIVEKIT_KEY, TEST_LIVEKIT_SECRET,
+  };
+
+  #[actix_web::test]
+  async fn roles_control_permissions_and_grants() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    add_room_users(&db, 1, &["admin1", "alice", "bob"]).await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db)))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope()),
+    )
+    .await;
+    let post = |uri: &str, user: &str, body: Value| {
+      as_user(test::TestRequest::post().uri(uri), user)
+        .set_json(body)
+        .to_request()
+    };
+    let set_role = |user: &str, target: &str, role: &str| {
+      post(
+        "/api/room/role/1",
+        user,
+        json!({ "user_id": target, "role": role }),
+      )
+    };
+    let reschedule = |user: &str| post("/api/room/update/1", user, json!({ "end_time": 3600.0 }));
+
+    let res: Value = test::call_and_read_body_json(&app, reschedule("alice")).await;
+    assert_eq!(res["ret"], -401);
+    let res: Value =
+      test::call_and_read_body_json(&app, set_role("admin1", "alice", "co_host")).await;
+    assert_eq!(res["ret"], 0);
+    let res: Value = test::call_and_read_body_json(&app, reschedule("alice")).await;
+    assert_eq!(res["ret"], 0);
+
+    // 联席主持人可调整普通角色，但不能任免联席主持人
+    let res: Value = test::call_and_read_body_json(&app, set_role("alice", "bob", "co_host")).await;
+    assert_eq!(res["ret"], -401);
+    let res: Value = test::call_and_read_body_json(&app, set_role("alice", "bob", "viewer")).await;
+    assert_eq!(res["ret"], 0);
+    let res: Value =
+      test::call_and_read_body_json(&app, set_role("alice", "admin1", "viewer")).await;
+    assert_eq!(res["ret"], -400);
+
+    let grants = |token: &Value| {
+      TokenVerifier::with_api_key(TEST_LIVEKIT_KEY, TEST_LIVEKIT_SECRET)
+        .verify(token["data"]["livekit_token"].as_str().unwrap())
+        .unwrap()
+        .video
+    };
+    let token = |user: &str| {
+      as_user(
+        test::TestRequest::get().uri("/api/room/roomToken/000000001"),
+        user,
+      )
+      .to_request()
+    };
+    let res: Value = test::call_and_read_body_json(&app, token("bob")).await;
+    let bob = grants(&res);
+    assert!(!bob.can_publish && bob.can_subscribe && !bob.room_admin);
+    let res: Value = test::call_and_read_body_json(&app, token("alice")).await;
+    let alice = grants(&res);
+    assert!(alice.can_publish && alice.room_admin && alice.room_record);
+
+    // 移交主持人后原主持人降为联席主持人
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      post("/api/room/update/1", "alice", json!({ "admin": "bob" })),
+    )
+    .await;
+    assert_eq!(res["ret"], -401);
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      post("/api/room/update/1", "admin1", json!({ "admin": "alice" })),
+    )
+    .await;
+    assert_eq!(res["ret"], 0);
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      as_user(test::TestRequest::get().uri("/api/room/rooms"), "admin1").to_request(),
+    )
+    .await;
+    assert_eq!(res["data"][0]["admin"], "alice");
+    assert_eq!(res["data"][0]["role"], "co_host");
+
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      test::TestRequest::get()
+        .uri("/api/room/resolve/000-000-001")
+        .to_request(),
+    )
+    .await;
+    assert_eq!(res["data"]["display_code"], "000 000 001");
+    let res = test::call_service(
+      &app,
+      test::TestRequest::get()
+        .uri("/api/room/resolve/000000002")
+        .to_request(),
+    )
+    .await;
+    assert_eq!(res.status(), 404);
+  }
+
+  #[actix_web::test]
+  async fn deletes_room_softly_then_hard() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    add_room_users(&db, 1, &["admin1", "alice"]).await;
+    RecordingService::create_recording(
+      &db,
+      recording::ActiveModel {
+        egress_id: ActiveValue::Set("EG_1".to_string()),
+        room_id: ActiveValue::Set(1),
+        started_by: ActiveValue::Set(None),
+        start_time: ActiveValue::Set(NaiveDateTime::default()),
+        end_time: ActiveValue::Set(None),
+        status: ActiveValue::Set("complete".to_string()),
+        file_path: ActiveValue::Set("1.mp4".to_string()),
+        size: ActiveValue::Set(0),
+        duration: ActiveValue::Set(0),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    TranscriptService::create_segments(
+      &db,
+      vec![transcript_segment::ActiveModel {
+        room_id: ActiveValue::Set(1),
+        speaker_id: ActiveValue::Set("alice".to_string()),
+        start_offset: ActiveValue::Set(0),
+        end_offset: ActiveValue::Set(1),
+        text: ActiveValue::Set("hi".to_string()),
+        language: ActiveValue::Set("en".to_string()),
+        created_at: ActiveValue::Set(NaiveDateTime::default()),
+        ..Default::default()
+      }],
+    )
+    .await
+    .unwrap();
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope()),
+    )
+    .await;
+    let delete =
+      |uri: &str, user: &str| as_user(test::TestRequest::delete().uri(uri), user).to_request();
+    let rooms =
+      |user: &str| as_user(test::TestRequest::get().uri("/api/room/rooms"), user).to_request();
+
+    let res = test::call_service(&app, delete("/api/room/delete/1", "alice")).await;
+    assert_eq!(res.status(), 403);
+    let res = test::call_service(&app, delete("/api/room/delete/1", "admin1")).await;
+    assert_eq!(res.status(), 200);
+    let res: Value = test::call_and_read_body_json(&app, rooms("alice")).await;
+    assert_eq!(res["data"], json!([]));
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert!(room.deleted_at.is_some() && room.code_released);
+    assert_eq!(
+      RecordingService::get_recordings_by_room_id(&db, 1)
+        .await
+        .unwrap()
+        .len(),
+      1
+    );
+
+    let res = test::call_service(&app, delete("/api/room/delete/1?hard=true", "admin1")).await;
+    assert_eq!(res.status(), 200);
+    assert!(RoomService::get_room_by_id(&db, 1).await.is_err());
+    assert!(RecordingService::get_recordings_by_room_id(&db, 1)
+      .await
+      .unwrap()
+      .is_empty());
+    assert!(TranscriptService::get_all_segments(&db, 1)
+      .await
+      .unwrap()
+      .is_empty());
+    assert!(RoomUserService::get_users_by_room_id(&db, 1)
+      .await
+      .unwrap()
+      .is_empty());
+  }
+
+  #[actix_web::test]
+  async fn stop_record_rejects_other_egress() {
+    let db = setup_db().await;
+    create_room(&db, 1, "EG_1").await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope()),
+    )
+    .await;
+    let req = as_user(
+      test::TestRequest::post().uri("/api/room/stopRecord/1/EG_2"),
+      "admin1",
+    )
+    .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 409);
+    let res: Value = test::read_body_json(res).await;
+    assert_eq!(res["msg_code"], "egress_mismatch");
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.cur_egress_id, "EG_1");
+  }
+
+  #[actix_web::test]
+  async fn only_host_removes_co_hosts() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    for id in ["alice", "bob", "carol"] {
+      create_user(&db, id).await;
+    }
+    add_room_users(&db, 1, &["admin1", "alice", "bob", "carol"]).await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope()),
+    )
+    .await;
+    let post = |uri: &str, user: &str, body: Value| {
+      as_user(test::TestRequest::post().uri(uri), user)
+        .set_json(body)
+        .to_request()
+    };
+    for target in ["alice", "bob"] {
+      let req = post(
+        "/api/room/role/1",
+        "admin1",
+        json!({ "user_id": target, "role": "co_host" }),
+      );
+      assert_eq!(test::call_service(&app, req).await.status(), 200);
+    }
+    let set_users = |user: &str, user_ids: &[&str]| {
+      post(
+        "/api/room/update/1",
+        user,
+        json!({ "user_ids": user_ids, "force": true }),
+      )
+    };
+    let members = || async {
+      RoomUserService::get_users_by_room_id(&db, 1)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|x| x.user_id)
+        .collect::<Vec<_>>()
+    };
+
+    let res = test::call_service(&app, set_users("alice", &["admin1", "alice", "carol"])).await;
+    assert_eq!(res.status(), 403);
+    let res: Value = test::read_body_json(res).await;
+    assert_eq!(res["msg_code"], "cannot_moderate_co_host");
+    assert!(members().await.contains(&"bob".to_string()));
+
+    // 联席主持人可以移除普通成员
+    let res = test::call_service(&app, set_users("alice", &["admin1", "alice", "bob"])).await;
+    assert_eq!(res.status(), 200);
+    assert!(!members().await.contains(&"carol".to_string()));
+
+    let res = test::call_service(&app, set_users("admin1", &["admin1", "alice"])).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(members().await, vec!["admin1", "alice"]);
+  }
 }
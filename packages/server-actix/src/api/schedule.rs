@@ -0,0 +1,337 @@
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder, Result};
+use chrono::{Duration, NaiveDateTime};
+use sea_orm::sqlx::types::chrono::Utc;
+use ts_rs::TS;
+
+use crate::common::{timestamp_to_datetime, AppState, AuthClaims, BaseResponse};
+use crate::entities::room;
+use crate::error::{AppError, ErrorCode, ErrorResponse};
+use crate::i18n::{Locale, Message, MsgCode};
+use crate::services::schedule::{
+  free_slots, validate_schedule, ScheduleError, ScheduleService, MAX_AVAILABILITY_DAYS,
+};
+use crate::services::user::UserService;
+
+fn to_timestamp(time: NaiveDateTime) -> f64 {
+  time.and_utc().timestamp() as f64
+}
+
+/// 校验并转换前端传入的会议时间
+pub fn parse_room_time(
+  start_time: f64,
+  end_time: f64,
+  check_past: bool,
+) -> Result<(NaiveDateTime, NaiveDateTime), AppError> {
+  if !start_time.is_finite() || !end_time.is_finite() {
+    return Err(AppError::InvalidInput(MsgCode::RoomEndBeforeStart.into()));
+  }
+  let (start, end) = (
+    timestamp_to_datetime(start_time),
+    timestamp_to_datetime(end_time),
+  );
+  validate_schedule(start, end, Utc::now().naive_utc(), check_past).map_err(|e| {
+    AppError::InvalidInput(
+      match e {
+        ScheduleError::EndBeforeStart => MsgCode::RoomEndBeforeStart,
+        ScheduleError::StartInPast => MsgCode::RoomStartInPast,
+        ScheduleError::TooLong => MsgCode::RoomTooLong,
+      }
+      .into(),
+    )
+  })?;
+  Ok((start, end))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS, Debug)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ConflictNode {
+  pub room_id: i32,
+  pub start_time: f64,
+  pub end_time: f64,
+  /// 在该会议中时间冲突的用户
+  pub user_ids: Vec<String>,
+}
+
+/// 时间冲突时返回 409，客户端可在确认后携带 `force` 重新提交
+#[derive(serde::Deserialize, serde::Serialize, TS, Debug)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct ScheduleConflictRes {
+  #[serde(flatten)]
+  pub error: ErrorResponse,
+  pub data: Vec<ConflictNode>,
+}
+
+/// 检查与会人员在该时段是否已有其他会议，无冲突时返回 `None`
+pub async fn check_conflicts(
+  data: &AppState,
+  locale: Locale,
+  user_ids: &[String],
+  (start, end): (NaiveDateTime, NaiveDateTime),
+  exclude_room: Option<i32>,
+) -> Result<Option<HttpResponse>, AppError> {
+  let conflicts =
+    ScheduleService::find_conflicts(&data.db_conn, user_ids, start, end, exclude_room).await?;
+  if conflicts.is_empty() {
+    return Ok(None);
+  }
+  let err = AppError::Conflict(Message::with_args(
+    MsgCode::ScheduleConflict,
+    vec![conflicts.len().to_string()],
+  ));
+  let message = err.message();
+  Ok(Some(
+    HttpResponse::Conflict().json(ScheduleConflictRes {
+      error: ErrorResponse {
+        base: BaseResponse {
+          ret: err.ret(),
+          msg: message.render(locale),
+          msg_code: Some(message.code()),
+        },
+        code: ErrorCode::Conflict,
+      },
+      data: conflicts
+        .into_iter()
+        .map(|(room, user_ids)| ConflictNode {
+          room_id: room.id,
+          start_time: to_timestamp(room.start_time),
+          end_time: to_timestamp(room.end_time),
+          user_ids,
+        })
+        .collect(),
+    }),
+  ))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct AvailabilityReq {
+  pub user_ids: Vec<String>,
+  pub start_time: f64,
+  pub end_time: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS, Debug, PartialEq)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct TimeSlot {
+  pub start_time: f64,
+  pub end_time: f64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct UserAvailability {
+  pub user_id: String,
+  pub busy: Vec<TimeSlot>,
+  pub free: Vec<TimeSlot>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct Availability {
+  pub users: Vec<UserAvailability>,
+  /// 所有用户均空闲的时段
+  pub free: Vec<TimeSlot>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct AvailabilityRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<Availability>,
+}
+
+fn to_slots(slots: Vec<(NaiveDateTime, NaiveDateTime)>) -> Vec<TimeSlot> {
+  slots
+    .into_iter()
+    .map(|(start, end)| TimeSlot {
+      start_time: to_timestamp(start),
+      end_time: to_timestamp(end),
+    })
+    .collect()
+}
+
+/// 查询用户在时间范围内的忙闲情况，只返回时段，不暴露会议详情，注册在 `/api/room` 下
+#[post("/availability")]
+pub async fn get_availability(
+  req: HttpRequest,
+  body: web::Json<AvailabilityReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  AuthClaims::user_id(&req)?;
+  let (from, to) = (
+    timestamp_to_datetime(body.start_time),
+    timestamp_to_datetime(body.end_time),
+  );
+  if !body.start_time.is_finite()
+    || !body.end_time.is_finite()
+    || to <= from
+    || to - from > Duration::days(MAX_AVAILABILITY_DAYS)
+  {
+    return Err(AppError::InvalidInput(
+      MsgCode::InvalidAvailabilityRange.into(),
+    ));
+  }
+  let users = UserService::get_users(&data.db_conn, &body.user_ids).await?;
+  let not_exists_users = body
+    .user_ids
+    .iter()
+    .filter(|&id| !users.iter().any(|u| u.id == *id))
+    .map(|x| x.as_str())
+    .collect::<Vec<_>>();
+  if !not_exists_users.is_empty() {
+    return Err(AppError::InvalidInput(Message::with_args(
+      MsgCode::UsersNotExist,
+      vec![not_exists_users.join(", ")],
+    )));
+  }
+
+  let busy = ScheduleService::find_busy(&data.db_conn, &body.user_ids, from, to, None).await?;
+  let clamp = |room: &room::Model| (room.start_time.max(from), room.end_time.min(to));
+  let users = body
+    .user_ids
+    .iter()
+    .map(|user_id| {
+      let slots = busy
+        .iter()
+        .filter(|(member, _)| member.user_id == *user_id)
+        .map(|(_, room)| clamp(room))
+        .collect::<Vec<_>>();
+      // 占用时段取空闲时段的补集，相互重叠的会议合并为一段
+      let free = free_slots(&slots, from, to);
+      let merged = free_slots(&free, from, to);
+      UserAvailability {
+        user_id: user_id.clone(),
+        busy: to_slots(merged),
+        free: to_slots(free),
+      }
+    })
+    .collect();
+  let all_busy = busy.iter().map(|(_, room)| clamp(room)).collect::<Vec<_>>();
+  Ok(web::Json(AvailabilityRes {
+    base: BaseResponse::success(locale, MsgCode::AvailabilityFetched),
+    data: Some(Availability {
+      users,
+      free: to_slots(free_slots(&all_busy, from, to)),
+    }),
+  }))
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use serde_json::{json, Value};
+
+  use super::*;
+  use crate::api::room::get_room_scope;
+  use crate::test_utils::{as_user, create_user, setup_db, test_auth, test_state};
+
+  #[actix_web::test]
+  async fn validates_schedule_and_reports_conflicts() {
+    let db = setup_db().await;
+    for id in ["alice", "bob", "carol"] {
+      create_user(&db, id).await;
+    }
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db)))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope()),
+    )
+    .await;
+    let post = |uri: &str, user: &str, body: Value| {
+      as_user(test::TestRequest::post().uri(uri), user)
+        .set_json(body)
+        .to_request()
+    };
+    let create = |user: &str, body: Value| {
+      as_user(test::TestRequest::put().uri("/api/room/create"), user)
+        .set_json(body)
+        .to_request()
+    };
+    let hour = 3600.0;
+    let base = ((Utc::now().timestamp() / 86400 + 2) * 86400) as f64;
+
+    let res = test::call_service(
+      &app,
+      create(
+        "alice",
+        json!({ "start_time": base, "end_time": base, "users_ids": ["bob"] }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 400);
+    let res = test::call_service(
+      &app,
+      create(
+        "alice",
+        json!({ "start_time": hour, "end_time": 2.0 * hour, "users_ids": ["bob"] }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 400);
+    let res = test::call_service(
+      &app,
+      create(
+        "alice",
+        json!({ "start_time": base + hour, "end_time": base + 2.0 * hour, "users_ids": ["bob"] }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+
+    // bob 的时间冲突，确认后可强制创建
+    let overlap = json!({
+      "start_time": base + 1.5 * hour,
+      "end_time": base + 2.5 * hour,
+      "users_ids": ["bob"],
+    });
+    let res: Value = test::call_and_read_body_json(&app, create("carol", overlap.clone())).await;
+    assert_eq!(res["code"], "conflict");
+    assert_eq!(res["data"][0]["user_ids"], json!(["bob"]));
+    let mut forced = overlap;
+    forced["force"] = json!(true);
+    let res = test::call_service(&app, create("carol", forced)).await;
+    assert_eq!(res.status(), 200);
+
+    // 修改自身时间不与自身冲突，移入 bob 的另一会议则冲突
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/update/1",
+        "alice",
+        json!({ "end_time": base + 1.5 * hour }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    let res = test::call_service(
+      &app,
+      post(
+        "/api/room/update/1",
+        "alice",
+        json!({ "end_time": base + 3.0 * hour }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 409);
+
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      post(
+        "/api/room/availability",
+        "alice",
+        json!({ "user_ids": ["bob", "carol"], "start_time": base, "end_time": base + 4.0 * hour }),
+      ),
+    )
+    .await;
+    let slot = |start: f64, end: f64| json!({ "start_time": base + start * hour, "end_time": base + end * hour });
+    assert_eq!(res["data"]["users"][0]["busy"], json!([slot(1.0, 2.5)]));
+    assert_eq!(
+      res["data"]["users"][1]["free"],
+      json!([slot(0.0, 1.5), slot(2.5, 4.0)])
+    );
+    assert_eq!(res["data"]["free"], json!([slot(0.0, 1.0), slot(2.5, 4.0)]));
+  }
+}
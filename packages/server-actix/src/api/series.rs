@@ -0,0 +1,404 @@
+use actix_web::{get, post, put, web, HttpRequest, Responder, Result, Scope};
+use sea_orm::{sqlx::types::chrono::Utc, ActiveValue};
+use ts_rs::TS;
+
+use crate::api::room::check_room_users;
+use crate::common::{timestamp_to_datetime, AppState, AuthClaims, BaseResponse};
+use crate::entities::{meeting_series, room};
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::room::RoomService;
+use crate::services::room_user::RoomUserService;
+use crate::services::series::{Recurrence, SeriesService};
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct OccurrenceNode {
+  pub room_id: i32,
+  pub code: String,
+  pub start_time: f64,
+  pub end_time: f64,
+  pub is_canceled: bool,
+  /// 在规则中对应的原始开始时间，修改规则后被取消的会议为 null
+  pub occurrence_start: Option<f64>,
+}
+
+impl From<room::Model> for OccurrenceNode {
+  fn from(x: room::Model) -> Self {
+    OccurrenceNode {
+      room_id: x.id,
+      code: x.code,
+      start_time: x.start_time.and_utc().timestamp() as f64,
+      end_time: x.end_time.and_utc().timestamp() as f64,
+      is_canceled: x.is_canceled,
+      occurrence_start: x.occurrence_start.map(|t| t.and_utc().timestamp() as f64),
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct SeriesNode {
+  pub id: i32,
+  pub admin: String,
+  pub rrule: String,
+  /// 第一次会议的开始时间
+  pub start_time: f64,
+  /// 每次会议的时长，秒
+  pub duration: i32,
+  pub lobby_enabled: bool,
+  pub is_canceled: bool,
+  /// 已生成的各次会议，更晚的会议在临近时生成
+  pub occurrences: Vec<OccurrenceNode>,
+}
+
+impl SeriesNode {
+  fn new(series: meeting_series::Model, occurrences: Vec<room::Model>) -> Self {
+    SeriesNode {
+      id: series.id,
+      admin: series.admin,
+      rrule: series.rrule,
+      start_time: series.start_time.and_utc().timestamp() as f64,
+      duration: series.duration,
+      lobby_enabled: series.lobby_enabled,
+      is_canceled: series.is_canceled,
+      occurrences: occurrences.into_iter().map(OccurrenceNode::from).collect(),
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct SeriesRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<SeriesNode>,
+}
+
+fn parse_schedule(start_time: f64, end_time: f64, rrule: &str) -> Result<i32, AppError> {
+  if Recurrence::parse(rrule).is_none() {
+    return Err(AppError::InvalidInput(MsgCode::InvalidRrule.into()));
+  }
+  let duration = (end_time - start_time) as i64;
+  if duration <= 0 || duration > i32::MAX as i64 {
+    return Err(AppError::InvalidInput(MsgCode::InvalidSeriesTime.into()));
+  }
+  Ok(duration as i32)
+}
+
+async fn find_series(data: &AppState, series_id: i32) -> Result<meeting_series::Model, AppError> {
+  SeriesService::get_series(&data.db_conn, series_id)
+    .await?
+    .ok_or(AppError::NotFound(MsgCode::SeriesNotFound.into()))
+}
+
+async fn series_res(
+  data: &AppState,
+  locale: Locale,
+  code: MsgCode,
+  series: meeting_series::Model,
+) -> Result<web::Json<SeriesRes>, AppError> {
+  let occurrences = SeriesService::get_occurrences(&data.db_conn, series.id).await?;
+  Ok(web::Json(SeriesRes {
+    base: BaseResponse::success(locale, code),
+    data: Some(SeriesNode::new(series, occurrences)),
+  }))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct CreateSeriesReq {
+  /// 第一次会议的开始、结束时间
+  pub start_time: f64,
+  pub end_time: f64,
+  /// 如 FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10，按 UTC 计算
+  pub rrule: String,
+  pub users_ids: Vec<String>,
+  pub lobby_enabled: Option<bool>,
+}
+
+#[put("/create")]
+async fn create_series(
+  req: HttpRequest,
+  body: web::Json<CreateSeriesReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let admin = AuthClaims::user_id(&req)?;
+  let mut body = body.into_inner();
+  let duration = parse_schedule(body.start_time, body.end_time, &body.rrule)?;
+  if !body.users_ids.contains(&admin) {
+    body.users_ids.push(admin.clone());
+  }
+  check_room_users(&data, &body.users_ids).await?;
+  let series = SeriesService::create_series(
+    &data.db_conn,
+    meeting_series::ActiveModel {
+      admin: ActiveValue::Set(admin.clone()),
+      rrule: ActiveValue::Set(body.rrule.trim().to_string()),
+      start_time: ActiveValue::Set(timestamp_to_datetime(body.start_time)),
+      duration: ActiveValue::Set(duration),
+      lobby_enabled: ActiveValue::Set(body.lobby_enabled.unwrap_or(false)),
+      is_canceled: ActiveValue::Set(false),
+      created_at: ActiveValue::Set(Utc::now().naive_utc()),
+      ..Default::default()
+    },
+  )
+  .await?;
+  SeriesService::materialize(&data.db_conn, &series).await?;
+  // 第一次会议生成后再按请求设置与会人员，之后生成的会议沿用
+  for x in SeriesService::get_occurrences(&data.db_conn, series.id).await? {
+    RoomUserService::update_room_user(&data.db_conn, x.id, &body.users_ids).await?;
+  }
+  series_res(&data, locale, MsgCode::SeriesCreated, series).await
+}
+
+#[get("/{series_id}")]
+async fn get_series(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let series = find_series(&data, path.into_inner()).await?;
+  if series.admin != user_id {
+    let mut is_member = false;
+    for x in SeriesService::get_occurrences(&data.db_conn, series.id).await? {
+      if RoomUserService::is_room_member(&data.db_conn, x.id, &user_id).await? {
+        is_member = true;
+        break;
+      }
+    }
+    if !is_member {
+      return Err(AppError::not_room_member());
+    }
+  }
+  series_res(&data, locale, MsgCode::SeriesFetched, series).await
+}
+
+/// 修改整个系列，只影响尚未开始的会议；单次会议通过 `/api/room/update` 修改或取消
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub struct UpdateSeriesReq {
+  pub start_time: Option<f64>,
+  pub end_time: Option<f64>,
+  pub rrule: Option<String>,
+  pub user_ids: Option<Vec<String>>,
+  pub lobby_enabled: Option<bool>,
+  /// 取消后不再生成新的会议，尚未开始的会议一并取消
+  pub is_canceled: Option<bool>,
+}
+
+#[post("/update/{series_id}")]
+async fn update_series(
+  path: web::Path<i32>,
+  body: web::Json<UpdateSeriesReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let series = find_series(&data, path.into_inner()).await?;
+  if series.admin != user_id {
+    return Err(AppError::Forbidden(MsgCode::OnlySeriesAdmin.into()));
+  }
+  let series_start = series.start_time.and_utc().timestamp() as f64;
+  let start_time = body.start_time.unwrap_or(series_start);
+  let end_time = body.end_time.unwrap_or(start_time + series.duration as f64);
+  let rrule = body.rrule.as_deref().unwrap_or(&series.rrule).trim();
+  let duration = parse_schedule(start_time, end_time, rrule)?;
+  let reschedule = body.start_time.is_some() || body.end_time.is_some() || body.rrule.is_some();
+  if let Some(user_ids) = &body.user_ids {
+    if !user_ids.contains(&series.admin) {
+      return Err(AppError::InvalidInput(MsgCode::HostMustBeAttendee.into()));
+    }
+    check_room_users(&data, user_ids).await?;
+  }
+
+  let series = SeriesService::update_series(
+    &data.db_conn,
+    meeting_series::ActiveModel {
+      id: ActiveValue::Set(series.id),
+      rrule: ActiveValue::Set(rrule.to_string()),
+      start_time: ActiveValue::Set(timestamp_to_datetime(start_time)),
+      duration: ActiveValue::Set(duration),
+      lobby_enabled: body
+        .lobby_enabled
+        .map(ActiveValue::Set)
+        .unwrap_or(ActiveValue::NotSet),
+      is_canceled: body
+        .is_canceled
+        .map(ActiveValue::Set)
+        .unwrap_or(ActiveValue::NotSet),
+      ..Default::default()
+    },
+  )
+  .await?;
+  if reschedule {
+    SeriesService::reschedule(&data.db_conn, &series).await?;
+  }
+  for x in SeriesService::get_upcoming_occurrences(&data.db_conn, series.id).await? {
+    if let Some(user_ids) = &body.user_ids {
+      RoomUserService::update_room_user(&data.db_conn, x.id, user_ids).await?;
+    }
+    if body.lobby_enabled.is_some() || body.is_canceled == Some(true) {
+      RoomService::update_room(
+        &data.db_conn,
+        room::ActiveModel {
+          id: ActiveValue::Set(x.id),
+          lobby_enabled: body
+            .lobby_enabled
+            .map(ActiveValue::Set)
+            .unwrap_or(ActiveValue::NotSet),
+          is_canceled: if body.is_canceled == Some(true) {
+            ActiveValue::Set(true)
+          } else {
+            ActiveValue::NotSet
+          },
+          ..Default::default()
+        },
+      )
+      .await?;
+    }
+  }
+  SeriesService::materialize(&data.db_conn, &series).await?;
+  series_res(&data, locale, MsgCode::SeriesUpdated, series).await
+}
+
+pub fn get_series_scope() -> Scope {
+  web::scope("/api/series")
+    .service(create_series)
+    .service(get_series)
+    .service(update_series)
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{middleware::from_fn, test, App};
+  use serde_json::{json, Value};
+
+  use super::*;
+  use crate::api::room::get_room_scope;
+  use crate::test_utils::{as_user, create_user, setup_db, test_auth, test_state};
+
+  #[actix_web::test]
+  async fn series_expands_and_edits_occurrences() {
+    let db = setup_db().await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    let app = test::init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db)))
+        .wrap(from_fn(test_auth))
+        .service(get_room_scope())
+        .service(get_series_scope()),
+    )
+    .await;
+    let call = |req: test::TestRequest, user: &str, body: Value| {
+      as_user(req, user).set_json(body).to_request()
+    };
+    let start = ((Utc::now().timestamp() / 3600 + 24) * 3600) as f64;
+
+    let res = test::call_service(
+      &app,
+      call(
+        test::TestRequest::put().uri("/api/series/create"),
+        "alice",
+        json!({ "start_time": start, "end_time": start + 1800.0, "rrule": "FREQ=YEARLY", "users_ids": ["bob"] }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 400);
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      call(
+        test::TestRequest::put().uri("/api/series/create"),
+        "alice",
+        json!({
+          "start_time": start,
+          "end_time": start + 1800.0,
+          "rrule": "FREQ=WEEKLY;COUNT=3",
+          "users_ids": ["bob"],
+        }),
+      ),
+    )
+    .await;
+    assert_eq!(res["ret"], 0);
+    let series_id = res["data"]["id"].as_i64().unwrap();
+    let occurrences = res["data"]["occurrences"].as_array().unwrap().clone();
+    assert_eq!(occurrences.len(), 3);
+    assert_eq!(occurrences[1]["start_time"], start + 7.0 * 86400.0);
+
+    // bob 是每次会议的成员，可以单独取消第二次会议
+    let rooms: Value = test::call_and_read_body_json(
+      &app,
+      as_user(test::TestRequest::get().uri("/api/room/rooms"), "bob").to_request(),
+    )
+    .await;
+    assert_eq!(rooms["data"].as_array().unwrap().len(), 3);
+    assert_eq!(rooms["data"][0]["series_id"], series_id);
+    let second = occurrences[1]["room_id"].as_i64().unwrap();
+    let res = test::call_service(
+      &app,
+      call(
+        test::TestRequest::post().uri(&format!("/api/room/update/{second}")),
+        "alice",
+        json!({ "is_canceled": true }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+
+    let update = format!("/api/series/update/{series_id}");
+    let res = test::call_service(
+      &app,
+      call(
+        test::TestRequest::post().uri(&update),
+        "bob",
+        json!({ "lobby_enabled": true }),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 403);
+    // 整体推迟一小时，沿用原有会议号
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      call(
+        test::TestRequest::post().uri(&update),
+        "alice",
+        json!({ "start_time": start + 3600.0, "end_time": start + 7200.0 }),
+      ),
+    )
+    .await;
+    let updated = res["data"]["occurrences"].as_array().unwrap();
+    assert_eq!(updated.len(), 3);
+    for (old, new) in occurrences.iter().zip(updated) {
+      assert_eq!(old["code"], new["code"]);
+      assert_eq!(
+        new["start_time"],
+        old["start_time"].as_f64().unwrap() + 3600.0
+      );
+      assert_eq!(
+        new["end_time"],
+        new["start_time"].as_f64().unwrap() + 3600.0
+      );
+    }
+    assert_eq!(updated[1]["is_canceled"], true);
+    assert_eq!(updated[2]["is_canceled"], false);
+
+    // 改为两次后多出的一次被取消
+    let res: Value = test::call_and_read_body_json(
+      &app,
+      call(
+        test::TestRequest::post().uri(&update),
+        "alice",
+        json!({ "rrule": "FREQ=WEEKLY;COUNT=2" }),
+      ),
+    )
+    .await;
+    let updated = res["data"]["occurrences"].as_array().unwrap();
+    assert_eq!(updated[2]["is_canceled"], true);
+    assert!(updated[2]["occurrence_start"].is_null());
+  }
+}
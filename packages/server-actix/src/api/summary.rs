@@ -0,0 +1,101 @@
+use actix_web::{get, post, web, HttpRequest, Responder, Result, Scope};
+use ts_rs::TS;
+
+use crate::api::room::authorize_room;
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::entities::meeting_summary;
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::room_user::RoomPermission;
+use crate::services::summary::{ActionItem, SummaryService};
+use crate::services::transcript::TranscriptService;
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/summary.ts")]
+pub struct MeetingSummaryNode {
+  pub room_id: i32,
+  /// running | done | failed
+  pub status: String,
+  pub summary: String,
+  pub decisions: Vec<String>,
+  pub action_items: Vec<ActionItem>,
+  pub error: String,
+  pub created_by: String,
+  pub updated_at: f64,
+}
+
+impl From<meeting_summary::Model> for MeetingSummaryNode {
+  fn from(x: meeting_summary::Model) -> Self {
+    MeetingSummaryNode {
+      room_id: x.room_id,
+      status: x.status,
+      summary: x.summary,
+      decisions: serde_json::from_str(&x.decisions).unwrap_or_default(),
+      action_items: serde_json::from_str(&x.action_items).unwrap_or_default(),
+      error: x.error,
+      created_by: x.created_by,
+      updated_at: x.updated_at.and_utc().timestamp() as f64,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/summary.ts")]
+pub struct MeetingSummaryRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<MeetingSummaryNode>,
+}
+
+#[post("")]
+async fn create_summary(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  authorize_room(&data, room_id, &user_id, RoomPermission::Record).await?;
+  if let (_, 0) = TranscriptService::get_segments(&data.db_conn, room_id, 1, 1).await? {
+    return Err(AppError::Conflict(MsgCode::NoTranscript.into()));
+  }
+  if !SummaryService::start(&data.db_conn, room_id, &user_id).await? {
+    return Err(AppError::Conflict(MsgCode::SummaryRunning.into()));
+  }
+  actix_web::rt::spawn(SummaryService::run(
+    data.db_conn.clone(),
+    data.llm_client.clone(),
+    room_id,
+  ));
+  let summary = SummaryService::get_summary(&data.db_conn, room_id).await?;
+  Ok(web::Json(MeetingSummaryRes {
+    base: BaseResponse::success(locale, MsgCode::SummaryRunning),
+    data: summary.map(MeetingSummaryNode::from),
+  }))
+}
+
+#[get("")]
+async fn get_summary(
+  path: web::Path<i32>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  authorize_room(&data, room_id, &user_id, RoomPermission::Join).await?;
+  let Some(summary) = SummaryService::get_summary(&data.db_conn, room_id).await? else {
+    return Err(AppError::NotFound(MsgCode::SummaryNotFound.into()));
+  };
+  Ok(web::Json(MeetingSummaryRes {
+    base: BaseResponse::success(locale, MsgCode::SummaryFetched),
+    data: Some(MeetingSummaryNode::from(summary)),
+  }))
+}
+
+pub fn get_summary_scope() -> Scope {
+  web::scope("/{room_id}/summary")
+    .service(create_summary)
+    .service(get_summary)
+}
@@ -0,0 +1,400 @@
+use actix_web::{
+  get, http::header, post, web, HttpRequest, HttpResponse, Responder, Result, Scope,
+};
+use sea_orm::{sqlx::types::chrono::Utc, ActiveValue};
+use ts_rs::TS;
+
+use crate::api::room::authorize_room;
+use crate::common::{AppState, AuthClaims, BaseResponse};
+use crate::entities::transcript_segment;
+use crate::error::AppError;
+use crate::i18n::{Locale, Message, MsgCode};
+use crate::services::room_user::RoomPermission;
+use crate::services::transcript::{to_plain_text, to_srt, to_webvtt, TranscriptService};
+
+const MAX_BATCH_SIZE: usize = 500;
+const DEFAULT_PAGE_SIZE: u64 = 100;
+const MAX_PAGE_SIZE: u64 = 500;
+
+/// 仅会议成员可读写会议转写，软删除的会议视为不存在
+async fn check_member(data: &AppState, room_id: i32, user_id: &str) -> Result<(), AppError> {
+  authorize_room(data, room_id, user_id, RoomPermission::Join).await?;
+  Ok(())
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/transcript.ts")]
+pub struct TranscriptSegmentReq {
+  /// 相对会议开始的毫秒偏移
+  #[ts(type = "number")]
+  pub start_offset: i64,
+  #[ts(type = "number")]
+  pub end_offset: i64,
+  pub text: String,
+  pub language: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/transcript.ts")]
+pub struct UploadTranscriptReq {
+  pub segments: Vec<TranscriptSegmentReq>,
+}
+
+/// 转写文本需单行，且不能包含 SRT/WebVTT 的时间分隔符
+fn normalize_text(text: &str) -> String {
+  text
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ")
+    .replace("-->", "->")
+}
+
+#[post("")]
+async fn upload_transcript(
+  path: web::Path<i32>,
+  body: web::Json<UploadTranscriptReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  check_member(&data, room_id, &user_id).await?;
+  if body.segments.len() > MAX_BATCH_SIZE {
+    return Err(AppError::InvalidInput(Message::with_args(
+      MsgCode::TranscriptBatchTooLarge,
+      vec![MAX_BATCH_SIZE.to_string()],
+    )));
+  }
+  if body
+    .segments
+    .iter()
+    .any(|s| s.start_offset < 0 || s.end_offset < s.start_offset)
+  {
+    return Err(AppError::InvalidInput(
+      MsgCode::InvalidTranscriptRange.into(),
+    ));
+  }
+  let now = Utc::now().naive_utc();
+  let segments = body
+    .segments
+    .iter()
+    .filter_map(|s| {
+      let text = normalize_text(&s.text);
+      if text.is_empty() {
+        return None;
+      }
+      Some(transcript_segment::ActiveModel {
+        room_id: ActiveValue::Set(room_id),
+        speaker_id: ActiveValue::Set(user_id.clone()),
+        start_offset: ActiveValue::Set(s.start_offset),
+        end_offset: ActiveValue::Set(s.end_offset),
+        text: ActiveValue::Set(text),
+        language: ActiveValue::Set(s.language.clone().unwrap_or_default()),
+        created_at: ActiveValue::Set(now),
+        ..Default::default()
+      })
+    })
+    .collect();
+  TranscriptService::create_segments(&data.db_conn, segments).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::TranscriptUploaded,
+  )))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/transcript.ts")]
+pub struct TranscriptSegmentNode {
+  pub id: i32,
+  pub speaker_id: String,
+  #[ts(type = "number")]
+  pub start_offset: i64,
+  #[ts(type = "number")]
+  pub end_offset: i64,
+  pub text: String,
+  pub language: String,
+}
+
+impl From<transcript_segment::Model> for TranscriptSegmentNode {
+  fn from(x: transcript_segment::Model) -> Self {
+    TranscriptSegmentNode {
+      id: x.id,
+      speaker_id: x.speaker_id,
+      start_offset: x.start_offset,
+      end_offset: x.end_offset,
+      text: x.text,
+      language: x.language,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/transcript.ts")]
+pub struct TranscriptPage {
+  #[ts(type = "number")]
+  pub total: u64,
+  #[ts(type = "number")]
+  pub page: u64,
+  #[ts(type = "number")]
+  pub page_size: u64,
+  pub segments: Vec<TranscriptSegmentNode>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/transcript.ts")]
+pub struct TranscriptListRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<TranscriptPage>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/transcript.ts")]
+pub struct TranscriptListQuery {
+  #[ts(type = "number | null")]
+  pub page: Option<u64>,
+  #[ts(type = "number | null")]
+  pub page_size: Option<u64>,
+}
+
+#[get("")]
+async fn get_transcript(
+  path: web::Path<i32>,
+  query: web::Query<TranscriptListQuery>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  check_member(&data, room_id, &user_id).await?;
+  let page = query.page.unwrap_or(1).max(1);
+  let page_size = query
+    .page_size
+    .unwrap_or(DEFAULT_PAGE_SIZE)
+    .clamp(1, MAX_PAGE_SIZE);
+  let (segments, total) =
+    TranscriptService::get_segments(&data.db_conn, room_id, page, page_size).await?;
+  Ok(web::Json(TranscriptListRes {
+    base: BaseResponse::success(locale, MsgCode::TranscriptFetched),
+    data: Some(TranscriptPage {
+      total,
+      page,
+      page_size,
+      segments: segments
+        .into_iter()
+        .map(TranscriptSegmentNode::from)
+        .collect(),
+    }),
+  }))
+}
+
+#[derive(serde::Deserialize)]
+pub struct ExportTranscriptQuery {
+  /// txt | srt | vtt
+  pub format: Option<String>,
+}
+
+#[get("/export")]
+async fn export_transcript(
+  path: web::Path<i32>,
+  query: web::Query<ExportTranscriptQuery>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+  let room_id = path.into_inner();
+  let user_id = AuthClaims::user_id(&req)?;
+  check_member(&data, room_id, &user_id).await?;
+  let format = query.format.as_deref().unwrap_or("txt");
+  let (render, content_type): (fn(&[transcript_segment::Model]) -> String, &str) = match format {
+    "txt" => (to_plain_text, "text/plain; charset=utf-8"),
+    "srt" => (to_srt, "application/x-subrip; charset=utf-8"),
+    "vtt" => (to_webvtt, "text/vtt; charset=utf-8"),
+    _ => {
+      return Err(AppError::InvalidInput(
+        MsgCode::UnsupportedExportFormat.into(),
+      ))
+    }
+  };
+  let segments = TranscriptService::get_all_segments(&data.db_conn, room_id).await?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type(content_type)
+      .insert_header((
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"transcript-{room_id}.{format}\""),
+      ))
+      .body(render(&segments)),
+  )
+}
+
+pub fn get_transcript_scope() -> Scope {
+  web::scope("/{room_id}/transcript")
+    .service(upload_transcript)
+    .service(get_transcript)
+    .service(export_transcript)
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::{
+    middleware::from_fn,
+    test::{
+      call_and_read_body, call_and_read_body_json, call_service, init_service, read_body_json,
+      TestRequest,
+    },
+    App,
+  };
+  use serde_json::{json, Value};
+
+  use super::*;
+  use crate::services::room::RoomService;
+  use crate::test_utils::{
+    add_room_users, as_user, create_room, create_user, setup_db, test_auth, test_state,
+  };
+
+  #[test]
+  fn normalizes_text_to_single_line() {
+    assert_eq!(normalize_text("  你好\n  世界\t! "), "你好 世界 !");
+    assert_eq!(normalize_text("a --> b-->c"), "a -> b->c");
+    assert_eq!(normalize_text(" \r\n "), "");
+  }
+
+  #[actix_web::test]
+  async fn transcript_is_member_only_and_validated() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    add_room_users(&db, 1, &["admin1", "alice"]).await;
+    let app = init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .wrap(from_fn(test_auth))
+        .service(web::scope("/api/room").service(get_transcript_scope())),
+    )
+    .await;
+    let upload = |user: Option<&str>, segments: Value| {
+      let req = TestRequest::post()
+        .uri("/api/room/1/transcript")
+        .set_json(json!({ "segments": segments }));
+      match user {
+        Some(user) => as_user(req, user),
+        None => req,
+      }
+      .to_request()
+    };
+    let get = |user: Option<&str>, uri: &str| {
+      let req = TestRequest::get().uri(uri);
+      match user {
+        Some(user) => as_user(req, user),
+        None => req,
+      }
+      .to_request()
+    };
+    let segment = |start: i64, end: i64, text: &str| json!({ "start_offset": start, "end_offset": end, "text": text });
+
+    for (user, status) in [(None, 401), (Some("bob"), 403)] {
+      let res = call_service(&app, upload(user, json!([segment(0, 1, "x")]))).await;
+      assert_eq!(res.status(), status);
+      let res = call_service(&app, get(user, "/api/room/1/transcript")).await;
+      assert_eq!(res.status(), status);
+      let res = call_service(&app, get(user, "/api/room/1/transcript/export")).await;
+      assert_eq!(res.status(), status);
+    }
+
+    let too_many = vec![segment(0, 1, "x"); MAX_BATCH_SIZE + 1];
+    let res = call_service(&app, upload(Some("alice"), json!(too_many))).await;
+    assert_eq!(res.status(), 400);
+    let res: Value = read_body_json(res).await;
+    assert_eq!(res["msg_code"], "transcript_batch_too_large");
+    assert_eq!(res["msg"], "单次最多上传 500 条转写");
+    for bad in [segment(-1, 1, "x"), segment(2000, 1000, "x")] {
+      let res = call_service(
+        &app,
+        upload(Some("alice"), json!([segment(0, 1, "ok"), bad])),
+      )
+      .await;
+      assert_eq!(res.status(), 400);
+    }
+    let full = vec![segment(0, 1, "x"); MAX_BATCH_SIZE];
+    let res = call_service(&app, upload(Some("alice"), json!(full))).await;
+    assert_eq!(res.status(), 200);
+    // 空白文本不入库，允许零时长
+    let res = call_service(
+      &app,
+      upload(
+        Some("admin1"),
+        json!([
+          segment(3_600_000, 3_600_000, "  最后\n一句 "),
+          segment(10, 20, " \n ")
+        ]),
+      ),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+
+    let page = |query: &str| get(Some("alice"), &format!("/api/room/1/transcript?{query}"));
+    let res: Value = call_and_read_body_json(&app, page("")).await;
+    assert_eq!(res["data"]["total"], MAX_BATCH_SIZE + 1);
+    assert_eq!(res["data"]["page"], 1);
+    assert_eq!(res["data"]["page_size"], DEFAULT_PAGE_SIZE);
+    assert_eq!(
+      res["data"]["segments"].as_array().unwrap().len() as u64,
+      DEFAULT_PAGE_SIZE
+    );
+    let res: Value = call_and_read_body_json(&app, page("page=0&page_size=0")).await;
+    assert_eq!(
+      (
+        res["data"]["page"].as_u64(),
+        res["data"]["page_size"].as_u64()
+      ),
+      (Some(1), Some(1))
+    );
+    let res: Value = call_and_read_body_json(&app, page("page=2&page_size=100000")).await;
+    assert_eq!(res["data"]["page_size"], MAX_PAGE_SIZE);
+    let segments = res["data"]["segments"].as_array().unwrap();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0]["speaker_id"], "admin1");
+    assert_eq!(segments[0]["text"], "最后 一句");
+    let res: Value = call_and_read_body_json(&app, page("page=9")).await;
+    assert!(res["data"]["segments"].as_array().unwrap().is_empty());
+
+    let res = call_service(
+      &app,
+      get(Some("alice"), "/api/room/1/transcript/export?format=vtt"),
+    )
+    .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+      res.headers().get(header::CONTENT_TYPE).unwrap(),
+      "text/vtt; charset=utf-8"
+    );
+    assert_eq!(
+      res.headers().get(header::CONTENT_DISPOSITION).unwrap(),
+      "attachment; filename=\"transcript-1.vtt\""
+    );
+    let body = call_and_read_body(
+      &app,
+      get(Some("alice"), "/api/room/1/transcript/export?format=srt"),
+    )
+    .await;
+    assert!(String::from_utf8(body.to_vec())
+      .unwrap()
+      .ends_with("501\n01:00:00,000 --> 01:00:00,000\nadmin1: 最后 一句\n\n"));
+    let res = call_service(
+      &app,
+      get(Some("alice"), "/api/room/1/transcript/export?format=doc"),
+    )
+    .await;
+    assert_eq!(res.status(), 400);
+
+    // 软删除的会议不再接受上传
+    RoomService::soft_delete(&db, 1, Utc::now().naive_utc())
+      .await
+      .unwrap();
+    let res = call_service(&app, upload(Some("alice"), json!([segment(0, 1, "x")]))).await;
+    assert_eq!(res.status(), 404);
+  }
+}
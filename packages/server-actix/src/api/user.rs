@@ -1,37 +1,73 @@
-use std::time::{self, Duration, UNIX_EPOCH};
-
-use crate::{common::{AppState, Filter}, entities::user, services::user::UserService};
-use actix_web::{
-  delete, error, post, put, web, HttpMessage, HttpRequest, Responder, Result, Scope,
+use crate::{
+  api::account::{cancel_account_deletion, delete_user, export_account, get_account_deletion},
+  api::calendar::{create_calendar_feed, get_calendar_ics, revoke_calendar_feed},
+  api::filter::get_filter_scope,
+  api::mfa::{
+    disable_mfa, enable_mfa, get_mfa_status, login_mfa, regenerate_recovery_codes, setup_mfa,
+  },
+  common::{AppState, CssFilter},
+  entities::user,
+  error::AppError,
+  i18n::{Locale, Message, MsgCode},
+  services::{
+    auth::{AuthService, MFA_TOKEN_TTL_SECS},
+    credential::{CredentialPolicy, PolicyError},
+    llm::{extract_json, ChatMessage, LlmError},
+    mfa::MfaService,
+    rate_limit::{Throttle, REASON_USER_DISABLED, REASON_USER_NOT_FOUND, REASON_WRONG_PASSWORD},
+    user::{AuthFailure, UserService},
+  },
 };
-use jsonwebtoken::{encode, EncodingKey, Header};
+use actix_web::{get, patch, post, put, web, HttpRequest, Responder, Result, Scope};
 use log::debug;
-use password_auth::{generate_hash, verify_password};
-use reqwest::Client;
-use sea_orm::{ActiveValue, DatabaseConnection};
-use serde_json::json;
+use password_auth::generate_hash;
+use sea_orm::{sqlx::types::chrono::Utc, ActiveValue, DatabaseConnection};
+use std::net::{IpAddr, SocketAddr};
 use ts_rs::TS;
 
 use crate::common::{AuthClaims, AuthToken, BaseResponse};
 
-async fn verify_user(
+/// 已登录用户重新验证密码，如修改密码、注销账号前
+pub async fn verify_user(
   id: String,
   password: String,
   db_conn: &DatabaseConnection,
-) -> Result<(), BaseResponse> {
-  let Ok(user_model) = UserService::get_user(db_conn, id).await else {
-    return Err(BaseResponse {
-      ret: -1,
-      msg: "用户不存在".to_string(),
-    });
-  };
-  if !verify_password(password, &user_model.password).is_ok() {
-    return Err(BaseResponse {
-      ret: -1,
-      msg: "用户密码错误".to_string(),
-    });
+) -> Result<user::Model, AppError> {
+  match UserService::authenticate(db_conn, &id, &password).await? {
+    Ok(user_model) => Ok(user_model),
+    Err(AuthFailure::UserNotFound) => Err(AppError::user_not_found()),
+    Err(AuthFailure::WrongPassword) => Err(AppError::InvalidInput(MsgCode::WrongPassword.into())),
+    Err(AuthFailure::UserDisabled) => Err(AppError::Forbidden(MsgCode::UserDisabled.into())),
   }
-  Ok(())
+}
+
+fn policy_error(policy: &CredentialPolicy, e: PolicyError) -> AppError {
+  let message = match e {
+    PolicyError::InvalidUserId => Message::with_args(
+      MsgCode::InvalidUserId,
+      vec![
+        policy.id_min_len.to_string(),
+        policy.id_max_len.to_string(),
+        policy.id_symbols.clone(),
+      ],
+    ),
+    PolicyError::ReservedUserId => MsgCode::UserIdReserved.into(),
+    PolicyError::PasswordTooShort => Message::with_args(
+      MsgCode::PasswordTooShort,
+      vec![policy.password_min_len.to_string()],
+    ),
+    PolicyError::PasswordTooLong => Message::with_args(
+      MsgCode::PasswordTooLong,
+      vec![policy.password_max_len.to_string()],
+    ),
+    PolicyError::PasswordTooWeak => Message::with_args(
+      MsgCode::PasswordTooWeak,
+      vec![policy.password_min_classes.to_string()],
+    ),
+    PolicyError::PasswordTooCommon => MsgCode::PasswordTooCommon.into(),
+    PolicyError::PasswordContainsUserId => MsgCode::PasswordContainsUserId.into(),
+  };
+  AppError::InvalidInput(message)
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
@@ -39,7 +75,7 @@ async fn verify_user(
 pub struct GptFilterRes {
   #[serde(flatten)]
   pub base: BaseResponse,
-  pub data: Option<Filter>,
+  pub data: Option<CssFilter>,
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
@@ -48,34 +84,7 @@ pub struct GptFilterReq {
   pub prompt: String,
 }
 
-
-#[derive(serde::Deserialize, std::fmt::Debug)]
-pub struct GPTMessage {
-  pub content: String,
-}
-#[derive(serde::Deserialize, std::fmt::Debug)]
-pub struct GPTChoice {
-  pub message: GPTMessage,
-}
-#[derive(serde::Deserialize)]
-pub struct GPTResp {
-  pub choices: Vec<GPTChoice>,
-}
-
-#[post("/getGptFilter")]
-async fn get_gpt_filter(
-  data: web::Data<AppState>,
-  body: web::Json<GptFilterReq>,
-) -> Result<impl Responder> {
-  let client = Client::new();
-  let Ok(resp) = client
-    .post(data.gpt_base_url.clone())
-    .header("Content-Type", "application/json")
-    .header("Authorization", format!("Bearer {}", data.gpt_api_key))
-    .json(&json!({
-      "model": "deepseek-v3-0324",
-      "messages": [
-        { "role": "system", "content": r#"
+const GPT_FILTER_PROMPT: &str = r#"
 你是一个精通 css 滤镜的专家，你需要帮助用户生成期望的滤镜参数，可用的 css 滤镜及其参数范围限制如下:
 {"blur":{"max":10,"min":0},"brightness":{"max":3,"min":0},"contrast":{"max":3,"min":0},"grayscale":{"max":1,"min":0},"hue-rotate":{"max":360,"min":0},"invert":{"max":1,"min":0},"opacity":{"max":1,"min":0},"saturate":{"max":3,"min":0},"sepia":{"max":1,"min":0}}
 对应的 css 滤镜需要解析成规范的 json，如 css "filter: blur(10px) brightness(2) hue-rotate(180deg)" 对应的返回值为 {"blur":10,"brightness":2,"hue-rotate":180}
@@ -90,49 +99,44 @@ async fn get_gpt_filter(
 输出2: {}
 解释：输入1按要求生成对应的滤镜 json，输入2内容与滤镜无关或不能用对应的滤镜参数表达，返回空对象
 下面是用户的描述：
-"# },
-        { "role": "user", "content": body.prompt }
-      ],
-      "stream": false
-    }))
-    .send()
-    .await
-  else {
-    return Ok(web::Json(GptFilterRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "请求失败，请稍后再试".to_string(),
-      },
-      data: None,
-    }));
-  };
+"#;
 
-  let Ok(GPTResp { choices }) = resp.json::<GPTResp>().await else {
-    return Ok(web::Json(GptFilterRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "解析失败，请稍后再试".to_string(),
-      },
-      data: None,
-    }));
-  };
-  if choices.is_empty() {
+#[post("/getGptFilter")]
+async fn get_gpt_filter(
+  data: web::Data<AppState>,
+  body: web::Json<GptFilterReq>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let messages = vec![
+    ChatMessage::system(GPT_FILTER_PROMPT),
+    ChatMessage::user(&body.prompt),
+  ];
+  // 模型输出无法解析时重试一次
+  for _ in 0..2 {
+    let content = match data.llm_client.chat(messages.clone()).await {
+      Ok(res) => {
+        debug!("get_gpt_filter usage: {:?}", res.usage);
+        res.content
+      }
+      Err(LlmError::Request(e)) | Err(LlmError::Status(_, e)) => {
+        debug!("get_gpt_filter err: {}", e);
+        return Err(AppError::Upstream(MsgCode::LlmRequestFailed.into()));
+      }
+      Err(e) => {
+        debug!("get_gpt_filter err: {}", e);
+        continue;
+      }
+    };
+    let Some(filter) = extract_json(&content).and_then(CssFilter::parse) else {
+      debug!("get_gpt_filter parse err: {}", content);
+      continue;
+    };
     return Ok(web::Json(GptFilterRes {
-      base: BaseResponse {
-        ret: -1,
-        msg: "解析失败，请稍后再试".to_string()
-      },
-      data: None,
+      base: BaseResponse::success(locale, MsgCode::GptFilterFetched),
+      data: Some(filter),
     }));
   }
-
-  Ok(web::Json(GptFilterRes {
-    base: BaseResponse {
-      ret: 0,
-      msg: "获取推荐滤镜成功".to_string()
-    },
-    data: Some(Filter { filter: choices[0].message.content.clone() })
-  }))
+  Err(AppError::Upstream(MsgCode::LlmParseFailed.into()))
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
@@ -141,98 +145,206 @@ pub struct UserLoginRes {
   #[serde(flatten)]
   pub base: BaseResponse,
   pub data: Option<AuthToken>,
+  /// 开启了两步验证时不返回令牌，需携带此凭证调用 `/login/mfa`
+  #[serde(default)]
+  pub mfa: Option<MfaChallenge>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct MfaChallenge {
+  pub mfa_token: String,
+  /// 有效期，单位秒
+  #[ts(type = "number")]
+  pub expires_in: u64,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UserAuthReq {
+  pub id: String,
+  pub password: String,
+}
+
+pub fn login_res(token: AuthToken, locale: Locale, code: MsgCode) -> web::Json<UserLoginRes> {
+  web::Json(UserLoginRes {
+    base: BaseResponse::success(locale, code),
+    data: Some(token),
+    mfa: None,
+  })
+}
+
+/// 客户端地址。对端是受信任的反向代理时取 `Forwarded`/`X-Forwarded-For` 中的地址，
+/// 否则取连接的对端地址，不信任可被伪造的转发请求头
+pub fn client_ip(req: &HttpRequest, trusted_proxies: &[IpAddr]) -> String {
+  let Some(peer) = req.peer_addr().map(|x| x.ip()) else {
+    return String::new();
+  };
+  if !trusted_proxies.contains(&peer) {
+    return peer.to_string();
+  }
+  let info = req.connection_info();
+  let forwarded = info.realip_remote_addr().and_then(|x| {
+    x.parse::<IpAddr>()
+      .or_else(|_| x.parse::<SocketAddr>().map(|x| x.ip()))
+      .ok()
+  });
+  forwarded.unwrap_or(peer).to_string()
+}
+
+pub fn throttled(throttle: Throttle) -> AppError {
+  let (code, secs) = match throttle {
+    Throttle::RateLimited(secs) => (MsgCode::TooManyAttempts, secs),
+    Throttle::Locked(secs) => (MsgCode::AccountLocked, secs),
+  };
+  AppError::TooManyRequests(Message::with_args(code, vec![secs.to_string()]), secs)
+}
+
+/// 审计日志中记录的失败原因
+fn failure_reason(e: &AuthFailure) -> &'static str {
+  match e {
+    AuthFailure::UserNotFound => REASON_USER_NOT_FOUND,
+    AuthFailure::WrongPassword => REASON_WRONG_PASSWORD,
+    AuthFailure::UserDisabled => REASON_USER_DISABLED,
+  }
 }
 
 #[post("/login")]
-async fn login(body: web::Json<user::Model>, data: web::Data<AppState>) -> Result<impl Responder> {
-  if let Err(e) = verify_user(body.id.clone(), body.password.clone(), &data.db_conn).await {
+async fn login(
+  req: HttpRequest,
+  body: web::Json<UserAuthReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let (ip, now) = (
+    client_ip(&req, &data.trusted_proxies),
+    Utc::now().naive_utc(),
+  );
+  let guard = &data.login_guard;
+  if let Some(x) = guard.check_login(&data.db_conn, &ip, &body.id, now).await? {
+    return Err(throttled(x));
+  }
+  let user_model = match UserService::authenticate(&data.db_conn, &body.id, &body.password).await? {
+    Ok(x) => x,
+    Err(e) => {
+      guard
+        .login_failed(&data.db_conn, &ip, &body.id, failure_reason(&e), now)
+        .await?;
+      // 不区分用户不存在与密码错误，避免探测用户名；密码正确时才提示账号已禁用
+      return Err(match e {
+        AuthFailure::UserDisabled => AppError::Forbidden(MsgCode::UserDisabled.into()),
+        _ => AppError::InvalidInput(MsgCode::InvalidCredentials.into()),
+      });
+    }
+  };
+  // 密码正确但尚未通过两步验证，失败计数留到验证码校验后再清除
+  if MfaService::is_enabled(&data.db_conn, &user_model.id).await? {
     return Ok(web::Json(UserLoginRes {
-      base: e,
+      base: BaseResponse::success(locale, MsgCode::MfaRequired),
       data: None,
+      mfa: Some(MfaChallenge {
+        mfa_token: AuthService::issue_mfa_token(&data.jwt_auth_secret, &user_model)?,
+        expires_in: MFA_TOKEN_TTL_SECS,
+      }),
     }));
+  }
+  guard.login_succeeded(&data.db_conn, &body.id).await?;
+  let token = AuthService::issue_tokens(&data.db_conn, &data.jwt_auth_secret, &user_model).await?;
+  Ok(login_res(token, locale, MsgCode::LoginSucceeded))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct RefreshTokenReq {
+  pub refresh_token: String,
+}
+
+#[post("/refresh")]
+async fn refresh(
+  body: web::Json<RefreshTokenReq>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let Some(token) =
+    AuthService::refresh(&data.db_conn, &data.jwt_auth_secret, &body.refresh_token).await?
+  else {
+    return Err(AppError::Unauthorized(MsgCode::SessionExpired.into()));
   };
-  let exp = time::SystemTime::now() + Duration::new(30 * 24 * 60 * 60, 0);
-  let Ok(auth_token) = encode(
-    &Header::default(),
-    &AuthClaims {
-      id: body.id.clone(),
-      exp: exp
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_secs() as usize,
-    },
-    &EncodingKey::from_secret(data.jwt_auth_secret.as_ref()),
-  ) else {
-    return Err(error::ErrorUnauthorized("Failed to encode token"));
-  };
-  Ok(web::Json(UserLoginRes {
-    base: BaseResponse {
-      ret: 0,
-      msg: "用户登录成功".to_string(),
-    },
-    data: Some(AuthToken { auth_token }),
-  }))
+  Ok(login_res(token, locale, MsgCode::TokenRefreshed))
+}
+
+#[post("/logout")]
+async fn logout(
+  body: web::Json<RefreshTokenReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  AuthService::revoke(&data.db_conn, &user_id, &body.refresh_token).await?;
+  Ok(web::Json(BaseResponse::success(locale, MsgCode::LoggedOut)))
+}
+
+#[post("/logoutAll")]
+async fn logout_all(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  AuthService::revoke_all(&data.db_conn, &user_id).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::LoggedOutAll,
+  )))
 }
 
 #[put("/create")]
 async fn create_user(
-  body: web::Json<user::Model>,
+  req: HttpRequest,
+  body: web::Json<UserAuthReq>,
   data: web::Data<AppState>,
-) -> Result<impl Responder> {
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  if let Some(secs) = data
+    .login_guard
+    .check_signup(
+      &client_ip(&req, &data.trusted_proxies),
+      Utc::now().naive_utc(),
+    )
+    .await?
+  {
+    return Err(throttled(Throttle::RateLimited(secs)));
+  }
+  let policy = &data.credential_policy;
+  policy
+    .check_user_id(&body.id)
+    .and_then(|_| policy.check_password(&body.id, &body.password))
+    .map_err(|e| policy_error(policy, e))?;
   if UserService::get_user(&data.db_conn, body.id.clone())
     .await
     .is_ok()
   {
-    return Ok(web::Json(BaseResponse {
-      ret: -1,
-      msg: "用户已存在，创建失败".to_string(),
-    }));
+    return Err(AppError::Conflict(MsgCode::UserExists.into()));
   }
   UserService::create_user(
     &data.db_conn,
     user::Model {
       id: body.id.clone(),
       password: generate_hash(body.password.clone()),
+      token_version: 0,
+      display_name: String::new(),
+      avatar_url: String::new(),
+      email: String::new(),
+      locale: None,
+      is_disabled: false,
     },
   )
-  .await
-  .map_or_else(
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "用户创建失败".to_string(),
-      }))
-    },
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: 0,
-        msg: "用户创建成功".to_string(),
-      }))
-    },
-  )
-}
-
-#[delete("/delete")]
-async fn delete_user(req: HttpRequest, data: web::Data<AppState>) -> Result<impl Responder> {
-  UserService::delete_user(
-    &data.db_conn,
-    req.extensions().get::<AuthClaims>().unwrap().id.clone(),
-  )
-  .await
-  .map_or_else(
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "用户删除失败".to_string(),
-      }))
-    },
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: 0,
-        msg: "用户删除成功".to_string(),
-      }))
-    },
-  )
+  .await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::UserCreated,
+  )))
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
@@ -247,33 +359,256 @@ async fn update_user(
   body: web::Json<UserUpdateReq>,
   req: HttpRequest,
   data: web::Data<AppState>,
-) -> Result<impl Responder> {
-  let user_id = req.extensions().get::<AuthClaims>().unwrap().id.clone();
-  if let Err(e) = verify_user(user_id.clone(), body.old_password.clone(), &data.db_conn).await {
-    return Ok(web::Json(e));
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  verify_user(user_id.clone(), body.old_password.clone(), &data.db_conn).await?;
+  let policy = &data.credential_policy;
+  policy
+    .check_password(&user_id, &body.new_password)
+    .map_err(|e| policy_error(policy, e))?;
+  // 修改密码后其他设备需重新登录
+  UserService::change_password(&data.db_conn, &user_id, &body.new_password).await?;
+  Ok(web::Json(BaseResponse::success(
+    locale,
+    MsgCode::PasswordUpdated,
+  )))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UserProfile {
+  pub id: String,
+  pub display_name: String,
+  pub avatar_url: String,
+  pub email: String,
+  /// 未设置时为空，按 Accept-Language 返回
+  pub locale: Option<String>,
+}
+
+impl From<user::Model> for UserProfile {
+  fn from(x: user::Model) -> Self {
+    UserProfile {
+      id: x.id,
+      display_name: x.display_name,
+      avatar_url: x.avatar_url,
+      email: x.email,
+      locale: x.locale,
+    }
+  }
+}
+
+/// 参会人员等列表中展示的用户信息，未设置昵称时使用用户 id
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UserSummary {
+  pub id: String,
+  pub display_name: String,
+  pub avatar_url: String,
+}
+
+impl From<user::Model> for UserSummary {
+  fn from(x: user::Model) -> Self {
+    UserSummary {
+      display_name: if x.display_name.is_empty() {
+        x.id.clone()
+      } else {
+        x.display_name
+      },
+      id: x.id,
+      avatar_url: x.avatar_url,
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UserProfileRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<UserProfile>,
+}
+
+#[get("/me")]
+async fn get_me(
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let user = UserService::get_user(&data.db_conn, user_id)
+    .await
+    .map_err(|_| AppError::user_not_found())?;
+  Ok(web::Json(UserProfileRes {
+    base: BaseResponse::success(locale, MsgCode::ProfileFetched),
+    data: Some(UserProfile::from(user)),
+  }))
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UpdateProfileReq {
+  pub display_name: Option<String>,
+  /// http(s) 地址，空字符串表示清除
+  pub avatar_url: Option<String>,
+  /// 空字符串表示清除
+  pub email: Option<String>,
+  /// 如 zh-CN、en，空字符串表示跟随 Accept-Language
+  pub locale: Option<String>,
+}
+
+pub const MAX_DISPLAY_NAME_CHARS: usize = 32;
+const MAX_URL_CHARS: usize = 1024;
+const MAX_EMAIL_CHARS: usize = 254;
+
+fn is_valid_email(email: &str) -> bool {
+  let Some((local, domain)) = email.split_once('@') else {
+    return false;
   };
-  UserService::change_password(
+  !local.is_empty()
+    && domain.contains('.')
+    && !domain.starts_with('.')
+    && !domain.ends_with('.')
+    && !email.chars().any(|c| c.is_whitespace())
+    && email.chars().count() <= MAX_EMAIL_CHARS
+}
+
+/// 语言标签形如 `zh`、`zh-CN`
+fn is_valid_locale(locale: &str) -> bool {
+  let mut parts = locale.split('-');
+  let lang = parts.next().unwrap_or_default();
+  (2..=3).contains(&lang.len())
+    && lang.chars().all(|c| c.is_ascii_lowercase())
+    && parts.all(|p| (2..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// 校验并规范化资料修改，返回待更新的字段
+fn check_profile_req(body: &UpdateProfileReq) -> Result<UpdateProfileReq, AppError> {
+  let err = |code: MsgCode| AppError::InvalidInput(code.into());
+  let display_name = body.display_name.as_deref().map(str::trim);
+  if display_name.is_some_and(|x| x.chars().count() > MAX_DISPLAY_NAME_CHARS) {
+    return Err(err(MsgCode::DisplayNameTooLong));
+  }
+  // 昵称会写入日历订阅等文本格式，换行可能被用来注入内容
+  if display_name.is_some_and(|x| x.chars().any(char::is_control)) {
+    return Err(err(MsgCode::InvalidDisplayName));
+  }
+  let avatar_url = body.avatar_url.as_deref().map(str::trim);
+  if avatar_url.is_some_and(|x| {
+    !x.is_empty()
+      && (!(x.starts_with("https://") || x.starts_with("http://"))
+        || x.chars().count() > MAX_URL_CHARS)
+  }) {
+    return Err(err(MsgCode::InvalidAvatarUrl));
+  }
+  let email = body.email.as_deref().map(str::trim);
+  if email.is_some_and(|x| !x.is_empty() && !is_valid_email(x)) {
+    return Err(err(MsgCode::InvalidEmail));
+  }
+  let locale = body.locale.as_deref().map(str::trim);
+  if locale.is_some_and(|x| !x.is_empty() && !is_valid_locale(x)) {
+    return Err(err(MsgCode::UnsupportedLocale));
+  }
+  Ok(UpdateProfileReq {
+    display_name: display_name.map(str::to_string),
+    avatar_url: avatar_url.map(str::to_string),
+    email: email.map(str::to_string),
+    locale: locale.map(str::to_string),
+  })
+}
+
+#[patch("/me")]
+async fn update_me(
+  body: web::Json<UpdateProfileReq>,
+  req: HttpRequest,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let user_id = AuthClaims::user_id(&req)?;
+  let body = check_profile_req(&body)?;
+  let set = |x: Option<String>| x.map_or(ActiveValue::NotSet, ActiveValue::Set);
+  let user = UserService::update_profile(
     &data.db_conn,
     user::ActiveModel {
       id: ActiveValue::Set(user_id),
-      password: ActiveValue::Set(generate_hash(body.new_password.clone())),
-    },
-  )
-  .await
-  .map_or_else(
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: -1,
-        msg: "用户更新失败".to_string(),
-      }))
-    },
-    |_| {
-      Ok(web::Json(BaseResponse {
-        ret: 0,
-        msg: "用户更新成功".to_string(),
-      }))
+      display_name: set(body.display_name),
+      avatar_url: set(body.avatar_url),
+      email: set(body.email),
+      locale: body.locale.map_or(ActiveValue::NotSet, |x| {
+        ActiveValue::Set(Some(x).filter(|x| !x.is_empty()))
+      }),
+      ..Default::default()
     },
   )
+  .await?;
+  Ok(web::Json(UserProfileRes {
+    base: BaseResponse::success(locale, MsgCode::ProfileUpdated),
+    data: Some(UserProfile::from(user)),
+  }))
+}
+
+const DEFAULT_SEARCH_PAGE_SIZE: u64 = 20;
+const MAX_SEARCH_PAGE_SIZE: u64 = 100;
+const MAX_SEARCH_CHARS: usize = 64;
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UserSearchQuery {
+  pub q: String,
+  #[ts(type = "number | null")]
+  pub page: Option<u64>,
+  #[ts(type = "number | null")]
+  pub page_size: Option<u64>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UserSearchPage {
+  #[ts(type = "number")]
+  pub total: u64,
+  #[ts(type = "number")]
+  pub page: u64,
+  #[ts(type = "number")]
+  pub page_size: u64,
+  pub users: Vec<UserSummary>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub struct UserSearchRes {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub data: Option<UserSearchPage>,
+}
+
+#[get("/search")]
+async fn search_users(
+  query: web::Query<UserSearchQuery>,
+  data: web::Data<AppState>,
+  locale: Locale,
+) -> Result<impl Responder, AppError> {
+  let q = query.q.trim();
+  if q.is_empty() || q.chars().count() > MAX_SEARCH_CHARS {
+    return Err(AppError::InvalidInput(Message::with_args(
+      MsgCode::InvalidSearchQuery,
+      vec![MAX_SEARCH_CHARS.to_string()],
+    )));
+  }
+  let page = query.page.unwrap_or(1).max(1);
+  let page_size = query
+    .page_size
+    .unwrap_or(DEFAULT_SEARCH_PAGE_SIZE)
+    .clamp(1, MAX_SEARCH_PAGE_SIZE);
+  let (users, total) = UserService::search_users(&data.db_conn, q, page, page_size).await?;
+  Ok(web::Json(UserSearchRes {
+    base: BaseResponse::success(locale, MsgCode::UsersSearched),
+    data: Some(UserSearchPage {
+      total,
+      page,
+      page_size,
+      users: users.into_iter().map(UserSummary::from).collect(),
+    }),
+  }))
 }
 
 pub fn get_user_scope() -> Scope {
@@ -281,6 +616,273 @@ pub fn get_user_scope() -> Scope {
     .service(get_gpt_filter)
     .service(create_user)
     .service(delete_user)
+    .service(get_account_deletion)
+    .service(cancel_account_deletion)
+    .service(export_account)
     .service(update_user)
     .service(login)
+    .service(login_mfa)
+    .service(get_mfa_status)
+    .service(setup_mfa)
+    .service(enable_mfa)
+    .service(disable_mfa)
+    .service(regenerate_recovery_codes)
+    .service(refresh)
+    .service(logout)
+    .service(logout_all)
+    .service(get_me)
+    .service(update_me)
+    .service(search_users)
+    .service(create_calendar_feed)
+    .service(revoke_calendar_feed)
+    .service(get_calendar_ics)
+    .service(get_filter_scope())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+
+  use actix_web::test::{
+    call_and_read_body_json, call_service, init_service, read_body_json, TestRequest,
+  };
+  use actix_web::{http::header, middleware::from_fn, App};
+  use serde_json::json;
+
+  use super::*;
+  use sea_orm::EntityTrait;
+
+  use crate::entities::login_failure;
+  use crate::i18n::localize;
+  use crate::services::llm::MockLlmClient;
+  use crate::services::rate_limit::{LOCKOUT_THRESHOLD, REASON_LOCKED, SIGNUP_IP_WINDOW};
+  use crate::test_utils::{create_user, setup_db, test_state};
+
+  #[actix_web::test]
+  async fn gpt_filter_retries_unparsable_output() {
+    let mut state = test_state(setup_db().await);
+    let client = Arc::new(MockLlmClient::with_replies(vec![
+      "好的，这是一个怀旧滤镜",
+      "```json\n{\"brightness\":1.2,\"sepia\":1.5,\"vignette\":1}\n```",
+    ]));
+    state.llm_client = client.clone();
+    let app = init_service(
+      App::new()
+        .app_data(web::Data::new(state))
+        .service(get_gpt_filter),
+    )
+    .await;
+    let req = TestRequest::post()
+      .uri("/getGptFilter")
+      .set_json(GptFilterReq {
+        prompt: "怀旧".to_string(),
+      })
+      .to_request();
+    let res: serde_json::Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(res["ret"], 0);
+    assert_eq!(res["data"]["brightness"], 1.2);
+    assert_eq!(res["data"]["sepia"], 1.0);
+    assert!(res["data"]["vignette"].is_null());
+    assert!(res["data"]["blur"].is_null());
+    assert_eq!(client.requests.lock().unwrap().len(), 2);
+  }
+
+  #[actix_web::test]
+  async fn messages_follow_request_locale() {
+    let db = setup_db().await;
+    let app = init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .wrap(from_fn(localize))
+        .service(get_user_scope()),
+    )
+    .await;
+    let login_req = |id: &str, lang: &str| {
+      TestRequest::post()
+        .uri("/api/user/login")
+        .insert_header((header::ACCEPT_LANGUAGE, lang))
+        .set_json(json!({ "id": id, "password": "x" }))
+        .to_request()
+    };
+    let res = call_service(&app, login_req("nobody", "en-US,en;q=0.9")).await;
+    assert_eq!(res.status(), 400);
+    let res: serde_json::Value = read_body_json(res).await;
+    assert_eq!(res["ret"], -1);
+    assert_eq!(res["msg"], "Incorrect user id or password");
+    assert_eq!(res["msg_code"], "invalid_credentials");
+    assert_eq!(res["code"], "invalid_input");
+    let res: serde_json::Value = call_and_read_body_json(&app, login_req("nobody", "fr")).await;
+    assert_eq!(res["msg"], "用户名或密码错误");
+
+    let create_req = |id: &str, password: &str| {
+      TestRequest::put()
+        .uri("/api/user/create")
+        .insert_header((header::ACCEPT_LANGUAGE, "en-GB"))
+        .set_json(json!({ "id": id, "password": password }))
+        .to_request()
+    };
+    let res: serde_json::Value = call_and_read_body_json(&app, create_req("al", "x")).await;
+    assert_eq!(
+      res["msg"],
+      "User id must be 3-32 letters, digits or _-., starting with a letter or digit"
+    );
+    let res: serde_json::Value = call_and_read_body_json(&app, create_req("alice", "x")).await;
+    assert_eq!(res["msg"], "Password must be at least 8 characters");
+    let res: serde_json::Value =
+      call_and_read_body_json(&app, create_req("alice", "Secret-123")).await;
+    assert_eq!(res["msg"], "User created");
+    assert_eq!(res["msg_code"], "user_created");
+    let user = UserService::get_user(&db, "alice".to_string())
+      .await
+      .unwrap();
+    assert_eq!(user.locale, None);
+
+    // 密码错误与用户不存在的响应相同
+    let res: serde_json::Value = call_and_read_body_json(&app, login_req("alice", "fr")).await;
+    assert_eq!(res["msg_code"], "invalid_credentials");
+  }
+
+  #[actix_web::test]
+  async fn login_is_throttled_and_audited() {
+    let db = setup_db().await;
+    let app = init_service(
+      App::new()
+        .app_data(web::Data::new(test_state(db.clone())))
+        .service(get_user_scope()),
+    )
+    .await;
+    let req = |uri: &str, id: &str, password: &str| {
+      TestRequest::default()
+        .method(if uri.ends_with("create") {
+          actix_web::http::Method::PUT
+        } else {
+          actix_web::http::Method::POST
+        })
+        .uri(uri)
+        .peer_addr("10.0.0.1:5000".parse().unwrap())
+        .set_json(json!({ "id": id, "password": password }))
+        .to_request()
+    };
+    let res = call_service(&app, req("/api/user/create", "alice", "Secret-123")).await;
+    assert_eq!(res.status(), 200);
+    for _ in 0..LOCKOUT_THRESHOLD {
+      let res = call_service(&app, req("/api/user/login", "alice", "wrong")).await;
+      assert_eq!(res.status(), 400);
+    }
+    // 锁定期间密码正确也无法登录
+    let res = call_service(&app, req("/api/user/login", "alice", "Secret-123")).await;
+    assert_eq!(res.status(), 429);
+    assert_eq!(res.headers().get(header::RETRY_AFTER).unwrap(), "60");
+    let res: serde_json::Value = read_body_json(res).await;
+    assert_eq!(res["code"], "too_many_requests");
+    assert_eq!(res["msg_code"], "account_locked");
+    let reasons = login_failure::Entity::find()
+      .all(&db)
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|x| x.reason)
+      .collect::<Vec<_>>();
+    assert_eq!(reasons.len(), LOCKOUT_THRESHOLD as usize + 1);
+    assert_eq!(reasons.last().unwrap(), REASON_LOCKED);
+
+    for i in 1..SIGNUP_IP_WINDOW.max {
+      let res = call_service(
+        &app,
+        req("/api/user/create", &format!("user{i}"), "Secret-123"),
+      )
+      .await;
+      assert_eq!(res.status(), 200);
+    }
+    let res = call_service(&app, req("/api/user/create", "mallory", "Secret-123")).await;
+    assert_eq!(res.status(), 429);
+  }
+
+  #[test]
+  fn client_ip_trusts_only_configured_proxies() {
+    let req = |peer: &str| {
+      TestRequest::default()
+        .peer_addr(peer.parse().unwrap())
+        .insert_header(("X-Forwarded-For", "203.0.113.7, 10.0.0.2"))
+        .to_http_request()
+    };
+    let proxies = ["10.0.0.1".parse().unwrap()];
+    assert_eq!(client_ip(&req("10.0.0.1:5000"), &proxies), "203.0.113.7");
+    assert_eq!(client_ip(&req("10.0.0.9:5000"), &proxies), "10.0.0.9");
+    assert_eq!(client_ip(&req("10.0.0.1:5000"), &[]), "10.0.0.1");
+    let req = TestRequest::default()
+      .peer_addr("10.0.0.1:5000".parse().unwrap())
+      .insert_header(("X-Forwarded-For", "not an ip"))
+      .to_http_request();
+    assert_eq!(client_ip(&req, &proxies), "10.0.0.1");
+  }
+
+  #[test]
+  fn profile_req_is_validated() {
+    let req = |avatar_url: &str, email: &str, locale: &str| UpdateProfileReq {
+      display_name: Some("  小明 ".to_string()),
+      avatar_url: Some(avatar_url.to_string()),
+      email: Some(email.to_string()),
+      locale: Some(locale.to_string()),
+    };
+    let res = check_profile_req(&req("https://a.com/x.png", "a@b.com", "en")).unwrap();
+    assert_eq!(res.display_name.as_deref(), Some("小明"));
+    assert!(check_profile_req(&req("", "", "zh-CN")).is_ok());
+    assert!(check_profile_req(&req("javascript:alert(1)", "", "en")).is_err());
+    assert!(check_profile_req(&req("", "a@b", "en")).is_err());
+    assert!(check_profile_req(&req("", "", "EN_us")).is_err());
+    let mut bad = req("", "", "en");
+    bad.display_name = Some("a\r\nDTSTART:19700101T000000Z".to_string());
+    assert!(check_profile_req(&bad).is_err());
+  }
+
+  #[actix_web::test]
+  async fn search_matches_prefixes_with_index() {
+    let db = setup_db().await;
+    for id in ["bob", "alice", "malice", "al_x", "alfred"] {
+      create_user(&db, id).await;
+    }
+    UserService::update_profile(
+      &db,
+      user::ActiveModel {
+        id: ActiveValue::Set("bob".to_string()),
+        display_name: ActiveValue::Set("Alan".to_string()),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    UserService::update_profile(
+      &db,
+      user::ActiveModel {
+        id: ActiveValue::Set("alfred".to_string()),
+        is_disabled: ActiveValue::Set(true),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+
+    // 前缀匹配在前，子串匹配的 malice 排在最后，且每个用户只计一次
+    let (users, total) = UserService::search_users(&db, "AL", 1, 10).await.unwrap();
+    let ids = users.iter().map(|x| x.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(total, 4);
+    assert_eq!(ids, vec!["al_x", "alice", "bob", "malice"]);
+
+    // 通配符按字面匹配
+    let (users, total) = UserService::search_users(&db, "al_", 1, 10).await.unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(users[0].id, "al_x");
+    let (users, total) = UserService::search_users(&db, "lice", 1, 10).await.unwrap();
+    let ids = users.iter().map(|x| x.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(total, 2);
+    assert_eq!(ids, vec!["alice", "malice"]);
+    let (users, _) = UserService::search_users(&db, "ali", 1, 10).await.unwrap();
+    let ids = users.iter().map(|x| x.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["alice", "malice"]);
+
+    let (users, _) = UserService::search_users(&db, "al", 2, 2).await.unwrap();
+    let ids = users.iter().map(|x| x.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["bob", "malice"]);
+  }
 }
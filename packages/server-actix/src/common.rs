@@ -1,26 +1,122 @@
+use std::net::IpAddr;
 use std::sync::Arc;
 
+use actix_web::{HttpMessage, HttpRequest};
 use futures_util::lock::Mutex;
 use livekit_api::services::egress::EgressClient;
+use sea_orm::sqlx::types::chrono::{DateTime, NaiveDateTime};
 use sea_orm::DatabaseConnection;
 use ts_rs::TS;
 
+use crate::error::AppError;
+use crate::i18n::{Locale, MsgCode};
+use crate::services::credential::CredentialPolicy;
+use crate::services::livekit::LiveKitRoomClient;
+use crate::services::llm::LlmClient;
+use crate::services::rate_limit::LoginGuard;
+
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct AuthClaims {
   pub id: String,
   pub exp: usize,
+  /// 签发时用户的 token_version，不一致时 token 失效
+  pub ver: i32,
+}
+
+impl AuthClaims {
+  /// 鉴权中间件写入请求的当前用户 id
+  pub fn user_id(req: &HttpRequest) -> Result<String, AppError> {
+    req
+      .extensions()
+      .get::<AuthClaims>()
+      .map(|x| x.id.clone())
+      .ok_or(AppError::Unauthorized(MsgCode::LoginRequired.into()))
+  }
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
 #[ts(export, export_to = "../../app-tauri/src/types/base.ts")]
 pub struct AuthToken {
   pub auth_token: String,
+  pub refresh_token: String,
+  /// auth_token 有效期，秒
+  #[ts(type = "number")]
+  pub expires_in: u64,
 }
 
-#[derive(serde::Deserialize, serde::Serialize, TS)]
+/// 支持的 css 滤镜，未设置的滤镜为 null
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Debug, Default, PartialEq)]
 #[ts(export, export_to = "../../app-tauri/src/types/base.ts")]
-pub struct Filter {
-  pub filter: String,
+pub struct CssFilter {
+  pub blur: Option<f64>,
+  pub brightness: Option<f64>,
+  pub contrast: Option<f64>,
+  pub grayscale: Option<f64>,
+  #[serde(rename = "hue-rotate")]
+  pub hue_rotate: Option<f64>,
+  pub invert: Option<f64>,
+  pub opacity: Option<f64>,
+  pub saturate: Option<f64>,
+  pub sepia: Option<f64>,
+}
+
+impl CssFilter {
+  /// 各滤镜的取值范围 (名称, 最小值, 最大值)
+  pub const LIMITS: [(&'static str, f64, f64); 9] = [
+    ("blur", 0.0, 10.0),
+    ("brightness", 0.0, 3.0),
+    ("contrast", 0.0, 3.0),
+    ("grayscale", 0.0, 1.0),
+    ("hue-rotate", 0.0, 360.0),
+    ("invert", 0.0, 1.0),
+    ("opacity", 0.0, 1.0),
+    ("saturate", 0.0, 3.0),
+    ("sepia", 0.0, 1.0),
+  ];
+
+  fn field_mut(&mut self, name: &str) -> Option<&mut Option<f64>> {
+    match name {
+      "blur" => Some(&mut self.blur),
+      "brightness" => Some(&mut self.brightness),
+      "contrast" => Some(&mut self.contrast),
+      "grayscale" => Some(&mut self.grayscale),
+      "hue-rotate" => Some(&mut self.hue_rotate),
+      "invert" => Some(&mut self.invert),
+      "opacity" => Some(&mut self.opacity),
+      "saturate" => Some(&mut self.saturate),
+      "sepia" => Some(&mut self.sepia),
+      _ => None,
+    }
+  }
+
+  /// 将取值限制在各滤镜的范围内，非有限数值视为未设置
+  pub fn clamp(mut self) -> Self {
+    for (name, min, max) in Self::LIMITS {
+      if let Some(value) = self.field_mut(name) {
+        *value = value.filter(|v| v.is_finite()).map(|v| v.clamp(min, max));
+      }
+    }
+    self
+  }
+
+  /// 解析 json 对象，忽略未知的滤镜和非数值参数，并限制取值范围
+  pub fn parse(content: &str) -> Option<Self> {
+    let serde_json::Value::Object(map) = serde_json::from_str(content).ok()? else {
+      return None;
+    };
+    let mut filter = CssFilter::default();
+    for (key, value) in map {
+      let value = match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+      };
+      if let Some(field) = filter.field_mut(&key) {
+        *field = value;
+      }
+    }
+    Some(filter.clamp())
+  }
 }
 
 #[derive(serde::Deserialize, serde::Serialize, TS)]
@@ -42,20 +138,73 @@ pub struct AppState {
   pub db_conn: DatabaseConnection,
   pub livekit_secret: String,
   pub livekit_key: String,
+  #[allow(dead_code)]
   pub livekit_url: String,
   pub livekit_egress_client: Arc<Mutex<EgressClient>>,
+  pub livekit_room_client: Arc<dyn LiveKitRoomClient>,
   pub s3_access_key: String,
   pub s3_secret: String,
   pub s3_endpoint: String,
   pub s3_bucket: String,
   pub s3_public_url: String,
-  pub gpt_api_key: String,
-  pub gpt_base_url: String,
+  pub llm_client: Arc<dyn LlmClient>,
+  pub login_guard: LoginGuard,
+  /// 受信任的反向代理地址，来自这些地址的请求按转发请求头取客户端地址
+  pub trusted_proxies: Vec<IpAddr>,
+  pub credential_policy: CredentialPolicy,
+  /// 加密两步验证密钥的 AES-256 密钥
+  pub mfa_key: [u8; 32],
 }
 
-#[derive(serde::Deserialize, serde::Serialize, TS)]
+#[derive(serde::Deserialize, serde::Serialize, TS, Debug)]
 #[ts(export, export_to = "../../app-tauri/src/types/base.ts")]
 pub struct BaseResponse {
   pub ret: i32,
   pub msg: String,
+  /// 接入消息码的接口返回，客户端可据此自行翻译
+  #[serde(default)]
+  pub msg_code: Option<MsgCode>,
+}
+
+impl BaseResponse {
+  pub fn success(locale: Locale, code: MsgCode) -> Self {
+    BaseResponse {
+      ret: 0,
+      msg: code.render(locale, &[]),
+      msg_code: Some(code),
+    }
+  }
+}
+
+/// 将前端传入的秒级时间戳转换为数据库使用的 `NaiveDateTime`
+pub fn timestamp_to_datetime(timestamp: f64) -> NaiveDateTime {
+  DateTime::from_timestamp(timestamp as i64, 0)
+    .unwrap_or_default()
+    .naive_utc()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn css_filter_parse_clamps_and_drops_unknown() {
+    let filter =
+      CssFilter::parse(r#"{"blur":20,"hue-rotate":"-30","sepia":0.5,"glow":1,"invert":true}"#)
+        .unwrap();
+    assert_eq!(
+      filter,
+      CssFilter {
+        blur: Some(10.0),
+        hue_rotate: Some(0.0),
+        sepia: Some(0.5),
+        ..Default::default()
+      }
+    );
+    let value = serde_json::to_value(&filter).unwrap();
+    assert_eq!(value["hue-rotate"], 0.0);
+    assert!(value["brightness"].is_null());
+    assert_eq!(CssFilter::parse("{}"), Some(CssFilter::default()));
+    assert_eq!(CssFilter::parse("[1]"), None);
+  }
 }
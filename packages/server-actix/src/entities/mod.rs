@@ -2,6 +2,22 @@
 
 pub mod prelude;
 
+pub mod account_deletion;
+pub mod calendar_token;
+pub mod filter_preset;
+pub mod login_failure;
+pub mod login_lockout;
+pub mod meeting_series;
+pub mod meeting_summary;
+pub mod mfa_recovery_code;
+pub mod moderation_log;
+pub mod rate_limit_hit;
+pub mod recording;
+pub mod refresh_token;
 pub mod room;
+pub mod room_admission;
+pub mod room_invite;
 pub mod room_user;
+pub mod transcript_segment;
 pub mod user;
+pub mod user_mfa;
@@ -1,5 +1,23 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.4
 
+#![allow(unused_imports)]
+
+pub use super::account_deletion::Entity as AccountDeletion;
+pub use super::calendar_token::Entity as CalendarToken;
+pub use super::filter_preset::Entity as FilterPreset;
+pub use super::login_failure::Entity as LoginFailure;
+pub use super::login_lockout::Entity as LoginLockout;
+pub use super::meeting_series::Entity as MeetingSeries;
+pub use super::meeting_summary::Entity as MeetingSummary;
+pub use super::mfa_recovery_code::Entity as MfaRecoveryCode;
+pub use super::moderation_log::Entity as ModerationLog;
+pub use super::rate_limit_hit::Entity as RateLimitHit;
+pub use super::recording::Entity as Recording;
+pub use super::refresh_token::Entity as RefreshToken;
 pub use super::room::Entity as Room;
+pub use super::room_admission::Entity as RoomAdmission;
+pub use super::room_invite::Entity as RoomInvite;
 pub use super::room_user::Entity as RoomUser;
+pub use super::transcript_segment::Entity as TranscriptSegment;
 pub use super::user::Entity as User;
+pub use super::user_mfa::Entity as UserMfa;
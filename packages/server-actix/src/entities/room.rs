@@ -11,16 +11,43 @@ pub struct Model {
   pub code: String,
   pub is_canceled: bool,
   pub cur_egress_id: String,
-  pub record_videos: String,
   pub start_time: DateTime,
   pub end_time: DateTime,
   pub admin: String,
+  pub pinned_filter_id: Option<i32>,
+  pub lobby_enabled: bool,
+  pub series_id: Option<i32>,
+  pub occurrence_start: Option<DateTime>,
+  pub code_released: bool,
+  pub deleted_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+  #[sea_orm(has_many = "super::filter_preset::Entity")]
+  FilterPreset,
+  #[sea_orm(
+    belongs_to = "super::meeting_series::Entity",
+    from = "Column::SeriesId",
+    to = "super::meeting_series::Column::Id",
+    on_update = "NoAction",
+    on_delete = "NoAction"
+  )]
+  MeetingSeries,
+  #[sea_orm(has_many = "super::meeting_summary::Entity")]
+  MeetingSummary,
+  #[sea_orm(has_many = "super::moderation_log::Entity")]
+  ModerationLog,
+  #[sea_orm(has_many = "super::recording::Entity")]
+  Recording,
+  #[sea_orm(has_many = "super::room_admission::Entity")]
+  RoomAdmission,
+  #[sea_orm(has_many = "super::room_invite::Entity")]
+  RoomInvite,
   #[sea_orm(has_many = "super::room_user::Entity")]
   RoomUser,
+  #[sea_orm(has_many = "super::transcript_segment::Entity")]
+  TranscriptSegment,
   #[sea_orm(
     belongs_to = "super::user::Entity",
     from = "Column::Admin",
@@ -31,6 +58,48 @@ pub enum Relation {
   User,
 }
 
+impl Related<super::filter_preset::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::FilterPreset.def()
+  }
+}
+
+impl Related<super::meeting_series::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::MeetingSeries.def()
+  }
+}
+
+impl Related<super::meeting_summary::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::MeetingSummary.def()
+  }
+}
+
+impl Related<super::moderation_log::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::ModerationLog.def()
+  }
+}
+
+impl Related<super::recording::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Recording.def()
+  }
+}
+
+impl Related<super::room_admission::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::RoomAdmission.def()
+  }
+}
+
+impl Related<super::room_invite::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::RoomInvite.def()
+  }
+}
+
 impl Related<super::room_user::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::RoomUser.def()
@@ -43,4 +112,10 @@ impl Related<super::user::Entity> for Entity {
   }
 }
 
+impl Related<super::transcript_segment::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::TranscriptSegment.def()
+  }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -10,6 +10,7 @@ pub struct Model {
   pub id: i32,
   pub user_id: String,
   pub room_id: i32,
+  pub role: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
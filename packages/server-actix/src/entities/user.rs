@@ -9,14 +9,100 @@ pub struct Model {
   #[sea_orm(primary_key, auto_increment = false)]
   pub id: String,
   pub password: String,
+  pub token_version: i32,
+  pub display_name: String,
+  pub avatar_url: String,
+  pub email: String,
+  pub locale: Option<String>,
+  pub is_disabled: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+  #[sea_orm(has_one = "super::account_deletion::Entity")]
+  AccountDeletion,
+  #[sea_orm(has_one = "super::calendar_token::Entity")]
+  CalendarToken,
+  #[sea_orm(has_many = "super::filter_preset::Entity")]
+  FilterPreset,
+  #[sea_orm(has_many = "super::meeting_series::Entity")]
+  MeetingSeries,
+  #[sea_orm(has_many = "super::meeting_summary::Entity")]
+  MeetingSummary,
+  #[sea_orm(has_many = "super::mfa_recovery_code::Entity")]
+  MfaRecoveryCode,
+  #[sea_orm(has_many = "super::moderation_log::Entity")]
+  ModerationLog,
+  #[sea_orm(has_many = "super::recording::Entity")]
+  Recording,
+  #[sea_orm(has_many = "super::refresh_token::Entity")]
+  RefreshToken,
   #[sea_orm(has_many = "super::room::Entity")]
   Room,
+  #[sea_orm(has_many = "super::room_admission::Entity")]
+  RoomAdmission,
+  #[sea_orm(has_many = "super::room_invite::Entity")]
+  RoomInvite,
   #[sea_orm(has_many = "super::room_user::Entity")]
   RoomUser,
+  #[sea_orm(has_many = "super::transcript_segment::Entity")]
+  TranscriptSegment,
+  #[sea_orm(has_one = "super::user_mfa::Entity")]
+  UserMfa,
+}
+
+impl Related<super::account_deletion::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::AccountDeletion.def()
+  }
+}
+
+impl Related<super::calendar_token::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::CalendarToken.def()
+  }
+}
+
+impl Related<super::filter_preset::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::FilterPreset.def()
+  }
+}
+
+impl Related<super::meeting_series::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::MeetingSeries.def()
+  }
+}
+
+impl Related<super::meeting_summary::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::MeetingSummary.def()
+  }
+}
+
+impl Related<super::mfa_recovery_code::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::MfaRecoveryCode.def()
+  }
+}
+
+impl Related<super::moderation_log::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::ModerationLog.def()
+  }
+}
+
+impl Related<super::recording::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::Recording.def()
+  }
+}
+
+impl Related<super::refresh_token::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::RefreshToken.def()
+  }
 }
 
 impl Related<super::room::Entity> for Entity {
@@ -25,10 +111,34 @@ impl Related<super::room::Entity> for Entity {
   }
 }
 
+impl Related<super::room_admission::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::RoomAdmission.def()
+  }
+}
+
+impl Related<super::room_invite::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::RoomInvite.def()
+  }
+}
+
 impl Related<super::room_user::Entity> for Entity {
   fn to() -> RelationDef {
     Relation::RoomUser.def()
   }
 }
 
+impl Related<super::transcript_segment::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::TranscriptSegment.def()
+  }
+}
+
+impl Related<super::user_mfa::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::UserMfa.def()
+  }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
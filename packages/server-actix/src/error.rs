@@ -0,0 +1,209 @@
+use actix_web::{
+  http::{header, StatusCode},
+  HttpResponse, ResponseError,
+};
+use log::debug;
+use sea_orm::DbErr;
+use ts_rs::TS;
+
+use crate::common::BaseResponse;
+use crate::i18n::{Locale, Message, MsgCode};
+
+/// 稳定的错误类型码，客户端据此区分错误，msg 仅用于展示
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../app-tauri/src/types/base.ts")]
+pub enum ErrorCode {
+  InvalidInput,
+  Unauthorized,
+  Forbidden,
+  NotFound,
+  Conflict,
+  TooManyRequests,
+  Upstream,
+  Internal,
+}
+
+/// 出错时的响应体，保留 ret 与 msg 以兼容客户端
+#[derive(serde::Deserialize, serde::Serialize, TS, Debug)]
+#[ts(export, export_to = "../../app-tauri/src/types/base.ts")]
+pub struct ErrorResponse {
+  #[serde(flatten)]
+  pub base: BaseResponse,
+  pub code: ErrorCode,
+}
+
+#[derive(Debug)]
+pub enum AppError {
+  /// 请求参数不合法
+  InvalidInput(Message),
+  /// 未登录或登录已失效
+  Unauthorized(Message),
+  /// 已登录但无权操作
+  Forbidden(Message),
+  NotFound(Message),
+  /// 与当前状态冲突，如会议已在录制中
+  Conflict(Message),
+  /// 请求过于频繁或账号被临时锁定，附带可重试前需等待的秒数
+  TooManyRequests(Message, u64),
+  /// LiveKit、LLM 等外部服务调用失败
+  Upstream(Message),
+  Database(DbErr),
+  /// 数据库以外的内部错误，如签发 token、加解密与打包导出失败，细节只写入日志
+  Internal(String),
+}
+
+impl AppError {
+  pub fn room_not_found() -> Self {
+    AppError::NotFound(MsgCode::RoomNotFound.into())
+  }
+  pub fn user_not_found() -> Self {
+    AppError::NotFound(MsgCode::UserNotFound.into())
+  }
+  pub fn not_room_member() -> Self {
+    AppError::Forbidden(MsgCode::NotRoomMember.into())
+  }
+  /// 请求体、查询参数或路径解析失败，附带解析错误
+  pub fn invalid_request(e: impl std::fmt::Display) -> Self {
+    AppError::InvalidInput(Message::with_args(
+      MsgCode::InvalidRequest,
+      vec![e.to_string()],
+    ))
+  }
+  pub fn code(&self) -> ErrorCode {
+    match self {
+      AppError::InvalidInput(_) => ErrorCode::InvalidInput,
+      AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+      AppError::Forbidden(_) => ErrorCode::Forbidden,
+      AppError::NotFound(_) => ErrorCode::NotFound,
+      AppError::Conflict(_) => ErrorCode::Conflict,
+      AppError::TooManyRequests(..) => ErrorCode::TooManyRequests,
+      AppError::Upstream(_) => ErrorCode::Upstream,
+      AppError::Database(_) | AppError::Internal(_) => ErrorCode::Internal,
+    }
+  }
+  pub fn message(&self) -> Message {
+    match self {
+      AppError::InvalidInput(msg)
+      | AppError::Unauthorized(msg)
+      | AppError::Forbidden(msg)
+      | AppError::NotFound(msg)
+      | AppError::Conflict(msg)
+      | AppError::TooManyRequests(msg, _)
+      | AppError::Upstream(msg) => msg.clone(),
+      // 内部错误不暴露给客户端
+      AppError::Database(_) | AppError::Internal(_) => MsgCode::InternalError.into(),
+    }
+  }
+  /// 按语言生成响应体，`error_response` 使用默认语言
+  pub fn localized_response(&self, locale: Locale) -> HttpResponse {
+    match self {
+      AppError::Database(e) => debug!("database err: {:?}", e),
+      AppError::Internal(e) => debug!("internal err: {e}"),
+      _ => {}
+    }
+    let message = self.message();
+    let mut res = HttpResponse::build(self.status_code());
+    if let AppError::TooManyRequests(_, retry_after) = self {
+      res.insert_header((header::RETRY_AFTER, retry_after.to_string()));
+    }
+    res.json(ErrorResponse {
+      base: BaseResponse {
+        ret: self.ret(),
+        msg: message.render(locale),
+        msg_code: Some(message.code()),
+      },
+      code: self.code(),
+    })
+  }
+  /// 沿用原有的 ret 约定
+  pub fn ret(&self) -> i32 {
+    match self {
+      AppError::Unauthorized(_) | AppError::Forbidden(_) => -401,
+      AppError::NotFound(_) => -404,
+      AppError::Conflict(_) => -400,
+      AppError::TooManyRequests(..) => -429,
+      AppError::InvalidInput(_)
+      | AppError::Upstream(_)
+      | AppError::Database(_)
+      | AppError::Internal(_) => -1,
+    }
+  }
+}
+
+impl std::fmt::Display for AppError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.message().render(Locale::default()))
+  }
+}
+
+impl From<DbErr> for AppError {
+  fn from(e: DbErr) -> Self {
+    match e {
+      DbErr::RecordNotFound(_) => AppError::NotFound(MsgCode::RecordNotFound.into()),
+      e => AppError::Database(e),
+    }
+  }
+}
+
+impl From<jsonwebtoken::errors::Error> for AppError {
+  fn from(e: jsonwebtoken::errors::Error) -> Self {
+    AppError::Internal(format!("encode jwt failed: {e}"))
+  }
+}
+
+impl From<openssl::error::ErrorStack> for AppError {
+  fn from(e: openssl::error::ErrorStack) -> Self {
+    AppError::Internal(format!("crypto failed: {e}"))
+  }
+}
+
+impl ResponseError for AppError {
+  fn status_code(&self) -> StatusCode {
+    match self {
+      AppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+      AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+      AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+      AppError::NotFound(_) => StatusCode::NOT_FOUND,
+      AppError::Conflict(_) => StatusCode::CONFLICT,
+      AppError::TooManyRequests(..) => StatusCode::TOO_MANY_REQUESTS,
+      AppError::Upstream(_) => StatusCode::BAD_GATEWAY,
+      AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+  }
+  fn error_response(&self) -> HttpResponse {
+    self.localized_response(Locale::default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use actix_web::body::to_bytes;
+
+  use super::*;
+
+  #[actix_web::test]
+  async fn error_response_keeps_ret_and_msg() {
+    let res = AppError::from(DbErr::Custom("secret".to_string())).error_response();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: ErrorResponse =
+      serde_json::from_slice(&to_bytes(res.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body.base.ret, -1);
+    assert_eq!(body.base.msg, "服务器内部错误");
+    assert_eq!(body.code, ErrorCode::Internal);
+    let res = AppError::Internal("secret".to_string()).error_response();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body: ErrorResponse =
+      serde_json::from_slice(&to_bytes(res.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body.base.msg, "服务器内部错误");
+    assert_eq!(body.code, ErrorCode::Internal);
+
+    let res = AppError::room_not_found().error_response();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    let body: ErrorResponse =
+      serde_json::from_slice(&to_bytes(res.into_body()).await.unwrap()).unwrap();
+    assert_eq!(body.base.ret, -404);
+    assert_eq!(body.base.msg_code, Some(MsgCode::RoomNotFound));
+    assert_eq!(body.code, ErrorCode::NotFound);
+  }
+}
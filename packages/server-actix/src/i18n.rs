@@ -0,0 +1,640 @@
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+
+use actix_web::{
+  body::{BoxBody, MessageBody},
+  dev::{Payload, ServiceRequest, ServiceResponse},
+  http::header,
+  middleware::Next,
+  Error, FromRequest, HttpMessage, HttpRequest,
+};
+use ts_rs::TS;
+
+use crate::error::AppError;
+
+/// 服务端支持的语言，未能识别时使用 zh-CN
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+  #[default]
+  ZhCn,
+  EnUs,
+}
+
+impl Locale {
+  /// 按主语言匹配，如 zh-TW 使用 zh-CN，en-GB 使用 en-US
+  pub fn from_tag(tag: &str) -> Option<Self> {
+    let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    match primary.as_str() {
+      "zh" => Some(Locale::ZhCn),
+      "en" => Some(Locale::EnUs),
+      _ => None,
+    }
+  }
+  /// 解析 Accept-Language，按 q 值取第一个支持的语言
+  pub fn from_accept_language(value: &str) -> Option<Self> {
+    let mut tags = value
+      .split(',')
+      .filter_map(|item| {
+        let mut parts = item.split(';');
+        let tag = parts.next()?.trim();
+        let q = parts
+          .find_map(|x| x.trim().strip_prefix("q="))
+          .map_or(Some(1.0), |x| x.trim().parse::<f32>().ok())?;
+        (q > 0.0).then_some((tag, q))
+      })
+      .collect::<Vec<_>>();
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().find_map(|(tag, _)| Self::from_tag(tag))
+  }
+  /// 优先使用鉴权中间件写入的用户资料语言，其次为 Accept-Language
+  pub fn of(req: &HttpRequest) -> Self {
+    if let Some(locale) = req.extensions().get::<Locale>() {
+      return *locale;
+    }
+    req
+      .headers()
+      .get(header::ACCEPT_LANGUAGE)
+      .and_then(|x| x.to_str().ok())
+      .and_then(Self::from_accept_language)
+      .unwrap_or_default()
+  }
+}
+
+impl FromRequest for Locale {
+  type Error = Infallible;
+  type Future = Ready<Result<Self, Self::Error>>;
+
+  fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+    ready(Ok(Self::of(req)))
+  }
+}
+
+/// 稳定的消息码，客户端可据此自行翻译
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../app-tauri/src/types/base.ts")]
+pub enum MsgCode {
+  InternalError,
+  RecordNotFound,
+  LoginRequired,
+  SessionExpired,
+  UserNotFound,
+  UserDisabled,
+  WrongPassword,
+  UserExists,
+  UsersNotExist,
+  UserCreated,
+  UserDeleted,
+  PasswordUpdated,
+  LoginSucceeded,
+  TokenRefreshed,
+  LoggedOut,
+  LoggedOutAll,
+  ProfileFetched,
+  ProfileUpdated,
+  DisplayNameTooLong,
+  InvalidAvatarUrl,
+  InvalidEmail,
+  UnsupportedLocale,
+  InvalidSearchQuery,
+  UsersSearched,
+  LlmRequestFailed,
+  LlmParseFailed,
+  GptFilterFetched,
+  RoomNotFound,
+  RoomCanceled,
+  NotRoomMember,
+  UserNotRoomMember,
+  RolePermissionDenied,
+  OnlyHostCanTransfer,
+  OnlyHostCanSetCoHost,
+  UseTransferHost,
+  HostMustBeAttendee,
+  NotEnoughAttendees,
+  RoomsFetched,
+  RoomCreated,
+  RoomUpdated,
+  RoleUpdated,
+  RoomTokenIssued,
+  RoomTokenFailed,
+  RoomRecording,
+  RoomNotRecording,
+  EgressBusy,
+  RecordFailed,
+  RecordStarted,
+  StopRecordFailed,
+  RecordStopped,
+  InviteCreated,
+  InvitesFetched,
+  InviteRevoked,
+  InviteNotFound,
+  InviteInvalid,
+  InvalidInviteExpiry,
+  InvalidInviteMaxUses,
+  InvalidGuestName,
+  GuestJoined,
+  LobbyWaiting,
+  LobbyDenied,
+  AdmissionsFetched,
+  AdmissionFetched,
+  AdmissionNotFound,
+  ParticipantAdmitted,
+  ParticipantDenied,
+  ParticipantsFetched,
+  ParticipantNotFound,
+  ParticipantMuted,
+  ParticipantUnmuted,
+  ParticipantRemoved,
+  ParticipantUpdated,
+  EmptyParticipantUpdate,
+  CannotModerateHost,
+  ModerationFailed,
+  ModerationLogsFetched,
+  InvalidRrule,
+  InvalidSeriesTime,
+  SeriesCreated,
+  SeriesFetched,
+  SeriesUpdated,
+  SeriesNotFound,
+  OnlySeriesAdmin,
+  IcsSummary,
+  IcsDescription,
+  IcsCalendarName,
+  CalendarTokenIssued,
+  CalendarTokenInvalid,
+  RoomEndBeforeStart,
+  RoomStartInPast,
+  RoomTooLong,
+  ScheduleConflict,
+  InvalidAvailabilityRange,
+  AvailabilityFetched,
+  RoomResolved,
+  RoomDeleted,
+  OnlyHostCanDelete,
+  SeriesOccurrenceNotDeletable,
+  AccountDeletionScheduled,
+  AccountDeletionCanceled,
+  AccountDeletionFetched,
+  NoPendingDeletion,
+  AccountDataExported,
+  TooManyAttempts,
+  AccountLocked,
+  InvalidUserId,
+  PasswordTooShort,
+  PasswordTooLong,
+  PasswordTooWeak,
+  PasswordTooCommon,
+  PasswordContainsUserId,
+  InvalidCredentials,
+  MfaRequired,
+  MfaChallengeExpired,
+  InvalidMfaCode,
+  MfaAlreadyEnabled,
+  MfaNotSetUp,
+  MfaSetupStarted,
+  MfaEnabled,
+  MfaDisabled,
+  MfaStatusFetched,
+  RecoveryCodesRegenerated,
+  InvalidDisplayName,
+  CalendarTokenRevoked,
+  NoCalendarFeed,
+  CannotModerateCoHost,
+  UserIdReserved,
+  EgressMismatch,
+  NoTranscript,
+  SummaryRunning,
+  SummaryNotFound,
+  SummaryFetched,
+  TranscriptBatchTooLarge,
+  InvalidTranscriptRange,
+  TranscriptUploaded,
+  TranscriptFetched,
+  UnsupportedExportFormat,
+  FilterPresetNotFound,
+  InvalidPresetName,
+  NotMemberCannotShare,
+  PresetRoomRequired,
+  UnsupportedVisibility,
+  FilterPresetsFetched,
+  FilterPresetCreated,
+  NotPresetOwner,
+  FilterPresetUpdated,
+  FilterPresetDeleted,
+  PresetNotPinnable,
+  FilterPresetPinned,
+  NoPinnedFilter,
+  PinnedFilterFetched,
+  InvalidWebhook,
+  WebhookReceived,
+  InvalidRequest,
+}
+
+impl MsgCode {
+  /// 对应语言的文案，`{}` 依次由参数替换
+  pub fn template(self, locale: Locale) -> &'static str {
+    match locale {
+      Locale::ZhCn => zh_cn(self),
+      Locale::EnUs => en_us(self),
+    }
+  }
+  pub fn render(self, locale: Locale, args: &[String]) -> String {
+    let mut parts = self.template(locale).split("{}");
+    let mut msg = parts.next().unwrap_or_default().to_string();
+    for (i, part) in parts.enumerate() {
+      msg.push_str(args.get(i).map_or("", String::as_str));
+      msg.push_str(part);
+    }
+    msg
+  }
+}
+
+fn zh_cn(code: MsgCode) -> &'static str {
+  match code {
+    MsgCode::InternalError => "服务器内部错误",
+    MsgCode::RecordNotFound => "找不到对应记录",
+    MsgCode::LoginRequired => "请先登录",
+    MsgCode::SessionExpired => "登录已失效，请重新登录",
+    MsgCode::UserNotFound => "用户不存在",
+    MsgCode::UserDisabled => "用户已被禁用",
+    MsgCode::WrongPassword => "用户密码错误",
+    MsgCode::UserExists => "用户已存在，创建失败",
+    MsgCode::UsersNotExist => "用户 {} 不存在",
+    MsgCode::UserCreated => "用户创建成功",
+    MsgCode::UserDeleted => "用户删除成功",
+    MsgCode::PasswordUpdated => "用户更新成功，请重新登录",
+    MsgCode::LoginSucceeded => "用户登录成功",
+    MsgCode::TokenRefreshed => "刷新 token 成功",
+    MsgCode::LoggedOut => "退出登录成功",
+    MsgCode::LoggedOutAll => "已退出全部设备",
+    MsgCode::ProfileFetched => "获取用户信息成功",
+    MsgCode::ProfileUpdated => "更新用户信息成功",
+    MsgCode::DisplayNameTooLong => "昵称过长",
+    MsgCode::InvalidAvatarUrl => "头像地址不合法",
+    MsgCode::InvalidEmail => "邮箱格式不正确",
+    MsgCode::UnsupportedLocale => "不支持的语言",
+    MsgCode::InvalidSearchQuery => "搜索内容需为 1 到 {} 个字符",
+    MsgCode::UsersSearched => "搜索用户成功",
+    MsgCode::LlmRequestFailed => "请求失败，请稍后再试",
+    MsgCode::LlmParseFailed => "解析失败，请稍后再试",
+    MsgCode::GptFilterFetched => "获取推荐滤镜成功",
+    MsgCode::RoomNotFound => "找不到对应会议",
+    MsgCode::RoomCanceled => "会议已取消",
+    MsgCode::NotRoomMember => "非会议成员无权操作",
+    MsgCode::UserNotRoomMember => "用户不是会议成员",
+    MsgCode::RolePermissionDenied => "当前角色无权操作",
+    MsgCode::OnlyHostCanTransfer => "仅主持人可移交主持人",
+    MsgCode::OnlyHostCanSetCoHost => "仅主持人可任免联席主持人",
+    MsgCode::UseTransferHost => "请通过移交主持人修改主持人",
+    MsgCode::HostMustBeAttendee => "主持人需为与会人员",
+    MsgCode::NotEnoughAttendees => "与会人数不足",
+    MsgCode::RoomsFetched => "获取会议列表成功",
+    MsgCode::RoomCreated => "会议创建成功",
+    MsgCode::RoomUpdated => "会议更新成功",
+    MsgCode::RoleUpdated => "设置角色成功",
+    MsgCode::RoomTokenIssued => "获取 room token 成功",
+    MsgCode::RoomTokenFailed => "获取 room token 失败",
+    MsgCode::RoomRecording => "会议已在录制中",
+    MsgCode::RoomNotRecording => "会议未在录制中",
+    MsgCode::EgressBusy => "获取 egress 失败",
+    MsgCode::RecordFailed => "录制会议失败",
+    MsgCode::RecordStarted => "会议录制进行中",
+    MsgCode::StopRecordFailed => "停止会议录制失败",
+    MsgCode::RecordStopped => "会议录制已停止",
+    MsgCode::InviteCreated => "邀请链接创建成功",
+    MsgCode::InvitesFetched => "获取邀请链接成功",
+    MsgCode::InviteRevoked => "邀请链接已失效",
+    MsgCode::InviteNotFound => "找不到对应邀请链接",
+    MsgCode::InviteInvalid => "邀请链接无效或已过期",
+    MsgCode::InvalidInviteExpiry => "有效期需为 1 分钟到 {} 天",
+    MsgCode::InvalidInviteMaxUses => "使用次数需大于 0",
+    MsgCode::InvalidGuestName => "昵称需为 1 到 {} 个字符",
+    MsgCode::GuestJoined => "加入会议成功",
+    MsgCode::LobbyWaiting => "等待主持人准入",
+    MsgCode::LobbyDenied => "主持人拒绝了你的加入请求",
+    MsgCode::AdmissionsFetched => "获取等候列表成功",
+    MsgCode::AdmissionFetched => "获取准入状态成功",
+    MsgCode::AdmissionNotFound => "找不到对应准入申请",
+    MsgCode::ParticipantAdmitted => "已准入",
+    MsgCode::ParticipantDenied => "已拒绝",
+    MsgCode::ParticipantsFetched => "获取会中成员成功",
+    MsgCode::ParticipantNotFound => "该成员不在会议中",
+    MsgCode::ParticipantMuted => "已静音",
+    MsgCode::ParticipantUnmuted => "已取消静音",
+    MsgCode::ParticipantRemoved => "已移出会议",
+    MsgCode::ParticipantUpdated => "已更新成员权限",
+    MsgCode::EmptyParticipantUpdate => "未指定需要修改的内容",
+    MsgCode::CannotModerateHost => "不能对主持人进行该操作",
+    MsgCode::ModerationFailed => "会议服务操作失败",
+    MsgCode::ModerationLogsFetched => "获取操作记录成功",
+    MsgCode::InvalidRrule => "不支持的重复规则",
+    MsgCode::InvalidSeriesTime => "结束时间需晚于开始时间",
+    MsgCode::SeriesCreated => "创建系列会议成功",
+    MsgCode::SeriesFetched => "获取系列会议成功",
+    MsgCode::SeriesUpdated => "修改系列会议成功",
+    MsgCode::SeriesNotFound => "找不到对应系列会议",
+    MsgCode::OnlySeriesAdmin => "只有系列会议的创建者可以修改整个系列",
+    MsgCode::IcsSummary => "会议 {}",
+    MsgCode::IcsDescription => "会议号：{}",
+    MsgCode::IcsCalendarName => "我的会议",
+    MsgCode::CalendarTokenIssued => "获取日历订阅地址成功",
+    MsgCode::CalendarTokenInvalid => "日历订阅地址无效，请重新获取",
+    MsgCode::RoomEndBeforeStart => "结束时间需晚于开始时间",
+    MsgCode::RoomStartInPast => "开始时间不能早于当前时间",
+    MsgCode::RoomTooLong => "会议时长不能超过 24 小时",
+    MsgCode::ScheduleConflict => "与 {} 个会议时间冲突",
+    MsgCode::InvalidAvailabilityRange => "查询时间范围无效，最长 31 天",
+    MsgCode::AvailabilityFetched => "获取空闲时间成功",
+    MsgCode::RoomResolved => "查询会议成功",
+    MsgCode::RoomDeleted => "会议已删除",
+    MsgCode::OnlyHostCanDelete => "仅主持人可删除会议",
+    MsgCode::SeriesOccurrenceNotDeletable => "系列中未结束的会议请取消该次会议或修改系列",
+    MsgCode::AccountDeletionScheduled => "已申请注销账号，宽限期内可撤销",
+    MsgCode::AccountDeletionCanceled => "已撤销注销申请",
+    MsgCode::AccountDeletionFetched => "获取注销状态成功",
+    MsgCode::NoPendingDeletion => "没有待处理的注销申请",
+    MsgCode::AccountDataExported => "导出个人数据成功",
+    MsgCode::TooManyAttempts => "操作过于频繁，请 {} 秒后重试",
+    MsgCode::AccountLocked => "登录失败次数过多，账号已临时锁定，请 {} 秒后重试",
+    MsgCode::InvalidUserId => "用户名需为 {}-{} 位字母、数字或 {}，且以字母或数字开头",
+    MsgCode::PasswordTooShort => "密码至少 {} 位",
+    MsgCode::PasswordTooLong => "密码不能超过 {} 位",
+    MsgCode::PasswordTooWeak => "密码需包含小写字母、大写字母、数字、符号中的至少 {} 类",
+    MsgCode::PasswordTooCommon => "密码过于常见，请更换",
+    MsgCode::PasswordContainsUserId => "密码不能包含用户名",
+    MsgCode::InvalidCredentials => "用户名或密码错误",
+    MsgCode::MfaRequired => "请输入两步验证码",
+    MsgCode::MfaChallengeExpired => "两步验证已过期，请重新登录",
+    MsgCode::InvalidMfaCode => "验证码错误",
+    MsgCode::MfaAlreadyEnabled => "已开启两步验证",
+    MsgCode::MfaNotSetUp => "请先设置两步验证",
+    MsgCode::MfaSetupStarted => "请使用身份验证器扫描二维码",
+    MsgCode::MfaEnabled => "已开启两步验证，请妥善保存恢复码",
+    MsgCode::MfaDisabled => "已关闭两步验证",
+    MsgCode::MfaStatusFetched => "获取两步验证状态成功",
+    MsgCode::RecoveryCodesRegenerated => "已生成新的恢复码，旧恢复码已失效",
+    MsgCode::InvalidDisplayName => "昵称不能包含换行等控制字符",
+    MsgCode::CalendarTokenRevoked => "已停用日历订阅地址",
+    MsgCode::NoCalendarFeed => "尚未生成日历订阅地址",
+    MsgCode::CannotModerateCoHost => "仅主持人可对联席主持人进行该操作",
+    MsgCode::UserIdReserved => "用户名不能以 guest- 开头",
+    MsgCode::EgressMismatch => "录制任务与会议当前录制不一致",
+    MsgCode::NoTranscript => "会议暂无转写记录",
+    MsgCode::SummaryRunning => "会议纪要生成中",
+    MsgCode::SummaryNotFound => "会议纪要尚未生成",
+    MsgCode::SummaryFetched => "获取会议纪要成功",
+    MsgCode::TranscriptBatchTooLarge => "单次最多上传 {} 条转写",
+    MsgCode::InvalidTranscriptRange => "转写时间范围不合法",
+    MsgCode::TranscriptUploaded => "上传转写成功",
+    MsgCode::TranscriptFetched => "获取转写成功",
+    MsgCode::UnsupportedExportFormat => "不支持的导出格式",
+    MsgCode::FilterPresetNotFound => "找不到对应滤镜预设",
+    MsgCode::InvalidPresetName => "预设名称需为 1 到 {} 个字符",
+    MsgCode::NotMemberCannotShare => "非会议成员无权共享",
+    MsgCode::PresetRoomRequired => "共享到会议时需指定会议",
+    MsgCode::UnsupportedVisibility => "不支持的可见范围",
+    MsgCode::FilterPresetsFetched => "获取滤镜预设成功",
+    MsgCode::FilterPresetCreated => "创建滤镜预设成功",
+    MsgCode::NotPresetOwner => "非预设所有者无权操作",
+    MsgCode::FilterPresetUpdated => "更新滤镜预设成功",
+    MsgCode::FilterPresetDeleted => "删除滤镜预设成功",
+    MsgCode::PresetNotPinnable => "仅可置顶公开或共享到本会议的预设",
+    MsgCode::FilterPresetPinned => "置顶滤镜预设成功",
+    MsgCode::NoPinnedFilter => "会议未置顶滤镜预设",
+    MsgCode::PinnedFilterFetched => "获取置顶滤镜预设成功",
+    MsgCode::InvalidWebhook => "无效的 webhook 请求",
+    MsgCode::WebhookReceived => "已接收 webhook",
+    MsgCode::InvalidRequest => "请求参数不合法：{}",
+  }
+}
+
+fn en_us(code: MsgCode) -> &'static str {
+  match code {
+    MsgCode::InternalError => "Internal server error",
+    MsgCode::RecordNotFound => "Record not found",
+    MsgCode::LoginRequired => "Please sign in first",
+    MsgCode::SessionExpired => "Your session has expired, please sign in again",
+    MsgCode::UserNotFound => "User does not exist",
+    MsgCode::UserDisabled => "User has been disabled",
+    MsgCode::WrongPassword => "Incorrect password",
+    MsgCode::UserExists => "User already exists",
+    MsgCode::UsersNotExist => "Users {} do not exist",
+    MsgCode::UserCreated => "User created",
+    MsgCode::UserDeleted => "User deleted",
+    MsgCode::PasswordUpdated => "Password updated, please sign in again",
+    MsgCode::LoginSucceeded => "Signed in",
+    MsgCode::TokenRefreshed => "Token refreshed",
+    MsgCode::LoggedOut => "Signed out",
+    MsgCode::LoggedOutAll => "Signed out of all devices",
+    MsgCode::ProfileFetched => "Profile loaded",
+    MsgCode::ProfileUpdated => "Profile updated",
+    MsgCode::DisplayNameTooLong => "Display name is too long",
+    MsgCode::InvalidAvatarUrl => "Invalid avatar URL",
+    MsgCode::InvalidEmail => "Invalid email address",
+    MsgCode::UnsupportedLocale => "Unsupported language",
+    MsgCode::InvalidSearchQuery => "Search text must be 1 to {} characters",
+    MsgCode::UsersSearched => "Users found",
+    MsgCode::LlmRequestFailed => "Request failed, please try again later",
+    MsgCode::LlmParseFailed => "Failed to parse the result, please try again later",
+    MsgCode::GptFilterFetched => "Filter suggestion generated",
+    MsgCode::RoomNotFound => "Meeting not found",
+    MsgCode::RoomCanceled => "Meeting has been canceled",
+    MsgCode::NotRoomMember => "Only meeting members can do this",
+    MsgCode::UserNotRoomMember => "User is not a member of the meeting",
+    MsgCode::RolePermissionDenied => "Your role does not allow this",
+    MsgCode::OnlyHostCanTransfer => "Only the host can transfer the host role",
+    MsgCode::OnlyHostCanSetCoHost => "Only the host can assign or remove co-hosts",
+    MsgCode::UseTransferHost => "Use host transfer to change the host",
+    MsgCode::HostMustBeAttendee => "The host must be a participant",
+    MsgCode::NotEnoughAttendees => "Not enough participants",
+    MsgCode::RoomsFetched => "Meetings loaded",
+    MsgCode::RoomCreated => "Meeting created",
+    MsgCode::RoomUpdated => "Meeting updated",
+    MsgCode::RoleUpdated => "Role updated",
+    MsgCode::RoomTokenIssued => "Room token issued",
+    MsgCode::RoomTokenFailed => "Failed to issue room token",
+    MsgCode::RoomRecording => "Meeting is already being recorded",
+    MsgCode::RoomNotRecording => "Meeting is not being recorded",
+    MsgCode::EgressBusy => "Recorder is busy",
+    MsgCode::RecordFailed => "Failed to record the meeting",
+    MsgCode::RecordStarted => "Recording started",
+    MsgCode::StopRecordFailed => "Failed to stop recording",
+    MsgCode::RecordStopped => "Recording stopped",
+    MsgCode::InviteCreated => "Invite link created",
+    MsgCode::InvitesFetched => "Invite links loaded",
+    MsgCode::InviteRevoked => "Invite link revoked",
+    MsgCode::InviteNotFound => "Invite link not found",
+    MsgCode::InviteInvalid => "Invite link is invalid or has expired",
+    MsgCode::InvalidInviteExpiry => "Expiry must be between 1 minute and {} days",
+    MsgCode::InvalidInviteMaxUses => "Usage limit must be greater than 0",
+    MsgCode::InvalidGuestName => "Name must be 1 to {} characters",
+    MsgCode::GuestJoined => "Joined the meeting",
+    MsgCode::LobbyWaiting => "Waiting for the host to let you in",
+    MsgCode::LobbyDenied => "The host declined your request to join",
+    MsgCode::AdmissionsFetched => "Lobby loaded",
+    MsgCode::AdmissionFetched => "Admission status loaded",
+    MsgCode::AdmissionNotFound => "Admission request not found",
+    MsgCode::ParticipantAdmitted => "Participant admitted",
+    MsgCode::ParticipantDenied => "Participant denied",
+    MsgCode::ParticipantsFetched => "Participants loaded",
+    MsgCode::ParticipantNotFound => "Participant is not in the meeting",
+    MsgCode::ParticipantMuted => "Track muted",
+    MsgCode::ParticipantUnmuted => "Track unmuted",
+    MsgCode::ParticipantRemoved => "Participant removed",
+    MsgCode::ParticipantUpdated => "Participant updated",
+    MsgCode::EmptyParticipantUpdate => "Nothing to update",
+    MsgCode::CannotModerateHost => "This action cannot target the host",
+    MsgCode::ModerationFailed => "Meeting service request failed",
+    MsgCode::ModerationLogsFetched => "Moderation log loaded",
+    MsgCode::InvalidRrule => "Unsupported recurrence rule",
+    MsgCode::InvalidSeriesTime => "End time must be after start time",
+    MsgCode::SeriesCreated => "Recurring meeting created",
+    MsgCode::SeriesFetched => "Recurring meeting loaded",
+    MsgCode::SeriesUpdated => "Recurring meeting updated",
+    MsgCode::SeriesNotFound => "Recurring meeting not found",
+    MsgCode::OnlySeriesAdmin => "Only the organizer can edit the whole series",
+    MsgCode::IcsSummary => "Meeting {}",
+    MsgCode::IcsDescription => "Join code: {}",
+    MsgCode::IcsCalendarName => "My meetings",
+    MsgCode::CalendarTokenIssued => "Calendar feed link created",
+    MsgCode::CalendarTokenInvalid => "Calendar feed link is invalid, please create a new one",
+    MsgCode::RoomEndBeforeStart => "End time must be after start time",
+    MsgCode::RoomStartInPast => "Start time cannot be in the past",
+    MsgCode::RoomTooLong => "A meeting cannot last longer than 24 hours",
+    MsgCode::ScheduleConflict => "Conflicts with {} meeting(s)",
+    MsgCode::InvalidAvailabilityRange => "Invalid time range, at most 31 days",
+    MsgCode::AvailabilityFetched => "Availability fetched",
+    MsgCode::RoomResolved => "Meeting found",
+    MsgCode::RoomDeleted => "Meeting deleted",
+    MsgCode::OnlyHostCanDelete => "Only the host can delete the meeting",
+    MsgCode::SeriesOccurrenceNotDeletable => {
+      "Cancel this occurrence or edit the series instead of deleting it"
+    }
+    MsgCode::AccountDeletionScheduled => {
+      "Account deletion scheduled, it can be canceled during the grace period"
+    }
+    MsgCode::AccountDeletionCanceled => "Account deletion canceled",
+    MsgCode::AccountDeletionFetched => "Account deletion status fetched",
+    MsgCode::NoPendingDeletion => "No pending account deletion",
+    MsgCode::AccountDataExported => "Account data exported",
+    MsgCode::TooManyAttempts => "Too many attempts, please retry in {} seconds",
+    MsgCode::AccountLocked => "Too many failed logins, the account is locked for {} seconds",
+    MsgCode::InvalidUserId => "User id must be {}-{} letters, digits or {}, starting with a letter or digit",
+    MsgCode::PasswordTooShort => "Password must be at least {} characters",
+    MsgCode::PasswordTooLong => "Password must be at most {} characters",
+    MsgCode::PasswordTooWeak => "Password must contain at least {} of lowercase letters, uppercase letters, digits and symbols",
+    MsgCode::PasswordTooCommon => "Password is too common",
+    MsgCode::PasswordContainsUserId => "Password must not contain the user id",
+    MsgCode::InvalidCredentials => "Incorrect user id or password",
+    MsgCode::MfaRequired => "Enter your two-factor authentication code",
+    MsgCode::MfaChallengeExpired => "Two-factor verification expired, please log in again",
+    MsgCode::InvalidMfaCode => "Invalid verification code",
+    MsgCode::MfaAlreadyEnabled => "Two-factor authentication is already enabled",
+    MsgCode::MfaNotSetUp => "Set up two-factor authentication first",
+    MsgCode::MfaSetupStarted => "Scan the QR code with your authenticator app",
+    MsgCode::MfaEnabled => "Two-factor authentication enabled, keep your recovery codes safe",
+    MsgCode::MfaDisabled => "Two-factor authentication disabled",
+    MsgCode::MfaStatusFetched => "Fetched two-factor authentication status",
+    MsgCode::RecoveryCodesRegenerated => "New recovery codes generated, old codes no longer work",
+    MsgCode::InvalidDisplayName => "Display name must not contain line breaks or other control characters",
+    MsgCode::CalendarTokenRevoked => "Calendar feed link revoked",
+    MsgCode::NoCalendarFeed => "No calendar feed link has been created",
+    MsgCode::CannotModerateCoHost => "Only the host can perform this action on a co-host",
+    MsgCode::UserIdReserved => "User id must not start with guest-",
+    MsgCode::EgressMismatch => "Recording does not match the meeting's current recording",
+    MsgCode::NoTranscript => "The meeting has no transcript yet",
+    MsgCode::SummaryRunning => "Meeting summary is being generated",
+    MsgCode::SummaryNotFound => "Meeting summary has not been generated yet",
+    MsgCode::SummaryFetched => "Fetched meeting summary",
+    MsgCode::TranscriptBatchTooLarge => "At most {} transcript segments can be uploaded at once",
+    MsgCode::InvalidTranscriptRange => "Invalid transcript time range",
+    MsgCode::TranscriptUploaded => "Transcript uploaded",
+    MsgCode::TranscriptFetched => "Fetched transcript",
+    MsgCode::UnsupportedExportFormat => "Unsupported export format",
+    MsgCode::FilterPresetNotFound => "Filter preset not found",
+    MsgCode::InvalidPresetName => "Preset name must be 1 to {} characters",
+    MsgCode::NotMemberCannotShare => "Only meeting members can share to the meeting",
+    MsgCode::PresetRoomRequired => "Specify a meeting to share the preset to",
+    MsgCode::UnsupportedVisibility => "Unsupported visibility",
+    MsgCode::FilterPresetsFetched => "Fetched filter presets",
+    MsgCode::FilterPresetCreated => "Filter preset created",
+    MsgCode::NotPresetOwner => "Only the preset owner can do this",
+    MsgCode::FilterPresetUpdated => "Filter preset updated",
+    MsgCode::FilterPresetDeleted => "Filter preset deleted",
+    MsgCode::PresetNotPinnable => "Only public presets or presets shared to this meeting can be pinned",
+    MsgCode::FilterPresetPinned => "Filter preset pinned",
+    MsgCode::NoPinnedFilter => "The meeting has no pinned filter preset",
+    MsgCode::PinnedFilterFetched => "Fetched pinned filter preset",
+    MsgCode::InvalidWebhook => "Invalid webhook",
+    MsgCode::WebhookReceived => "Webhook received",
+    MsgCode::InvalidRequest => "Invalid request: {}",
+  }
+}
+
+/// 错误信息，由消息码和文案参数组成
+#[derive(Clone, Debug)]
+pub struct Message {
+  code: MsgCode,
+  args: Vec<String>,
+}
+
+impl Message {
+  pub fn with_args(code: MsgCode, args: Vec<String>) -> Self {
+    Message { code, args }
+  }
+  pub fn code(&self) -> MsgCode {
+    self.code
+  }
+  pub fn render(&self, locale: Locale) -> String {
+    self.code.render(locale, &self.args)
+  }
+}
+
+impl From<MsgCode> for Message {
+  fn from(code: MsgCode) -> Self {
+    Message::with_args(code, Vec::new())
+  }
+}
+
+/// 按请求语言重新生成 `AppError` 的响应，需包在鉴权中间件外层
+pub async fn localize(
+  req: ServiceRequest,
+  next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+  let res = next.call(req).await?;
+  let localized = res
+    .response()
+    .error()
+    .and_then(|e| e.as_error::<AppError>())
+    .map(|e| e.localized_response(Locale::of(res.request())));
+  Ok(match localized {
+    Some(response) => res.into_response(response),
+    None => res.map_into_boxed_body(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolves_locale_and_renders_args() {
+    assert_eq!(
+      Locale::from_accept_language("fr-FR, en-GB;q=0.8, zh-CN;q=0.5"),
+      Some(Locale::EnUs)
+    );
+    assert_eq!(
+      Locale::from_accept_language("en;q=0, zh-TW;q=0.3"),
+      Some(Locale::ZhCn)
+    );
+    assert_eq!(Locale::from_accept_language("fr, de"), None);
+    assert_eq!(
+      MsgCode::InvalidSearchQuery.render(Locale::EnUs, &["64".to_string()]),
+      "Search text must be 1 to 64 characters"
+    );
+    assert_eq!(
+      Message::with_args(MsgCode::InvalidPresetName, vec!["32".to_string()]).render(Locale::ZhCn),
+      "预设名称需为 1 到 32 个字符"
+    );
+  }
+}
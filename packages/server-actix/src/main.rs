@@ -1,22 +1,36 @@
 mod api;
 mod common;
 mod entities;
+mod error;
+mod i18n;
 mod services;
+#[cfg(test)]
+mod test_utils;
 
 use actix_cors::Cors;
-use actix_web::{
-  error, get, middleware, post, web, App, HttpMessage, HttpResponse, HttpServer, Responder,
-};
+use actix_web::{middleware, middleware::from_fn, web, App, HttpMessage, HttpServer};
 use actix_web_httpauth::{extractors::bearer::BearerAuth, middleware::HttpAuthentication};
-use api::{room::get_room_scope, user::get_user_scope};
+use api::{
+  invite::get_guest_scope, livekit::get_livekit_scope, room::get_room_scope,
+  series::get_series_scope, user::get_user_scope,
+};
 use common::{AppState, AuthClaims};
+use error::AppError;
 use futures_util::lock::Mutex;
-use jsonwebtoken::{decode, DecodingKey, Validation};
+use i18n::{localize, Locale, MsgCode};
 use livekit_api::services::egress::EgressClient;
+use livekit_api::services::room::RoomClient;
 use log::{debug, info};
 use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
 use sea_orm::Database;
-use std::{env, sync::Arc};
+use services::account::AccountService;
+use services::auth::AuthService;
+use services::credential::CredentialPolicy;
+use services::llm::{OpenAiClient, OpenAiConfig};
+use services::mfa::parse_key;
+use services::rate_limit::{DbStore, LoginGuard};
+use services::series::SeriesService;
+use std::{env, sync::Arc, time::Duration};
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
@@ -38,14 +52,22 @@ async fn main() -> std::io::Result<()> {
   let livekit_key = env::var("LIVEKIT_API_KEY").expect("LIVEKIT_API_KEY must be set in .env file");
   let livekit_secret =
     env::var("LIVEKIT_API_SECRET").expect("LIVEKIT_API_SECRET must be set in .env file");
-  let mut client = EgressClient::with_api_key(&livekit_url, &livekit_key, &livekit_secret);
+  let client = EgressClient::with_api_key(&livekit_url, &livekit_key, &livekit_secret);
+  let room_client = RoomClient::with_api_key(&livekit_url, &livekit_key, &livekit_secret);
+  let db = db.unwrap();
+  // 限流计数默认保存在内存中，多实例部署时可改为数据库
+  let login_guard = match env::var("RATE_LIMIT_STORE").as_deref() {
+    Ok("database") => LoginGuard::new(Arc::new(DbStore::new(db.clone()))),
+    _ => LoginGuard::memory(),
+  };
   let state = AppState {
     jwt_auth_secret: env::var("JWT_SECRET").expect("JWT_SECRET must be set in .env file"),
-    db_conn: db.unwrap(),
+    db_conn: db,
     livekit_key,
     livekit_secret,
     livekit_url,
     livekit_egress_client: Arc::new(Mutex::new(client)),
+    livekit_room_client: Arc::new(room_client),
     s3_access_key: env::var("S3_STORAGE_ACCESS_KEY")
       .expect("S3_STORAGE_ACCESS_KEY must be set in .env file"),
     s3_secret: env::var("S3_STORAGE_SECRET").expect("S3_STORAGE_SECRET must be set in .env file"),
@@ -53,9 +75,53 @@ async fn main() -> std::io::Result<()> {
       .expect("S3_STORAGE_ENDPOINT must be set in .env file"),
     s3_bucket: env::var("S3_STORAGE_BUCKET").expect("S3_STORAGE_BUCKET must be set in .env file"),
     s3_public_url: env::var("S3_PUBLIC_URL").expect("S3_PUBLIC_URL must be set in .env file"),
-    gpt_api_key: env::var("GPT_API_KEY").expect("GPT_API_KEY must be set in .env file"),
-    gpt_base_url: env::var("GPT_BASE_URL").expect("GPT_BASE_URL must be set in .env file"),
+    llm_client: Arc::new(
+      OpenAiClient::new(OpenAiConfig {
+        base_url: env::var("GPT_BASE_URL").expect("GPT_BASE_URL must be set in .env file"),
+        api_key: env::var("GPT_API_KEY").expect("GPT_API_KEY must be set in .env file"),
+        model: env::var("GPT_MODEL").unwrap_or("deepseek-v3-0324".to_string()),
+        timeout: Duration::from_secs(
+          env::var("GPT_TIMEOUT_SECS")
+            .ok()
+            .and_then(|x| x.parse().ok())
+            .unwrap_or(60),
+        ),
+        max_retries: env::var("GPT_MAX_RETRIES")
+          .ok()
+          .and_then(|x| x.parse().ok())
+          .unwrap_or(2),
+      })
+      .expect("build llm http client failed"),
+    ),
+    login_guard,
+    trusted_proxies: env::var("TRUSTED_PROXIES")
+      .unwrap_or_default()
+      .split(',')
+      .map(str::trim)
+      .filter(|x| !x.is_empty())
+      .map(|x| {
+        x.parse()
+          .unwrap_or_else(|_| panic!("invalid ip in TRUSTED_PROXIES: {x}"))
+      })
+      .collect(),
+    credential_policy: CredentialPolicy::from_env(),
+    mfa_key: parse_key(
+      &env::var("MFA_SECRET_KEY").expect("MFA_SECRET_KEY must be set in .env file"),
+    )
+    .expect("MFA_SECRET_KEY must be 64 hex characters"),
   };
+  // 清除宽限期已结束的注销账号
+  actix_web::rt::spawn(AccountService::run_purge_job(state.db_conn.clone()));
+  // 提前生成系列会议，查询接口不再写入
+  actix_web::rt::spawn(SeriesService::run_materialize_job(state.db_conn.clone()));
+  // 清理登录限流的过期计数与锁定记录
+  actix_web::rt::spawn(
+    state
+      .login_guard
+      .clone()
+      .run_prune_job(state.db_conn.clone()),
+  );
+
   // start server
   let server_url = env::var("SERVER_URL").expect("SERVER_URL must be set in .env file");
   let server = HttpServer::new(move || {
@@ -63,7 +129,21 @@ async fn main() -> std::io::Result<()> {
       .wrap(Cors::permissive())
       .wrap(middleware::NormalizePath::trim())
       .app_data(web::Data::new(state.clone()))
-      .wrap(middleware::Logger::default())
+      // 请求体、查询参数与路径解析失败时同样返回统一的错误响应
+      .app_data(
+        web::JsonConfig::default().error_handler(|e, _| AppError::invalid_request(e).into()),
+      )
+      .app_data(
+        web::QueryConfig::default().error_handler(|e, _| AppError::invalid_request(e).into()),
+      )
+      .app_data(
+        web::PathConfig::default().error_handler(|e, _| AppError::invalid_request(e).into()),
+      )
+      // 不记录查询参数，日历订阅等地址中的 token 不会写入访问日志
+      .wrap(
+        middleware::Logger::new(r#"%a "%{method}xi %U" %s %b "%{Referer}i" "%{User-Agent}i" %T"#)
+          .custom_request_replace("method", |req| req.method().to_string()),
+      )
       .wrap(HttpAuthentication::with_fn(
         |req, credentials: Option<BearerAuth>| async move {
           let method = req.method();
@@ -72,39 +152,57 @@ async fn main() -> std::io::Result<()> {
           }
           let path = req.path();
 
-          if ["/api/user/login", "/api/user/create"]
-            .iter()
-            .any(|p| path.starts_with(p))
+          if [
+            "/api/user/login",
+            "/api/user/create",
+            "/api/user/refresh",
+            "/api/livekit/webhook",
+            "/api/guest/",
+            "/api/user/calendar.ics",
+            "/api/room/resolve/",
+          ]
+          .iter()
+          .any(|p| path.starts_with(p))
           {
             return Ok(req);
           }
 
           let Some(credentials) = credentials else {
-            return Err((error::ErrorUnauthorized("unauthorized"), req));
+            return Err((
+              AppError::Unauthorized(MsgCode::LoginRequired.into()).into(),
+              req,
+            ));
           };
-          let validation = Validation::default();
-
-          let decoding_key = DecodingKey::from_secret(
-            req
-              .app_data::<web::Data<AppState>>()
-              .unwrap()
-              .jwt_auth_secret
-              .as_ref(),
-          );
-
-          let Ok(data) = decode::<AuthClaims>(credentials.token(), &decoding_key, &validation)
+          let data = req.app_data::<web::Data<AppState>>().unwrap().clone();
+          let Some((claims, user)) = AuthService::verify_access_token(
+            &data.db_conn,
+            &data.jwt_auth_secret,
+            credentials.token(),
+          )
+          .await
           else {
-            return Err((error::ErrorUnauthorized("unauthorized"), req));
+            return Err((
+              AppError::Unauthorized(MsgCode::SessionExpired.into()).into(),
+              req,
+            ));
           };
           // 保存用户信息
-          req.extensions_mut().insert(data.claims);
+          req.extensions_mut().insert(claims);
+          if let Some(locale) = user.locale.as_deref().and_then(Locale::from_tag) {
+            req.extensions_mut().insert(locale);
+          }
 
           debug!("id: {}", req.extensions().get::<AuthClaims>().unwrap().id);
           Ok(req)
         },
       ))
+      // 需在鉴权之外，以便鉴权失败的响应同样按语言返回
+      .wrap(from_fn(localize))
       .service(get_user_scope())
       .service(get_room_scope())
+      .service(get_series_scope())
+      .service(get_livekit_scope())
+      .service(get_guest_scope())
   })
   .bind_openssl(server_url.as_str(), ssl_builder)?
   .workers(1)
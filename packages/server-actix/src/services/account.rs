@@ -0,0 +1,331 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, NaiveDateTime};
+use log::{debug, warn};
+use sea_orm::{
+  sea_query::{Expr, Query},
+  sqlx::types::chrono::Utc,
+  ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+  EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+use ts_rs::TS;
+
+use crate::entities::{
+  account_deletion, meeting_series, room, room_admission, room_user, transcript_segment, user,
+};
+use crate::services::auth::AuthService;
+use crate::services::mfa::MfaService;
+use crate::services::room::RoomService;
+use crate::services::room_user::{RoomRole, RoomUserService};
+
+/// 申请注销后的宽限期，期间可导出数据或撤销
+pub const DELETION_GRACE_DAYS: i64 = 14;
+/// 后台清理任务的执行间隔
+const PURGE_INTERVAL: StdDuration = StdDuration::from_secs(3600);
+
+/// 注销用户时对其主持的会议的处理方式
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../app-tauri/src/types/user.ts")]
+pub enum RoomHandover {
+  /// 移交给其他成员，依次优先联席主持人、主讲人、与会者、观众，没有其他成员时归档
+  #[default]
+  Transfer,
+  /// 全部归档（软删除），系列会议随之取消
+  Archive,
+}
+
+impl RoomHandover {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RoomHandover::Transfer => "transfer",
+      RoomHandover::Archive => "archive",
+    }
+  }
+  /// 无法识别时按移交处理
+  pub fn parse(x: &str) -> Self {
+    match x {
+      "archive" => RoomHandover::Archive,
+      _ => RoomHandover::Transfer,
+    }
+  }
+}
+
+/// 移交主持人时的优先顺序
+fn successor_rank(role: RoomRole) -> u8 {
+  match role {
+    RoomRole::Host => 0,
+    RoomRole::CoHost => 1,
+    RoomRole::Presenter => 2,
+    RoomRole::Attendee => 3,
+    RoomRole::Viewer => 4,
+  }
+}
+
+pub struct AccountService;
+
+impl AccountService {
+  /// 会议中除 `user_id` 外最适合接任主持人的成员
+  async fn find_successor<C: ConnectionTrait>(
+    db: &C,
+    room_id: i32,
+    user_id: &str,
+  ) -> Result<Option<String>, DbErr> {
+    Ok(
+      RoomUserService::get_users_by_room_id(db, room_id)
+        .await?
+        .into_iter()
+        .filter(|x| x.user_id != user_id)
+        .min_by_key(|x| (successor_rank(RoomRole::parse(&x.role)), x.id))
+        .map(|x| x.user_id),
+    )
+  }
+
+  /// 在事务中注销用户。主持的会议按 `handover` 移交或归档，并退出其余会议；系列会议交给最近一次会议的
+  /// 新主持人，无人接任时取消。转写、审计日志等历史记录引用用户 id，因此用户行保留为停用的
+  /// 匿名占位，不再能登录，id 也不会被重新注册
+  pub async fn delete_user<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
+    user_id: &str,
+    handover: RoomHandover,
+  ) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    let now = Utc::now().naive_utc();
+    let rooms = room::Entity::find()
+      .filter(room::Column::Admin.eq(user_id))
+      .filter(room::Column::DeletedAt.is_null())
+      .all(&txn)
+      .await?;
+    for room in rooms {
+      let successor = match handover {
+        RoomHandover::Transfer => Self::find_successor(&txn, room.id, user_id).await?,
+        RoomHandover::Archive => None,
+      };
+      match successor {
+        Some(successor) => RoomUserService::transfer_host(&txn, &room, &successor).await?,
+        None => RoomService::soft_delete(&txn, room.id, now).await?,
+      }
+    }
+
+    let series = meeting_series::Entity::find()
+      .filter(meeting_series::Column::Admin.eq(user_id))
+      .filter(meeting_series::Column::IsCanceled.eq(false))
+      .all(&txn)
+      .await?;
+    for x in series {
+      let latest = room::Entity::find()
+        .filter(room::Column::SeriesId.eq(x.id))
+        .filter(room::Column::DeletedAt.is_null())
+        .order_by_desc(room::Column::StartTime)
+        .one(&txn)
+        .await?;
+      let mut x: meeting_series::ActiveModel = x.into();
+      match latest.filter(|r| r.admin != user_id) {
+        Some(r) => x.admin = ActiveValue::Set(r.admin),
+        None => x.is_canceled = ActiveValue::Set(true),
+      }
+      x.update(&txn).await?;
+    }
+
+    // 归档的会议保留成员记录
+    room_user::Entity::delete_many()
+      .filter(room_user::Column::UserId.eq(user_id))
+      .filter(
+        room_user::Column::RoomId.in_subquery(
+          Query::select()
+            .column(room::Column::Id)
+            .from(room::Entity)
+            .and_where(Expr::col(room::Column::DeletedAt).is_null())
+            .to_owned(),
+        ),
+      )
+      .exec(&txn)
+      .await?;
+
+    user::Entity::update_many()
+      .col_expr(user::Column::Password, Expr::value(""))
+      .col_expr(user::Column::DisplayName, Expr::value(""))
+      .col_expr(user::Column::AvatarUrl, Expr::value(""))
+      .col_expr(user::Column::Email, Expr::value(""))
+      .col_expr(user::Column::IsDisabled, Expr::value(true))
+      .col_expr(
+        user::Column::TokenVersion,
+        Expr::col(user::Column::TokenVersion).add(1),
+      )
+      .filter(user::Column::Id.eq(user_id))
+      .exec(&txn)
+      .await?;
+    txn.commit().await
+  }
+
+  /// 申请注销，重复申请时重新计算宽限期
+  pub async fn schedule_deletion(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+    handover: RoomHandover,
+    now: NaiveDateTime,
+  ) -> Result<account_deletion::Model, DbErr> {
+    account_deletion::Entity::delete_by_id(user_id)
+      .exec(dbconn)
+      .await?;
+    account_deletion::ActiveModel {
+      user_id: ActiveValue::Set(user_id.to_string()),
+      rooms: ActiveValue::Set(handover.as_str().to_string()),
+      requested_at: ActiveValue::Set(now),
+      purge_at: ActiveValue::Set(now + Duration::days(DELETION_GRACE_DAYS)),
+    }
+    .insert(dbconn)
+    .await
+  }
+  pub async fn get_deletion(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<Option<account_deletion::Model>, DbErr> {
+    account_deletion::Entity::find_by_id(user_id)
+      .one(dbconn)
+      .await
+  }
+  /// 撤销注销申请，没有待注销的申请时返回 false
+  pub async fn cancel_deletion(dbconn: &DatabaseConnection, user_id: &str) -> Result<bool, DbErr> {
+    let res = account_deletion::Entity::delete_by_id(user_id)
+      .exec(dbconn)
+      .await?;
+    Ok(res.rows_affected > 0)
+  }
+
+  /// 宽限期结束后在一个事务中清除个人数据：处理主持的会议并匿名化账号，删除本人的转写发言、
+  /// 登录凭证与两步验证密钥，清空等候室中的昵称
+  pub async fn purge(
+    dbconn: &DatabaseConnection,
+    deletion: &account_deletion::Model,
+  ) -> Result<(), DbErr> {
+    let user_id = deletion.user_id.as_str();
+    let txn = dbconn.begin().await?;
+    AuthService::revoke_all_in(&txn, user_id).await?;
+    MfaService::disable(&txn, user_id).await?;
+    Self::delete_user(&txn, user_id, RoomHandover::parse(&deletion.rooms)).await?;
+    transcript_segment::Entity::delete_many()
+      .filter(transcript_segment::Column::SpeakerId.eq(user_id))
+      .exec(&txn)
+      .await?;
+    room_admission::Entity::update_many()
+      .col_expr(room_admission::Column::DisplayName, Expr::value(""))
+      .filter(room_admission::Column::UserId.eq(user_id))
+      .exec(&txn)
+      .await?;
+    account_deletion::Entity::delete_by_id(user_id)
+      .exec(&txn)
+      .await?;
+    txn.commit().await
+  }
+  /// 清除所有宽限期已结束的账号，返回清除成功的数量；单个账号失败时保留其申请，下次重试
+  pub async fn purge_due(dbconn: &DatabaseConnection, now: NaiveDateTime) -> Result<usize, DbErr> {
+    let due = account_deletion::Entity::find()
+      .filter(account_deletion::Column::PurgeAt.lte(now))
+      .all(dbconn)
+      .await?;
+    let mut purged = 0;
+    for x in &due {
+      match Self::purge(dbconn, x).await {
+        Ok(()) => purged += 1,
+        Err(e) => warn!("purge account {} err: {:?}", x.user_id, e),
+      }
+    }
+    Ok(purged)
+  }
+  /// 后台定时清理，随服务启动
+  pub async fn run_purge_job(dbconn: DatabaseConnection) {
+    let mut interval = actix_web::rt::time::interval(PURGE_INTERVAL);
+    loop {
+      interval.tick().await;
+      match Self::purge_due(&dbconn, Utc::now().naive_utc()).await {
+        Ok(0) => {}
+        Ok(n) => debug!("purged {n} deleted accounts"),
+        Err(e) => debug!("purge deleted accounts err: {:?}", e),
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::services::user::UserService;
+  use crate::test_utils::{add_room_users, create_room, create_user, setup_db};
+
+  #[actix_web::test]
+  async fn deleting_user_hands_over_or_archives_rooms() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_room(&db, 2, "").await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    add_room_users(&db, 1, &["admin1", "alice", "bob"]).await;
+    add_room_users(&db, 2, &["admin2"]).await;
+    RoomUserService::set_role(&db, 1, "alice", RoomRole::Viewer)
+      .await
+      .unwrap();
+
+    AccountService::delete_user(&db, "admin1", RoomHandover::Transfer)
+      .await
+      .unwrap();
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert_eq!(room.admin, "bob");
+    assert!(room.deleted_at.is_none());
+    assert!(!RoomUserService::is_room_member(&db, 1, "admin1")
+      .await
+      .unwrap());
+    let user = UserService::get_user(&db, "admin1".to_string())
+      .await
+      .unwrap();
+    assert!(user.is_disabled && user.password.is_empty());
+    assert_eq!(user.token_version, 1);
+
+    // 没有其他成员可接任时归档
+    AccountService::delete_user(&db, "admin2", RoomHandover::Transfer)
+      .await
+      .unwrap();
+    let room = RoomService::get_room_by_id(&db, 2).await.unwrap();
+    assert!(room.deleted_at.is_some());
+    assert!(RoomUserService::is_room_member(&db, 2, "admin2")
+      .await
+      .unwrap());
+  }
+
+  #[actix_web::test]
+  async fn purge_due_skips_failing_accounts() {
+    let db = setup_db().await;
+    create_user(&db, "alice").await;
+    create_user(&db, "bob").await;
+    let now = Utc::now().naive_utc();
+    for id in ["alice", "bob"] {
+      AccountService::schedule_deletion(&db, id, RoomHandover::Transfer, now)
+        .await
+        .unwrap();
+    }
+    // 使 bob 的匿名化失败
+    db.execute_unprepared(
+      "CREATE TRIGGER fail_purge BEFORE UPDATE ON \"user\" WHEN OLD.id = 'bob' \
+       BEGIN SELECT RAISE(ABORT, 'purge failed'); END",
+    )
+    .await
+    .unwrap();
+
+    let later = now + Duration::days(DELETION_GRACE_DAYS);
+    assert_eq!(AccountService::purge_due(&db, later).await.unwrap(), 1);
+    let alice = UserService::get_user(&db, "alice".to_string())
+      .await
+      .unwrap();
+    assert!(alice.is_disabled);
+    assert!(AccountService::get_deletion(&db, "alice")
+      .await
+      .unwrap()
+      .is_none());
+    let bob = UserService::get_user(&db, "bob".to_string()).await.unwrap();
+    assert!(!bob.is_disabled);
+    assert!(AccountService::get_deletion(&db, "bob")
+      .await
+      .unwrap()
+      .is_some());
+  }
+}
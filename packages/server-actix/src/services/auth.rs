@@ -0,0 +1,352 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::common::{AuthClaims, AuthToken};
+use crate::entities::{calendar_token, refresh_token, user};
+use crate::error::AppError;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use log::debug;
+use openssl::sha::sha256;
+use sea_orm::{
+  sea_query::Expr, sqlx::types::chrono::Utc, ActiveModelTrait, ActiveValue, ColumnTrait, Condition,
+  ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, TransactionTrait,
+};
+
+pub struct AuthService;
+
+/// access token 有效期
+pub const ACCESS_TOKEN_TTL_SECS: u64 = 15 * 60;
+/// refresh token 有效期
+pub const REFRESH_TOKEN_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// 两步验证 challenge token 有效期
+pub const MFA_TOKEN_TTL_SECS: u64 = 5 * 60;
+
+/// 密码校验通过、等待两步验证时签发，字段与 `AuthClaims` 不同，不能用作 access token
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct MfaClaims {
+  pub mfa: String,
+  pub exp: usize,
+  pub ver: i32,
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// 数据库只保存 refresh token 等凭证的摘要
+pub fn hash_token(token: &str) -> String {
+  to_hex(&sha256(token.as_bytes()))
+}
+
+impl AuthService {
+  /// 签发 access token 与 refresh token，同时清理该用户已过期的 refresh token
+  pub async fn issue_tokens(
+    dbconn: &DatabaseConnection,
+    jwt_secret: &str,
+    user: &user::Model,
+  ) -> Result<AuthToken, AppError> {
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .expect("Time went backwards")
+      .as_secs();
+    let auth_token = encode(
+      &Header::default(),
+      &AuthClaims {
+        id: user.id.clone(),
+        exp: (now + ACCESS_TOKEN_TTL_SECS) as usize,
+        ver: user.token_version,
+      },
+      &EncodingKey::from_secret(jwt_secret.as_ref()),
+    )?;
+
+    let refresh_token = to_hex(&rand::random::<[u8; 32]>());
+    let now = Utc::now().naive_utc();
+    refresh_token::Entity::delete_many()
+      .filter(
+        Condition::all()
+          .add(refresh_token::Column::UserId.eq(&user.id))
+          .add(refresh_token::Column::ExpiresAt.lt(now)),
+      )
+      .exec(dbconn)
+      .await?;
+    refresh_token::ActiveModel {
+      user_id: ActiveValue::Set(user.id.clone()),
+      token_hash: ActiveValue::Set(hash_token(&refresh_token)),
+      revoked: ActiveValue::Set(false),
+      expires_at: ActiveValue::Set(now + Duration::from_secs(REFRESH_TOKEN_TTL_SECS)),
+      created_at: ActiveValue::Set(now),
+      ..Default::default()
+    }
+    .insert(dbconn)
+    .await?;
+    Ok(AuthToken {
+      auth_token,
+      refresh_token,
+      expires_in: ACCESS_TOKEN_TTL_SECS,
+    })
+  }
+  /// 校验签名、有效期以及 token_version，用户不存在、被禁用或已退出全部设备时返回 None
+  pub async fn verify_access_token(
+    dbconn: &DatabaseConnection,
+    jwt_secret: &str,
+    token: &str,
+  ) -> Option<(AuthClaims, user::Model)> {
+    let claims = decode::<AuthClaims>(
+      token,
+      &DecodingKey::from_secret(jwt_secret.as_ref()),
+      &Validation::default(),
+    )
+    .ok()?
+    .claims;
+    let user = user::Entity::find_by_id(&claims.id)
+      .one(dbconn)
+      .await
+      .inspect_err(|e| debug!("verify_access_token err: {:?}", e))
+      .ok()??;
+    (!user.is_disabled && user.token_version == claims.ver).then_some((claims, user))
+  }
+  pub fn issue_mfa_token(
+    jwt_secret: &str,
+    user: &user::Model,
+  ) -> jsonwebtoken::errors::Result<String> {
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .expect("Time went backwards")
+      .as_secs();
+    encode(
+      &Header::default(),
+      &MfaClaims {
+        mfa: user.id.clone(),
+        exp: (now + MFA_TOKEN_TTL_SECS) as usize,
+        ver: user.token_version,
+      },
+      &EncodingKey::from_secret(jwt_secret.as_ref()),
+    )
+  }
+  pub async fn verify_mfa_token(
+    dbconn: &DatabaseConnection,
+    jwt_secret: &str,
+    token: &str,
+  ) -> Option<user::Model> {
+    let claims = decode::<MfaClaims>(
+      token,
+      &DecodingKey::from_secret(jwt_secret.as_ref()),
+      &Validation::default(),
+    )
+    .ok()?
+    .claims;
+    let user = user::Entity::find_by_id(&claims.mfa)
+      .one(dbconn)
+      .await
+      .inspect_err(|e| debug!("verify_mfa_token err: {:?}", e))
+      .ok()??;
+    (!user.is_disabled && user.token_version == claims.ver).then_some(user)
+  }
+  /// 日历订阅地址中的 token。日历客户端无法携带请求头且需长期有效，因此使用随机生成、
+  /// 只保存摘要的独立凭证，重新生成或吊销后旧地址立即失效
+  pub async fn issue_calendar_token(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<String, DbErr> {
+    let token = to_hex(&rand::random::<[u8; 32]>());
+    let txn = dbconn.begin().await?;
+    calendar_token::Entity::delete_by_id(user_id)
+      .exec(&txn)
+      .await?;
+    calendar_token::ActiveModel {
+      user_id: ActiveValue::Set(user_id.to_string()),
+      token_hash: ActiveValue::Set(hash_token(&token)),
+      created_at: ActiveValue::Set(Utc::now().naive_utc()),
+    }
+    .insert(&txn)
+    .await?;
+    txn.commit().await?;
+    Ok(token)
+  }
+  /// 没有订阅地址时返回 false
+  pub async fn revoke_calendar_token(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<bool, DbErr> {
+    let res = calendar_token::Entity::delete_by_id(user_id)
+      .exec(dbconn)
+      .await?;
+    Ok(res.rows_affected > 0)
+  }
+  pub async fn verify_calendar_token(
+    dbconn: &DatabaseConnection,
+    token: &str,
+  ) -> Result<Option<user::Model>, DbErr> {
+    let Some((_, user)) = calendar_token::Entity::find()
+      .filter(calendar_token::Column::TokenHash.eq(hash_token(token)))
+      .find_also_related(user::Entity)
+      .one(dbconn)
+      .await?
+    else {
+      return Ok(None);
+    };
+    Ok(user.filter(|x| !x.is_disabled))
+  }
+  /// 轮换 refresh token，已轮换过的 token 再次使用视为泄露，吊销该用户全部 token
+  pub async fn refresh(
+    dbconn: &DatabaseConnection,
+    jwt_secret: &str,
+    token: &str,
+  ) -> Result<Option<AuthToken>, AppError> {
+    let Some(model) = refresh_token::Entity::find()
+      .filter(refresh_token::Column::TokenHash.eq(hash_token(token)))
+      .one(dbconn)
+      .await?
+    else {
+      return Ok(None);
+    };
+    if model.revoked {
+      debug!("refresh token of {} reused, revoke all", model.user_id);
+      Self::revoke_all(dbconn, &model.user_id).await?;
+      return Ok(None);
+    }
+    if model.expires_at < Utc::now().naive_utc() {
+      return Ok(None);
+    }
+    // 并发刷新时仅有一个请求能成功吊销旧 token
+    let res = refresh_token::Entity::update_many()
+      .col_expr(refresh_token::Column::Revoked, Expr::value(true))
+      .filter(
+        Condition::all()
+          .add(refresh_token::Column::Id.eq(model.id))
+          .add(refresh_token::Column::Revoked.eq(false)),
+      )
+      .exec(dbconn)
+      .await?;
+    if res.rows_affected == 0 {
+      return Ok(None);
+    }
+    let Some(user) = user::Entity::find_by_id(&model.user_id)
+      .one(dbconn)
+      .await?
+      .filter(|x| !x.is_disabled)
+    else {
+      return Ok(None);
+    };
+    Self::issue_tokens(dbconn, jwt_secret, &user)
+      .await
+      .map(Some)
+  }
+  /// 退出当前设备
+  pub async fn revoke(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+    token: &str,
+  ) -> Result<(), DbErr> {
+    refresh_token::Entity::delete_many()
+      .filter(
+        Condition::all()
+          .add(refresh_token::Column::UserId.eq(user_id))
+          .add(refresh_token::Column::TokenHash.eq(hash_token(token))),
+      )
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  /// 退出全部设备：递增 token_version 使 access token 失效，并删除全部 refresh token 与日历订阅地址
+  pub async fn revoke_all(dbconn: &DatabaseConnection, user_id: &str) -> Result<(), DbErr> {
+    let txn = dbconn.begin().await?;
+    Self::revoke_all_in(&txn, user_id).await?;
+    txn.commit().await
+  }
+  /// 在调用方的事务中退出全部设备
+  pub async fn revoke_all_in<C: ConnectionTrait>(db: &C, user_id: &str) -> Result<(), DbErr> {
+    user::Entity::update_many()
+      .col_expr(
+        user::Column::TokenVersion,
+        Expr::col(user::Column::TokenVersion).add(1),
+      )
+      .filter(user::Column::Id.eq(user_id))
+      .exec(db)
+      .await?;
+    refresh_token::Entity::delete_many()
+      .filter(refresh_token::Column::UserId.eq(user_id))
+      .exec(db)
+      .await?;
+    calendar_token::Entity::delete_by_id(user_id)
+      .exec(db)
+      .await
+      .and(Ok(()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::{create_user, setup_db};
+
+  const SECRET: &str = "test-jwt-secret";
+
+  async fn get_user(db: &DatabaseConnection, id: &str) -> user::Model {
+    user::Entity::find_by_id(id).one(db).await.unwrap().unwrap()
+  }
+
+  #[actix_web::test]
+  async fn refresh_rotates_and_detects_reuse() {
+    let db = setup_db().await;
+    create_user(&db, "alice").await;
+    let first = AuthService::issue_tokens(&db, SECRET, &get_user(&db, "alice").await)
+      .await
+      .unwrap();
+    assert!(
+      AuthService::verify_access_token(&db, SECRET, &first.auth_token)
+        .await
+        .is_some()
+    );
+
+    let second = AuthService::refresh(&db, SECRET, &first.refresh_token)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_ne!(second.refresh_token, first.refresh_token);
+
+    // 旧 token 被重放，吊销全部 token
+    assert!(AuthService::refresh(&db, SECRET, &first.refresh_token)
+      .await
+      .unwrap()
+      .is_none());
+    assert!(AuthService::refresh(&db, SECRET, &second.refresh_token)
+      .await
+      .unwrap()
+      .is_none());
+    assert!(
+      AuthService::verify_access_token(&db, SECRET, &second.auth_token)
+        .await
+        .is_none()
+    );
+  }
+
+  #[actix_web::test]
+  async fn revoke_all_invalidates_access_tokens() {
+    let db = setup_db().await;
+    create_user(&db, "alice").await;
+    let token = AuthService::issue_tokens(&db, SECRET, &get_user(&db, "alice").await)
+      .await
+      .unwrap();
+    AuthService::revoke(&db, "alice", &token.refresh_token)
+      .await
+      .unwrap();
+    assert!(AuthService::refresh(&db, SECRET, &token.refresh_token)
+      .await
+      .unwrap()
+      .is_none());
+    assert!(
+      AuthService::verify_access_token(&db, SECRET, &token.auth_token)
+        .await
+        .is_some()
+    );
+
+    AuthService::revoke_all(&db, "alice").await.unwrap();
+    assert_eq!(get_user(&db, "alice").await.token_version, 1);
+    assert!(
+      AuthService::verify_access_token(&db, SECRET, &token.auth_token)
+        .await
+        .is_none()
+    );
+  }
+}
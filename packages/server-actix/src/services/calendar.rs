@@ -0,0 +1,191 @@
+use chrono::NaiveDateTime;
+
+/// 生成的 UID 后缀，保证与其他日历来源的事件不冲突
+const UID_DOMAIN: &str = "meeting.server-actix";
+/// RFC 5545 建议每行不超过 75 字节
+const MAX_LINE_OCTETS: usize = 75;
+
+/// 日历中的参与人，地址为 RFC 5545 的 cal-address
+pub struct IcsPerson {
+  pub name: String,
+  pub address: String,
+}
+
+impl IcsPerson {
+  /// 未填写邮箱的用户以用户 id 作为地址
+  pub fn new(id: &str, name: &str, email: &str) -> Self {
+    IcsPerson {
+      name: name.to_string(),
+      address: if email.is_empty() {
+        format!("urn:x-meeting-user:{id}")
+      } else {
+        format!("mailto:{email}")
+      },
+    }
+  }
+}
+
+pub struct IcsEvent {
+  pub room_id: i32,
+  pub start_time: NaiveDateTime,
+  pub end_time: NaiveDateTime,
+  pub summary: String,
+  pub description: String,
+  pub organizer: Option<IcsPerson>,
+  pub attendees: Vec<IcsPerson>,
+  pub canceled: bool,
+}
+
+fn format_time(time: &NaiveDateTime) -> String {
+  time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// TEXT 类型需转义反斜杠、分号、逗号与换行
+fn escape_text(text: &str) -> String {
+  let mut res = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '\\' => res.push_str("\\\\"),
+      ';' => res.push_str("\\;"),
+      ',' => res.push_str("\\,"),
+      '\n' => res.push_str("\\n"),
+      '\r' => {}
+      c => res.push(c),
+    }
+  }
+  res
+}
+
+/// 参数值中含有特殊字符时需加引号。引号与控制字符不允许出现，去掉换行以免注入其他属性
+fn quote_param(value: &str) -> String {
+  let value = value
+    .chars()
+    .filter(|c| !c.is_control())
+    .map(|c| if c == '"' { '\'' } else { c })
+    .collect::<String>();
+  if value.contains([':', ';', ',']) {
+    format!("\"{value}\"")
+  } else {
+    value
+  }
+}
+
+/// 按字节折行，不拆开多字节字符，续行以空格开头
+fn push_line(out: &mut String, line: &str) {
+  let mut width = 0;
+  for c in line.chars() {
+    if width + c.len_utf8() > MAX_LINE_OCTETS {
+      out.push_str("\r\n ");
+      width = 1;
+    }
+    out.push(c);
+    width += c.len_utf8();
+  }
+  out.push_str("\r\n");
+}
+
+/// 生成 VCALENDAR，`name` 为订阅时显示的日历名称
+pub fn render_calendar(name: Option<&str>, events: &[IcsEvent], now: NaiveDateTime) -> String {
+  let mut out = String::new();
+  push_line(&mut out, "BEGIN:VCALENDAR");
+  push_line(&mut out, "VERSION:2.0");
+  push_line(&mut out, "PRODID:-//server-actix//Meeting//CN");
+  push_line(&mut out, "CALSCALE:GREGORIAN");
+  push_line(&mut out, "METHOD:PUBLISH");
+  if let Some(name) = name {
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+  }
+  for event in events {
+    push_line(&mut out, "BEGIN:VEVENT");
+    push_line(
+      &mut out,
+      &format!("UID:room-{}@{UID_DOMAIN}", event.room_id),
+    );
+    push_line(&mut out, &format!("DTSTAMP:{}", format_time(&now)));
+    push_line(
+      &mut out,
+      &format!("DTSTART:{}", format_time(&event.start_time)),
+    );
+    push_line(&mut out, &format!("DTEND:{}", format_time(&event.end_time)));
+    push_line(
+      &mut out,
+      &format!("SUMMARY:{}", escape_text(&event.summary)),
+    );
+    push_line(
+      &mut out,
+      &format!("DESCRIPTION:{}", escape_text(&event.description)),
+    );
+    if let Some(organizer) = &event.organizer {
+      push_line(
+        &mut out,
+        &format!(
+          "ORGANIZER;CN={}:{}",
+          quote_param(&organizer.name),
+          organizer.address
+        ),
+      );
+    }
+    for attendee in &event.attendees {
+      push_line(
+        &mut out,
+        &format!(
+          "ATTENDEE;CN={};ROLE=REQ-PARTICIPANT:{}",
+          quote_param(&attendee.name),
+          attendee.address
+        ),
+      );
+    }
+    push_line(
+      &mut out,
+      if event.canceled {
+        "STATUS:CANCELLED"
+      } else {
+        "STATUS:CONFIRMED"
+      },
+    );
+    push_line(&mut out, "END:VEVENT");
+  }
+  push_line(&mut out, "END:VCALENDAR");
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn renders_escaped_and_folded_event() {
+    let time = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+    let ics = render_calendar(
+      None,
+      &[IcsEvent {
+        room_id: 7,
+        start_time: time("2025-06-06 01:30"),
+        end_time: time("2025-06-06 02:00"),
+        summary: "周会, 第 1 次".to_string(),
+        description: "会议号：123456789\n".repeat(4),
+        organizer: Some(IcsPerson::new("alice", "Alice: PM", "")),
+        attendees: vec![
+          IcsPerson::new("bob", "Bob", "bob@example.com"),
+          IcsPerson::new("eve", "e\"v\r\nDTSTART:19700101T000000Z", ""),
+        ],
+        canceled: true,
+      }],
+      time("2025-06-01 00:00"),
+    );
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(ics.contains("UID:room-7@"));
+    assert!(ics.contains("DTSTART:20250606T013000Z\r\n"));
+    assert!(ics.contains("SUMMARY:周会\\, 第 1 次\r\n"));
+    assert!(ics.contains("ORGANIZER;CN=\"Alice: PM\":urn:x-meeting-user:alice\r\n"));
+    assert!(ics.contains("ATTENDEE;CN=Bob;ROLE=REQ-PARTICIPANT:mailto:bob@example.com\r\n"));
+    assert!(ics.contains("STATUS:CANCELLED\r\n"));
+    assert!(ics.contains("ATTENDEE;CN=\"e'vDTSTART:19700101T000000Z\";ROLE="));
+    assert_eq!(ics.matches("DTSTART:").count(), 2);
+    for line in ics.split("\r\n") {
+      assert!(line.len() <= MAX_LINE_OCTETS, "{line}");
+    }
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains(&format!("DESCRIPTION:{}", "会议号：123456789\\n".repeat(4))));
+  }
+}
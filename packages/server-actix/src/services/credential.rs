@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::env;
+use std::sync::Arc;
+
+/// 内置的常见弱密码，均为小写
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+/// 访客在 LiveKit 中的身份前缀，注册用户不能使用，以免与访客混淆
+pub const GUEST_ID_PREFIX: &str = "guest-";
+
+/// 用户名与密码规则，可通过环境变量调整，仅在注册与修改密码时校验
+#[derive(Debug, Clone)]
+pub struct CredentialPolicy {
+  pub id_min_len: usize,
+  pub id_max_len: usize,
+  /// 用户名中除字母、数字外允许的符号，不能出现在开头
+  pub id_symbols: String,
+  pub password_min_len: usize,
+  pub password_max_len: usize,
+  /// 至少包含的字符类别数：小写字母、大写字母、数字、其他符号
+  pub password_min_classes: usize,
+  blocklist: Arc<HashSet<String>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PolicyError {
+  InvalidUserId,
+  ReservedUserId,
+  PasswordTooShort,
+  PasswordTooLong,
+  PasswordTooWeak,
+  PasswordTooCommon,
+  PasswordContainsUserId,
+}
+
+impl Default for CredentialPolicy {
+  fn default() -> Self {
+    CredentialPolicy {
+      id_min_len: 3,
+      id_max_len: 32,
+      id_symbols: "_-.".to_string(),
+      password_min_len: 8,
+      password_max_len: 128,
+      password_min_classes: 2,
+      blocklist: Arc::new(parse_blocklist(COMMON_PASSWORDS)),
+    }
+  }
+}
+
+fn parse_blocklist(text: &str) -> HashSet<String> {
+  text
+    .lines()
+    .map(|x| x.trim().to_lowercase())
+    .filter(|x| !x.is_empty() && !x.starts_with('#'))
+    .collect()
+}
+
+fn char_classes(password: &str) -> usize {
+  let checks: [fn(&char) -> bool; 3] = [
+    char::is_ascii_lowercase,
+    char::is_ascii_uppercase,
+    char::is_ascii_digit,
+  ];
+  let mut classes = checks
+    .iter()
+    .filter(|f| password.chars().any(|c| f(&c)))
+    .count();
+  if password.chars().any(|c| !c.is_ascii_alphanumeric()) {
+    classes += 1;
+  }
+  classes
+}
+
+impl CredentialPolicy {
+  /// 未设置的变量使用默认值，`PASSWORD_BLOCKLIST_FILE` 中的密码追加到内置列表
+  pub fn from_env() -> Self {
+    let default = Self::default();
+    let num = |key: &str, default: usize| {
+      env::var(key)
+        .ok()
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(default)
+    };
+    let mut blocklist = (*default.blocklist).clone();
+    if let Ok(path) = env::var("PASSWORD_BLOCKLIST_FILE") {
+      let text = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read PASSWORD_BLOCKLIST_FILE {path}: {e}"));
+      blocklist.extend(parse_blocklist(&text));
+    }
+    CredentialPolicy {
+      id_min_len: num("USER_ID_MIN_LEN", default.id_min_len),
+      id_max_len: num("USER_ID_MAX_LEN", default.id_max_len),
+      id_symbols: env::var("USER_ID_SYMBOLS").unwrap_or(default.id_symbols),
+      password_min_len: num("PASSWORD_MIN_LEN", default.password_min_len),
+      password_max_len: num("PASSWORD_MAX_LEN", default.password_max_len),
+      password_min_classes: num("PASSWORD_MIN_CLASSES", default.password_min_classes),
+      blocklist: Arc::new(blocklist),
+    }
+  }
+
+  /// 用户名只能包含 ASCII 字母、数字与允许的符号，且以字母或数字开头
+  pub fn check_user_id(&self, id: &str) -> Result<(), PolicyError> {
+    let len = id.chars().count();
+    let valid = (self.id_min_len..=self.id_max_len).contains(&len)
+      && id.starts_with(|c: char| c.is_ascii_alphanumeric())
+      && id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || self.id_symbols.contains(c));
+    if !valid {
+      return Err(PolicyError::InvalidUserId);
+    }
+    let reserved = id
+      .get(..GUEST_ID_PREFIX.len())
+      .is_some_and(|x| x.eq_ignore_ascii_case(GUEST_ID_PREFIX));
+    if reserved {
+      return Err(PolicyError::ReservedUserId);
+    }
+    Ok(())
+  }
+
+  pub fn check_password(&self, user_id: &str, password: &str) -> Result<(), PolicyError> {
+    let len = password.chars().count();
+    if len < self.password_min_len || password.trim().is_empty() {
+      return Err(PolicyError::PasswordTooShort);
+    }
+    if len > self.password_max_len {
+      return Err(PolicyError::PasswordTooLong);
+    }
+    if char_classes(password) < self.password_min_classes {
+      return Err(PolicyError::PasswordTooWeak);
+    }
+    let lower = password.to_lowercase();
+    if self.blocklist.contains(&lower) {
+      return Err(PolicyError::PasswordTooCommon);
+    }
+    if !user_id.is_empty() && lower.contains(&user_id.to_lowercase()) {
+      return Err(PolicyError::PasswordContainsUserId);
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn checks_user_id_and_password() {
+    let policy = CredentialPolicy::default();
+    assert_eq!(policy.check_user_id("alice_01"), Ok(()));
+    for id in [
+      "",
+      "ab",
+      " alice",
+      "_alice",
+      "al ice",
+      "张三丰",
+      &"a".repeat(33),
+    ] {
+      assert_eq!(policy.check_user_id(id), Err(PolicyError::InvalidUserId));
+    }
+    for id in ["guest-1234", "Guest-alice"] {
+      assert_eq!(policy.check_user_id(id), Err(PolicyError::ReservedUserId));
+    }
+    assert_eq!(policy.check_user_id("guest_alice"), Ok(()));
+
+    let check = |password: &str| policy.check_password("alice", password);
+    assert_eq!(check("Tr0ub4dor"), Ok(()));
+    assert_eq!(check("        "), Err(PolicyError::PasswordTooShort));
+    assert_eq!(check("abc"), Err(PolicyError::PasswordTooShort));
+    assert_eq!(check(&"a1".repeat(65)), Err(PolicyError::PasswordTooLong));
+    assert_eq!(check("abcdefghij"), Err(PolicyError::PasswordTooWeak));
+    assert_eq!(check("Password123"), Err(PolicyError::PasswordTooCommon));
+    assert_eq!(
+      check("ALICE-2024"),
+      Err(PolicyError::PasswordContainsUserId)
+    );
+  }
+}
@@ -0,0 +1,113 @@
+use crate::entities::{filter_preset, room, room_user};
+use sea_orm::{
+  sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr,
+  EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+
+pub struct FilterPresetService;
+
+pub const VISIBILITY_PRIVATE: &str = "private";
+pub const VISIBILITY_ROOM: &str = "room";
+pub const VISIBILITY_PUBLIC: &str = "public";
+
+impl FilterPresetService {
+  pub async fn create_preset(
+    dbconn: &DatabaseConnection,
+    preset: filter_preset::ActiveModel,
+  ) -> Result<filter_preset::Model, DbErr> {
+    preset.insert(dbconn).await
+  }
+  pub async fn get_preset_by_id(
+    dbconn: &DatabaseConnection,
+    id: i32,
+  ) -> Result<Option<filter_preset::Model>, DbErr> {
+    filter_preset::Entity::find_by_id(id).one(dbconn).await
+  }
+  /// 用户可见的预设：自己的、公开的以及共享到所在会议的
+  pub async fn get_visible_presets(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<Vec<filter_preset::Model>, DbErr> {
+    let room_ids = room_user::Entity::find()
+      .select_only()
+      .column(room_user::Column::RoomId)
+      .filter(room_user::Column::UserId.eq(user_id))
+      .into_tuple::<i32>()
+      .all(dbconn)
+      .await?;
+    filter_preset::Entity::find()
+      .filter(
+        Condition::any()
+          .add(filter_preset::Column::Owner.eq(user_id))
+          .add(filter_preset::Column::Visibility.eq(VISIBILITY_PUBLIC))
+          .add(
+            Condition::all()
+              .add(filter_preset::Column::Visibility.eq(VISIBILITY_ROOM))
+              .add(filter_preset::Column::RoomId.is_in(room_ids)),
+          ),
+      )
+      .order_by_desc(filter_preset::Column::UpdatedAt)
+      .all(dbconn)
+      .await
+  }
+  pub async fn update_preset(
+    dbconn: &DatabaseConnection,
+    preset: filter_preset::ActiveModel,
+  ) -> Result<filter_preset::Model, DbErr> {
+    preset.update(dbconn).await
+  }
+  /// 删除预设，并取消引用它的会议置顶
+  pub async fn delete_preset(dbconn: &DatabaseConnection, id: i32) -> Result<(), DbErr> {
+    let txn = dbconn.begin().await?;
+    room::Entity::update_many()
+      .col_expr(
+        room::Column::PinnedFilterId,
+        Expr::value(Option::<i32>::None),
+      )
+      .filter(room::Column::PinnedFilterId.eq(id))
+      .exec(&txn)
+      .await?;
+    filter_preset::Entity::delete_by_id(id).exec(&txn).await?;
+    txn.commit().await
+  }
+  /// 预设不再对会议可见时取消置顶
+  pub async fn unpin_if_hidden(
+    dbconn: &DatabaseConnection,
+    preset: &filter_preset::Model,
+  ) -> Result<(), DbErr> {
+    let mut cond = Condition::all().add(room::Column::PinnedFilterId.eq(preset.id));
+    if preset.visibility == VISIBILITY_PUBLIC {
+      return Ok(());
+    }
+    if let (VISIBILITY_ROOM, Some(room_id)) = (preset.visibility.as_str(), preset.room_id) {
+      cond = cond.add(room::Column::Id.ne(room_id));
+    }
+    room::Entity::update_many()
+      .col_expr(
+        room::Column::PinnedFilterId,
+        Expr::value(Option::<i32>::None),
+      )
+      .filter(cond)
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  pub async fn pin_preset(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    preset_id: Option<i32>,
+  ) -> Result<(), DbErr> {
+    room::Entity::update_many()
+      .col_expr(room::Column::PinnedFilterId, Expr::value(preset_id))
+      .filter(room::Column::Id.eq(room_id))
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+}
+
+/// 仅公开或共享到该会议的预设可被置顶，保证与会者都能看到
+pub fn visible_in_room(preset: &filter_preset::Model, room_id: i32) -> bool {
+  preset.visibility == VISIBILITY_PUBLIC
+    || (preset.visibility == VISIBILITY_ROOM && preset.room_id == Some(room_id))
+}
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use livekit_api::services::room::{RoomClient, UpdateParticipantOptions};
+use livekit_api::services::ServiceResult;
+use livekit_protocol::{ParticipantInfo, TrackInfo};
+
+/// 会中管理用到的 LiveKit 房间接口，便于在测试中替换
+#[async_trait::async_trait]
+pub trait LiveKitRoomClient: Send + Sync + fmt::Debug {
+  async fn list_participants(&self, room: &str) -> ServiceResult<Vec<ParticipantInfo>>;
+  async fn get_participant(&self, room: &str, identity: &str) -> ServiceResult<ParticipantInfo>;
+  async fn mute_published_track(
+    &self,
+    room: &str,
+    identity: &str,
+    track_sid: &str,
+    muted: bool,
+  ) -> ServiceResult<TrackInfo>;
+  async fn remove_participant(&self, room: &str, identity: &str) -> ServiceResult<()>;
+  async fn update_participant(
+    &self,
+    room: &str,
+    identity: &str,
+    options: UpdateParticipantOptions,
+  ) -> ServiceResult<ParticipantInfo>;
+  async fn delete_room(&self, room: &str) -> ServiceResult<()>;
+}
+
+#[async_trait::async_trait]
+impl LiveKitRoomClient for RoomClient {
+  async fn list_participants(&self, room: &str) -> ServiceResult<Vec<ParticipantInfo>> {
+    RoomClient::list_participants(self, room).await
+  }
+  async fn get_participant(&self, room: &str, identity: &str) -> ServiceResult<ParticipantInfo> {
+    RoomClient::get_participant(self, room, identity).await
+  }
+  async fn mute_published_track(
+    &self,
+    room: &str,
+    identity: &str,
+    track_sid: &str,
+    muted: bool,
+  ) -> ServiceResult<TrackInfo> {
+    RoomClient::mute_published_track(self, room, identity, track_sid, muted).await
+  }
+  async fn remove_participant(&self, room: &str, identity: &str) -> ServiceResult<()> {
+    RoomClient::remove_participant(self, room, identity).await
+  }
+  async fn update_participant(
+    &self,
+    room: &str,
+    identity: &str,
+    options: UpdateParticipantOptions,
+  ) -> ServiceResult<ParticipantInfo> {
+    RoomClient::update_participant(self, room, identity, options).await
+  }
+  async fn delete_room(&self, room: &str) -> ServiceResult<()> {
+    RoomClient::delete_room(self, room).await
+  }
+}
+
+/// 在内存中模拟房间内的参会者，每人带一条麦克风音轨 `TR_<identity>`
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MockRoomClient {
+  pub rooms: std::sync::Mutex<std::collections::HashMap<String, Vec<ParticipantInfo>>>,
+}
+
+#[cfg(test)]
+impl MockRoomClient {
+  pub fn with_participants(room: &str, identities: &[&str]) -> Self {
+    let participants = identities
+      .iter()
+      .map(|x| ParticipantInfo {
+        sid: format!("PA_{x}"),
+        identity: x.to_string(),
+        permission: Some(livekit_protocol::ParticipantPermission {
+          can_subscribe: true,
+          can_publish: true,
+          can_publish_data: true,
+          ..Default::default()
+        }),
+        tracks: vec![TrackInfo {
+          sid: format!("TR_{x}"),
+          r#type: livekit_protocol::TrackType::Audio as i32,
+          source: livekit_protocol::TrackSource::Microphone as i32,
+          ..Default::default()
+        }],
+        ..Default::default()
+      })
+      .collect();
+    MockRoomClient {
+      rooms: std::sync::Mutex::new([(room.to_string(), participants)].into()),
+    }
+  }
+
+  fn with_participant<T>(
+    &self,
+    room: &str,
+    identity: &str,
+    f: impl FnOnce(&mut Vec<ParticipantInfo>, usize) -> Option<T>,
+  ) -> ServiceResult<T> {
+    use livekit_api::services::{TwirpError, TwirpErrorCode};
+    let mut rooms = self.rooms.lock().unwrap();
+    let participants = rooms.entry(room.to_string()).or_default();
+    participants
+      .iter()
+      .position(|x| x.identity == identity)
+      .and_then(|i| f(participants, i))
+      .ok_or(
+        TwirpError::Twirp(TwirpErrorCode {
+          code: TwirpErrorCode::NOT_FOUND.to_string(),
+          msg: "participant not found".to_string(),
+        })
+        .into(),
+      )
+  }
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl LiveKitRoomClient for MockRoomClient {
+  async fn list_participants(&self, room: &str) -> ServiceResult<Vec<ParticipantInfo>> {
+    Ok(
+      self
+        .rooms
+        .lock()
+        .unwrap()
+        .get(room)
+        .cloned()
+        .unwrap_or_default(),
+    )
+  }
+  async fn get_participant(&self, room: &str, identity: &str) -> ServiceResult<ParticipantInfo> {
+    self.with_participant(room, identity, |x, i| Some(x[i].clone()))
+  }
+  async fn mute_published_track(
+    &self,
+    room: &str,
+    identity: &str,
+    track_sid: &str,
+    muted: bool,
+  ) -> ServiceResult<TrackInfo> {
+    self.with_participant(room, identity, |x, i| {
+      let track = x[i].tracks.iter_mut().find(|t| t.sid == track_sid)?;
+      track.muted = muted;
+      Some(track.clone())
+    })
+  }
+  async fn remove_participant(&self, room: &str, identity: &str) -> ServiceResult<()> {
+    self.with_participant(room, identity, |x, i| {
+      x.remove(i);
+      Some(())
+    })
+  }
+  async fn update_participant(
+    &self,
+    room: &str,
+    identity: &str,
+    options: UpdateParticipantOptions,
+  ) -> ServiceResult<ParticipantInfo> {
+    self.with_participant(room, identity, |x, i| {
+      if !options.metadata.is_empty() {
+        x[i].metadata = options.metadata;
+      }
+      if options.permission.is_some() {
+        x[i].permission = options.permission;
+      }
+      Some(x[i].clone())
+    })
+  }
+  async fn delete_room(&self, room: &str) -> ServiceResult<()> {
+    self.rooms.lock().unwrap().remove(room);
+    Ok(())
+  }
+}
@@ -0,0 +1,453 @@
+use std::{
+  fmt,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use futures_util::stream::{self, BoxStream, StreamExt};
+use log::debug;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+pub struct ChatMessage {
+  pub role: String,
+  pub content: String,
+}
+
+impl ChatMessage {
+  pub fn system(content: &str) -> Self {
+    ChatMessage {
+      role: "system".to_string(),
+      content: content.to_string(),
+    }
+  }
+  pub fn user(content: &str) -> Self {
+    ChatMessage {
+      role: "user".to_string(),
+      content: content.to_string(),
+    }
+  }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct TokenUsage {
+  pub prompt_tokens: u64,
+  pub completion_tokens: u64,
+  pub total_tokens: u64,
+}
+
+impl TokenUsage {
+  fn add(&mut self, other: &TokenUsage) {
+    self.prompt_tokens += other.prompt_tokens;
+    self.completion_tokens += other.completion_tokens;
+    self.total_tokens += other.total_tokens;
+  }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ChatResponse {
+  pub content: String,
+  pub usage: TokenUsage,
+}
+
+#[derive(Debug)]
+pub enum LlmError {
+  Request(String),
+  Status(u16, String),
+  Decode(String),
+  EmptyChoices,
+}
+
+impl fmt::Display for LlmError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      LlmError::Request(e) => write!(f, "request failed: {e}"),
+      LlmError::Status(code, body) => write!(f, "unexpected status {code}: {body}"),
+      LlmError::Decode(e) => write!(f, "decode response failed: {e}"),
+      LlmError::EmptyChoices => write!(f, "empty choices"),
+    }
+  }
+}
+
+/// 流式返回的增量文本
+pub type ChatStream = BoxStream<'static, Result<String, LlmError>>;
+
+#[async_trait::async_trait]
+pub trait LlmClient: Send + Sync + fmt::Debug {
+  async fn chat(&self, messages: Vec<ChatMessage>) -> Result<ChatResponse, LlmError>;
+  /// 以 SSE 流式返回增量文本，消耗的 token 计入 `usage`
+  async fn chat_stream(&self, messages: Vec<ChatMessage>) -> Result<ChatStream, LlmError>;
+  /// 客户端创建以来累计消耗的 token
+  fn usage(&self) -> TokenUsage;
+}
+
+/// 模型输出可能带有 markdown 代码块或多余文字，取第一个 `{` 到最后一个 `}` 之间的内容
+pub fn extract_json(content: &str) -> Option<&str> {
+  let start = content.find('{')?;
+  let end = content.rfind('}')?;
+  (start < end).then(|| &content[start..=end])
+}
+
+#[derive(serde::Deserialize)]
+struct CompletionMessage {
+  #[serde(default)]
+  content: String,
+}
+#[derive(serde::Deserialize)]
+struct CompletionChoice {
+  message: CompletionMessage,
+}
+#[derive(serde::Deserialize)]
+struct CompletionResp {
+  choices: Vec<CompletionChoice>,
+  #[serde(default)]
+  usage: Option<TokenUsage>,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct StreamDelta {
+  #[serde(default)]
+  content: Option<String>,
+}
+#[derive(serde::Deserialize)]
+struct StreamChoice {
+  #[serde(default)]
+  delta: StreamDelta,
+}
+#[derive(serde::Deserialize)]
+struct StreamChunk {
+  #[serde(default)]
+  choices: Vec<StreamChoice>,
+  #[serde(default)]
+  usage: Option<TokenUsage>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OpenAiConfig {
+  /// chat completions 的完整地址
+  pub base_url: String,
+  pub api_key: String,
+  pub model: String,
+  pub timeout: Duration,
+  /// 网络错误、429 及 5xx 时的重试次数
+  pub max_retries: u32,
+}
+
+/// OpenAI 兼容的 chat completions 客户端，内部复用同一个 `reqwest::Client`
+#[derive(Clone, Debug)]
+pub struct OpenAiClient {
+  http: Client,
+  config: OpenAiConfig,
+  usage: Arc<Mutex<TokenUsage>>,
+}
+
+impl OpenAiClient {
+  pub fn new(config: OpenAiConfig) -> reqwest::Result<Self> {
+    let http = Client::builder().timeout(config.timeout).build()?;
+    Ok(OpenAiClient {
+      http,
+      config,
+      usage: Arc::default(),
+    })
+  }
+
+  fn record_usage(usage: &Mutex<TokenUsage>, delta: &TokenUsage) {
+    if let Ok(mut usage) = usage.lock() {
+      usage.add(delta);
+    }
+  }
+
+  async fn send(
+    &self,
+    messages: &[ChatMessage],
+    stream: bool,
+  ) -> Result<reqwest::Response, LlmError> {
+    let mut body = json!({
+      "model": self.config.model,
+      "messages": messages,
+      "stream": stream,
+    });
+    if stream {
+      body["stream_options"] = json!({ "include_usage": true });
+    }
+    let mut attempt = 0;
+    loop {
+      let res = self
+        .http
+        .post(&self.config.base_url)
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", self.config.api_key))
+        .json(&body)
+        .send()
+        .await;
+      let err = match res {
+        Ok(resp) if resp.status().is_success() => return Ok(resp),
+        Ok(resp) => {
+          let status = resp.status();
+          let err = LlmError::Status(status.as_u16(), resp.text().await.unwrap_or_default());
+          if status != StatusCode::TOO_MANY_REQUESTS && !status.is_server_error() {
+            return Err(err);
+          }
+          err
+        }
+        Err(e) => LlmError::Request(e.to_string()),
+      };
+      if attempt >= self.config.max_retries {
+        return Err(err);
+      }
+      attempt += 1;
+      debug!("llm request failed, retry {attempt}: {err}");
+      actix_web::rt::time::sleep(Duration::from_millis(500 * 2u64.pow(attempt - 1))).await;
+    }
+  }
+}
+
+enum SseFrame {
+  Delta(Result<String, LlmError>),
+  Done,
+  /// 注释、空行、其他字段以及不含文本的增量
+  Skip,
+}
+
+/// 解析 SSE 中的 `data:` 行，`[DONE]` 表示流结束
+fn parse_sse_line(line: &str, usage: &Mutex<TokenUsage>) -> SseFrame {
+  let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+    return SseFrame::Skip;
+  };
+  if data == "[DONE]" {
+    return SseFrame::Done;
+  }
+  if data.is_empty() {
+    return SseFrame::Skip;
+  }
+  let chunk = match serde_json::from_str::<StreamChunk>(data) {
+    Ok(chunk) => chunk,
+    Err(e) => return SseFrame::Delta(Err(LlmError::Decode(e.to_string()))),
+  };
+  if let Some(delta) = &chunk.usage {
+    OpenAiClient::record_usage(usage, delta);
+  }
+  let content = chunk
+    .choices
+    .into_iter()
+    .filter_map(|c| c.delta.content)
+    .collect::<String>();
+  if content.is_empty() {
+    return SseFrame::Skip;
+  }
+  SseFrame::Delta(Ok(content))
+}
+
+#[async_trait::async_trait]
+impl LlmClient for OpenAiClient {
+  async fn chat(&self, messages: Vec<ChatMessage>) -> Result<ChatResponse, LlmError> {
+    let resp = self.send(&messages, false).await?;
+    let CompletionResp { choices, usage } = resp
+      .json::<CompletionResp>()
+      .await
+      .map_err(|e| LlmError::Decode(e.to_string()))?;
+    let usage = usage.unwrap_or_default();
+    Self::record_usage(&self.usage, &usage);
+    let Some(choice) = choices.into_iter().next() else {
+      return Err(LlmError::EmptyChoices);
+    };
+    Ok(ChatResponse {
+      content: choice.message.content,
+      usage,
+    })
+  }
+
+  async fn chat_stream(&self, messages: Vec<ChatMessage>) -> Result<ChatStream, LlmError> {
+    let resp = self.send(&messages, true).await?;
+    let usage = self.usage.clone();
+    // (响应, 未处理完的行缓冲, 待返回的增量)
+    let state = (
+      Some(resp),
+      String::new(),
+      Vec::<Result<String, LlmError>>::new(),
+    );
+    Ok(
+      stream::unfold(state, move |(mut resp, mut buf, mut pending)| {
+        let usage = usage.clone();
+        async move {
+          loop {
+            if !pending.is_empty() {
+              let item = pending.remove(0);
+              return Some((item, (resp, buf, pending)));
+            }
+            let chunk = match resp.as_mut()?.chunk().await {
+              Ok(Some(chunk)) => chunk,
+              // 未收到 [DONE] 就断开时，仍处理最后一行
+              Ok(None) => {
+                return match parse_sse_line(buf.trim(), &usage) {
+                  SseFrame::Delta(item) => Some((item, (None, String::new(), pending))),
+                  _ => None,
+                };
+              }
+              Err(e) => return Some((Err(LlmError::Request(e.to_string())), (None, buf, pending))),
+            };
+            buf.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(pos) = buf.find('\n') {
+              let line = buf[..pos].trim().to_string();
+              buf.drain(..=pos);
+              match parse_sse_line(&line, &usage) {
+                SseFrame::Delta(item) => pending.push(item),
+                SseFrame::Done => {
+                  // 丢弃连接，返回完已解析的增量后结束
+                  resp = None;
+                  buf.clear();
+                  break;
+                }
+                SseFrame::Skip => {}
+              }
+            }
+          }
+        }
+      })
+      .boxed(),
+    )
+  }
+
+  fn usage(&self) -> TokenUsage {
+    self.usage.lock().map(|u| *u).unwrap_or_default()
+  }
+}
+
+/// 按顺序返回预设回复，并记录收到的请求
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MockLlmClient {
+  pub replies: Mutex<std::collections::VecDeque<Result<String, LlmError>>>,
+  pub requests: Mutex<Vec<Vec<ChatMessage>>>,
+}
+
+#[cfg(test)]
+impl MockLlmClient {
+  pub fn with_replies(replies: Vec<&str>) -> Self {
+    MockLlmClient {
+      replies: Mutex::new(replies.into_iter().map(|r| Ok(r.to_string())).collect()),
+      requests: Mutex::default(),
+    }
+  }
+}
+
+#[cfg(test)]
+#[async_trait::async_trait]
+impl LlmClient for MockLlmClient {
+  async fn chat(&self, messages: Vec<ChatMessage>) -> Result<ChatResponse, LlmError> {
+    self.requests.lock().unwrap().push(messages);
+    let content = self
+      .replies
+      .lock()
+      .unwrap()
+      .pop_front()
+      .unwrap_or(Err(LlmError::EmptyChoices))?;
+    Ok(ChatResponse {
+      content,
+      usage: TokenUsage::default(),
+    })
+  }
+
+  /// 将预设回复按每 4 个字符拆分为增量返回
+  async fn chat_stream(&self, messages: Vec<ChatMessage>) -> Result<ChatStream, LlmError> {
+    let content = self.chat(messages).await?.content;
+    let chars = content.chars().collect::<Vec<_>>();
+    let deltas = chars
+      .chunks(4)
+      .map(|x| Ok(x.iter().collect::<String>()))
+      .collect::<Vec<_>>();
+    Ok(stream::iter(deltas).boxed())
+  }
+
+  fn usage(&self) -> TokenUsage {
+    TokenUsage::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  use actix_web::{post, web, App, HttpResponse, HttpServer, Responder};
+
+  use super::*;
+
+  static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+  #[post("/chat/completions")]
+  async fn mock_completions(body: web::Json<serde_json::Value>) -> impl Responder {
+    assert_eq!(body["model"], "test-model");
+    if body["stream"] == true {
+      return HttpResponse::Ok().content_type("text/event-stream").body(
+        "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+         data: {\"choices\":[{\"delta\":{\"content\":\"{\\\"blur\\\"\"}}]}\n\n\
+         data: {\"choices\":[{\"delta\":{\"content\":\":2}\"}}]}\n\n\
+         data: {\"choices\":[],\"usage\":{\"prompt_tokens\":7,\"completion_tokens\":3,\"total_tokens\":10}}\n\n\
+         data: [DONE]\n\n\
+         data: {\"choices\":[{\"delta\":{\"content\":\"ignored\"}}]}\n\n",
+      );
+    }
+    // 第一次请求模拟服务端异常以验证重试
+    if CALLS.fetch_add(1, Ordering::SeqCst) == 0 {
+      return HttpResponse::ServiceUnavailable().finish();
+    }
+    HttpResponse::Ok().json(json!({
+      "choices": [{ "message": { "role": "assistant", "content": "{}" } }],
+      "usage": { "prompt_tokens": 20, "completion_tokens": 2, "total_tokens": 22 }
+    }))
+  }
+
+  #[actix_web::test]
+  async fn openai_client_against_mock_completions() {
+    let server = HttpServer::new(|| App::new().service(mock_completions))
+      .bind(("127.0.0.1", 0))
+      .unwrap();
+    let addr = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+
+    let client = OpenAiClient::new(OpenAiConfig {
+      base_url: format!("http://{addr}/chat/completions"),
+      api_key: "test-key".to_string(),
+      model: "test-model".to_string(),
+      timeout: Duration::from_secs(5),
+      max_retries: 1,
+    })
+    .unwrap();
+    let res = client
+      .chat(vec![ChatMessage::system("s"), ChatMessage::user("u")])
+      .await
+      .unwrap();
+    assert_eq!(res.content, "{}");
+    assert_eq!(res.usage.total_tokens, 22);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+
+    let content = client
+      .chat_stream(vec![ChatMessage::user("u")])
+      .await
+      .unwrap()
+      .map(|x| x.unwrap())
+      .collect::<Vec<_>>()
+      .await
+      .concat();
+    assert_eq!(content, "{\"blur\":2}");
+    assert_eq!(client.usage().total_tokens, 32);
+  }
+
+  #[actix_web::test]
+  async fn mock_client_streams_reply_in_deltas() {
+    let client = MockLlmClient::with_replies(vec!["{\"blur\":2}"]);
+    let deltas = client
+      .chat_stream(vec![ChatMessage::user("u")])
+      .await
+      .unwrap()
+      .map(|x| x.unwrap())
+      .collect::<Vec<_>>()
+      .await;
+    assert_eq!(deltas, vec!["{\"bl", "ur\":", "2}"]);
+    assert_eq!(client.requests.lock().unwrap().len(), 1);
+    // 回复耗尽后返回错误
+    assert!(client.chat_stream(vec![]).await.is_err());
+  }
+}
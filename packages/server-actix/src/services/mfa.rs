@@ -0,0 +1,340 @@
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use rand::random_range;
+use sea_orm::{
+  sea_query::Expr, sqlx::types::chrono::NaiveDateTime, ActiveValue, ColumnTrait, ConnectionTrait,
+  DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+};
+
+use crate::entities::{mfa_recovery_code, user_mfa};
+use crate::error::AppError;
+use crate::services::auth::{hash_token, to_hex};
+
+/// RFC 6238 默认参数：HMAC-SHA1、30 秒一步、6 位数字
+pub const TOTP_STEP_SECS: i64 = 30;
+pub const TOTP_DIGITS: usize = 6;
+/// 允许前后各一步的时钟误差
+const TOTP_SKEW_STEPS: i64 = 1;
+const SECRET_LEN: usize = 20;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+/// 身份验证器中显示的服务名
+pub const MFA_ISSUER: &str = "omeeting";
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// RFC 4648 base32，不补 `=`，身份验证器通用的密钥格式
+pub fn base32_encode(bytes: &[u8]) -> String {
+  let mut out = String::new();
+  let (mut buffer, mut bits) = (0u32, 0);
+  for &b in bytes {
+    buffer = (buffer << 8) | b as u32;
+    bits += 8;
+    while bits >= 5 {
+      bits -= 5;
+      out.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+    }
+  }
+  if bits > 0 {
+    out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+  }
+  out
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+  if !s.len().is_multiple_of(2) {
+    return None;
+  }
+  (0..s.len())
+    .step_by(2)
+    .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+    .collect()
+}
+
+/// 解析环境变量中的 64 位十六进制密钥
+pub fn parse_key(hex: &str) -> Option<[u8; 32]> {
+  from_hex(hex.trim())?.try_into().ok()
+}
+
+/// 第 `step` 个时间步的验证码
+pub fn totp(secret: &[u8], step: i64) -> Result<u32, ErrorStack> {
+  let key = PKey::hmac(secret)?;
+  let mut signer = Signer::new(MessageDigest::sha1(), &key)?;
+  signer.update(&step.to_be_bytes())?;
+  let hash = signer.sign_to_vec()?;
+  let offset = (hash[hash.len() - 1] & 0xf) as usize;
+  let value = u32::from_be_bytes([
+    hash[offset] & 0x7f,
+    hash[offset + 1],
+    hash[offset + 2],
+    hash[offset + 3],
+  ]);
+  Ok(value % 10u32.pow(TOTP_DIGITS as u32))
+}
+
+/// 校验验证码，返回匹配的时间步；不晚于 `last_step` 的时间步视为重放
+pub fn match_totp(
+  secret: &[u8],
+  code: &str,
+  unix_secs: i64,
+  last_step: i64,
+) -> Result<Option<i64>, ErrorStack> {
+  if code.len() != TOTP_DIGITS || !code.chars().all(|c| c.is_ascii_digit()) {
+    return Ok(None);
+  }
+  let current = unix_secs / TOTP_STEP_SECS;
+  for step in current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS {
+    if step > last_step && format!("{:0width$}", totp(secret, step)?, width = TOTP_DIGITS) == code {
+      return Ok(Some(step));
+    }
+  }
+  Ok(None)
+}
+
+fn percent_encode(s: &str) -> String {
+  s.bytes()
+    .map(|b| match b {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        (b as char).to_string()
+      }
+      _ => format!("%{b:02X}"),
+    })
+    .collect()
+}
+
+/// 供身份验证器扫码导入的地址
+pub fn otpauth_uri(user_id: &str, secret: &[u8]) -> String {
+  format!(
+    "otpauth://totp/{issuer}:{user}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={TOTP_DIGITS}&period={TOTP_STEP_SECS}",
+    issuer = percent_encode(MFA_ISSUER),
+    user = percent_encode(user_id),
+    secret = base32_encode(secret),
+  )
+}
+
+/// AES-256-GCM 加密，结果为十六进制的 nonce + 密文 + tag
+pub fn encrypt_secret(key: &[u8; 32], secret: &[u8]) -> Result<String, ErrorStack> {
+  let nonce = rand::random::<[u8; NONCE_LEN]>();
+  let mut tag = [0u8; TAG_LEN];
+  let cipher = encrypt_aead(
+    Cipher::aes_256_gcm(),
+    key,
+    Some(&nonce),
+    &[],
+    secret,
+    &mut tag,
+  )?;
+  Ok(to_hex(&[&nonce[..], &cipher, &tag].concat()))
+}
+
+/// 数据被篡改或密钥不匹配时返回 None
+pub fn decrypt_secret(key: &[u8; 32], data: &str) -> Option<Vec<u8>> {
+  let data = from_hex(data)?;
+  if data.len() < NONCE_LEN + TAG_LEN {
+    return None;
+  }
+  let (nonce, rest) = data.split_at(NONCE_LEN);
+  let (cipher, tag) = rest.split_at(rest.len() - TAG_LEN);
+  decrypt_aead(Cipher::aes_256_gcm(), key, Some(nonce), &[], cipher, tag).ok()
+}
+
+/// 忽略用户输入中的空格、连字符与大小写
+fn normalize_code(code: &str) -> String {
+  code
+    .chars()
+    .filter(|c| !c.is_whitespace() && *c != '-')
+    .collect::<String>()
+    .to_lowercase()
+}
+
+fn new_recovery_code() -> String {
+  let chars = (0..10)
+    .map(|_| RECOVERY_CODE_CHARS[random_range(0..RECOVERY_CODE_CHARS.len())] as char)
+    .collect::<String>();
+  format!("{}-{}", &chars[..5], &chars[5..])
+}
+
+pub struct MfaService;
+
+impl MfaService {
+  pub async fn get(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<Option<user_mfa::Model>, DbErr> {
+    user_mfa::Entity::find_by_id(user_id).one(dbconn).await
+  }
+  pub async fn is_enabled(dbconn: &DatabaseConnection, user_id: &str) -> Result<bool, DbErr> {
+    Ok(Self::get(dbconn, user_id).await?.is_some_and(|x| x.enabled))
+  }
+  fn secret(key: &[u8; 32], mfa: &user_mfa::Model) -> Result<Vec<u8>, AppError> {
+    decrypt_secret(key, &mfa.secret)
+      .ok_or_else(|| AppError::Internal("decrypt mfa secret failed".to_string()))
+  }
+
+  /// 生成新密钥，覆盖未启用的旧密钥；已启用时返回 None
+  pub async fn setup(
+    dbconn: &DatabaseConnection,
+    key: &[u8; 32],
+    user_id: &str,
+    now: NaiveDateTime,
+  ) -> Result<Option<Vec<u8>>, AppError> {
+    if Self::is_enabled(dbconn, user_id).await? {
+      return Ok(None);
+    }
+    let secret = rand::random::<[u8; SECRET_LEN]>().to_vec();
+    user_mfa::Entity::delete_by_id(user_id).exec(dbconn).await?;
+    user_mfa::Entity::insert(user_mfa::ActiveModel {
+      user_id: ActiveValue::Set(user_id.to_string()),
+      secret: ActiveValue::Set(encrypt_secret(key, &secret)?),
+      enabled: ActiveValue::Set(false),
+      last_step: ActiveValue::Set(0),
+      created_at: ActiveValue::Set(now),
+    })
+    .exec(dbconn)
+    .await?;
+    Ok(Some(secret))
+  }
+
+  /// 校验首个验证码后启用并返回恢复码，验证码错误时返回 None
+  pub async fn enable(
+    dbconn: &DatabaseConnection,
+    key: &[u8; 32],
+    mfa: &user_mfa::Model,
+    code: &str,
+    now: NaiveDateTime,
+  ) -> Result<Option<Vec<String>>, AppError> {
+    let secret = Self::secret(key, mfa)?;
+    let unix = now.and_utc().timestamp();
+    let Some(step) = match_totp(&secret, &normalize_code(code), unix, 0)? else {
+      return Ok(None);
+    };
+    user_mfa::Entity::update_many()
+      .col_expr(user_mfa::Column::Enabled, Expr::value(true))
+      .col_expr(user_mfa::Column::LastStep, Expr::value(step))
+      .filter(user_mfa::Column::UserId.eq(&mfa.user_id))
+      .exec(dbconn)
+      .await?;
+    Ok(Some(
+      Self::regenerate_recovery_codes(dbconn, &mfa.user_id).await?,
+    ))
+  }
+
+  /// 校验验证码或未使用的恢复码，校验通过的验证码与恢复码均不能再次使用
+  pub async fn verify(
+    dbconn: &DatabaseConnection,
+    key: &[u8; 32],
+    user_id: &str,
+    code: &str,
+    now: NaiveDateTime,
+  ) -> Result<bool, AppError> {
+    let Some(mfa) = Self::get(dbconn, user_id).await?.filter(|x| x.enabled) else {
+      return Ok(false);
+    };
+    let code = normalize_code(code);
+    if code.len() == TOTP_DIGITS {
+      let secret = Self::secret(key, &mfa)?;
+      let unix = now.and_utc().timestamp();
+      let Some(step) = match_totp(&secret, &code, unix, mfa.last_step)? else {
+        return Ok(false);
+      };
+      // 并发提交同一验证码时只有一个成功
+      let res = user_mfa::Entity::update_many()
+        .col_expr(user_mfa::Column::LastStep, Expr::value(step))
+        .filter(user_mfa::Column::UserId.eq(user_id))
+        .filter(user_mfa::Column::LastStep.lt(step))
+        .exec(dbconn)
+        .await?;
+      return Ok(res.rows_affected > 0);
+    }
+    let res = mfa_recovery_code::Entity::update_many()
+      .col_expr(mfa_recovery_code::Column::UsedAt, Expr::value(now))
+      .filter(mfa_recovery_code::Column::UserId.eq(user_id))
+      .filter(mfa_recovery_code::Column::CodeHash.eq(hash_token(&code)))
+      .filter(mfa_recovery_code::Column::UsedAt.is_null())
+      .exec(dbconn)
+      .await?;
+    Ok(res.rows_affected > 0)
+  }
+
+  /// 作废旧恢复码并生成新的一组，明文只在此时返回
+  pub async fn regenerate_recovery_codes(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<Vec<String>, DbErr> {
+    mfa_recovery_code::Entity::delete_many()
+      .filter(mfa_recovery_code::Column::UserId.eq(user_id))
+      .exec(dbconn)
+      .await?;
+    let codes = (0..RECOVERY_CODE_COUNT)
+      .map(|_| new_recovery_code())
+      .collect::<Vec<_>>();
+    mfa_recovery_code::Entity::insert_many(codes.iter().map(|code| {
+      mfa_recovery_code::ActiveModel {
+        user_id: ActiveValue::Set(user_id.to_string()),
+        code_hash: ActiveValue::Set(hash_token(&normalize_code(code))),
+        used_at: ActiveValue::Set(None),
+        ..Default::default()
+      }
+    }))
+    .exec(dbconn)
+    .await?;
+    Ok(codes)
+  }
+  pub async fn recovery_codes_left(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<u64, DbErr> {
+    mfa_recovery_code::Entity::find()
+      .filter(mfa_recovery_code::Column::UserId.eq(user_id))
+      .filter(mfa_recovery_code::Column::UsedAt.is_null())
+      .count(dbconn)
+      .await
+  }
+  pub async fn disable<C: ConnectionTrait>(db: &C, user_id: &str) -> Result<(), DbErr> {
+    mfa_recovery_code::Entity::delete_many()
+      .filter(mfa_recovery_code::Column::UserId.eq(user_id))
+      .exec(db)
+      .await?;
+    user_mfa::Entity::delete_by_id(user_id)
+      .exec(db)
+      .await
+      .and(Ok(()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn totp_matches_rfc_6238_and_secret_round_trips() {
+    // RFC 6238 附录 B 的 SHA1 测试向量，取后 6 位
+    let secret = b"12345678901234567890";
+    for (time, code) in [
+      (59, "287082"),
+      (1111111109, "081804"),
+      (2000000000, "279037"),
+    ] {
+      assert_eq!(
+        match_totp(secret, code, time, 0).unwrap(),
+        Some(time / TOTP_STEP_SECS)
+      );
+    }
+    assert_eq!(match_totp(secret, "287082", 59, 1).unwrap(), None);
+    assert_eq!(match_totp(secret, "287082", 59 + 90, 0).unwrap(), None);
+    assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+    assert!(otpauth_uri("a b", secret).starts_with("otpauth://totp/omeeting:a%20b?secret=GEZDGNBV"));
+
+    let key = [7u8; 32];
+    let data = encrypt_secret(&key, secret).unwrap();
+    assert_eq!(decrypt_secret(&key, &data).unwrap(), secret);
+    assert!(decrypt_secret(&[8u8; 32], &data).is_none());
+    assert_eq!(parse_key(&"0a".repeat(32)), Some([10u8; 32]));
+    assert_eq!(parse_key("0a"), None);
+  }
+}
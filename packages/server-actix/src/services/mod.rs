@@ -1,3 +1,20 @@
 pub mod user;
 pub mod room;
-pub mod room_user;
\ No newline at end of file
+pub mod room_user;
+pub mod account;
+pub mod auth;
+pub mod calendar;
+pub mod credential;
+pub mod filter_preset;
+pub mod livekit;
+pub mod llm;
+pub mod mfa;
+pub mod moderation;
+pub mod rate_limit;
+pub mod recording;
+pub mod room_admission;
+pub mod room_invite;
+pub mod schedule;
+pub mod series;
+pub mod summary;
+pub mod transcript;
@@ -0,0 +1,94 @@
+use crate::entities::moderation_log;
+use sea_orm::{
+  sqlx::types::chrono::Utc, ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait,
+  DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+use ts_rs::TS;
+
+pub struct ModerationService;
+
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub enum ModerationAction {
+  Mute,
+  Unmute,
+  Remove,
+  UpdatePermission,
+  UpdateMetadata,
+}
+
+impl ModerationAction {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ModerationAction::Mute => "mute",
+      ModerationAction::Unmute => "unmute",
+      ModerationAction::Remove => "remove",
+      ModerationAction::UpdatePermission => "update_permission",
+      ModerationAction::UpdateMetadata => "update_metadata",
+    }
+  }
+}
+
+impl ModerationService {
+  /// 记录一次会中管理操作，detail 为操作参数的 json
+  pub async fn log<C: ConnectionTrait>(
+    db: &C,
+    room_id: i32,
+    actor: &str,
+    action: ModerationAction,
+    target: &str,
+    detail: serde_json::Value,
+  ) -> Result<moderation_log::Model, DbErr> {
+    moderation_log::ActiveModel {
+      room_id: ActiveValue::Set(room_id),
+      actor: ActiveValue::Set(actor.to_string()),
+      action: ActiveValue::Set(action.as_str().to_string()),
+      target: ActiveValue::Set(target.to_string()),
+      detail: ActiveValue::Set(detail.to_string()),
+      created_at: ActiveValue::Set(Utc::now().naive_utc()),
+      ..Default::default()
+    }
+    .insert(db)
+    .await
+  }
+  /// 在事务中记录同一对象的多项操作，返回记录 id
+  pub async fn log_all(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    actor: &str,
+    target: &str,
+    entries: Vec<(ModerationAction, serde_json::Value)>,
+  ) -> Result<Vec<i32>, DbErr> {
+    let txn = dbconn.begin().await?;
+    let mut ids = vec![];
+    for (action, detail) in entries {
+      ids.push(
+        Self::log(&txn, room_id, actor, action, target, detail)
+          .await?
+          .id,
+      );
+    }
+    txn.commit().await?;
+    Ok(ids)
+  }
+  /// 操作未生效时撤销预先写入的记录
+  pub async fn discard(dbconn: &DatabaseConnection, ids: &[i32]) -> Result<(), DbErr> {
+    moderation_log::Entity::delete_many()
+      .filter(moderation_log::Column::Id.is_in(ids.iter().copied()))
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  pub async fn get_logs_by_room_id(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+  ) -> Result<Vec<moderation_log::Model>, DbErr> {
+    moderation_log::Entity::find()
+      .filter(moderation_log::Column::RoomId.eq(room_id))
+      .order_by_desc(moderation_log::Column::CreatedAt)
+      .order_by_desc(moderation_log::Column::Id)
+      .all(dbconn)
+      .await
+  }
+}
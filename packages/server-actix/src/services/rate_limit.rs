@@ -0,0 +1,518 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use log::debug;
+use sea_orm::{
+  sea_query::{OnConflict, Query},
+  ActiveValue, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+  QueryOrder, QuerySelect,
+};
+
+use crate::entities::{login_failure, login_lockout, rate_limit_hit};
+
+/// 滑动窗口：`secs` 秒内最多 `max` 次
+#[derive(Clone, Copy, Debug)]
+pub struct Window {
+  pub max: usize,
+  pub secs: i64,
+}
+
+/// 同一 IP 的登录请求
+pub const LOGIN_IP_WINDOW: Window = Window {
+  max: 20,
+  secs: 5 * 60,
+};
+/// 同一用户 id 的登录失败
+pub const LOGIN_USER_WINDOW: Window = Window {
+  max: 10,
+  secs: 15 * 60,
+};
+/// 同一 IP 的注册请求
+pub const SIGNUP_IP_WINDOW: Window = Window {
+  max: 5,
+  secs: 60 * 60,
+};
+/// 窗口计数最多保留的时长，不小于上面各窗口
+const MAX_WINDOW_SECS: i64 = 60 * 60;
+
+/// 连续失败达到该次数后锁定账号
+pub const LOCKOUT_THRESHOLD: i32 = 5;
+const LOCKOUT_BASE_SECS: i64 = 60;
+const LOCKOUT_MAX_SECS: i64 = 24 * 60 * 60;
+/// 超过该时长没有新的失败且未在锁定中时，清除失败计数与锁定次数
+const LOCKOUT_RESET_SECS: i64 = 24 * 60 * 60;
+/// 清理过期计数与锁定记录的间隔
+const PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// 审计日志中的失败原因
+pub const REASON_WRONG_PASSWORD: &str = "wrong_password";
+pub const REASON_USER_NOT_FOUND: &str = "user_not_found";
+pub const REASON_USER_DISABLED: &str = "user_disabled";
+pub const REASON_LOCKED: &str = "locked";
+pub const REASON_RATE_LIMITED: &str = "rate_limited";
+pub const REASON_WRONG_MFA_CODE: &str = "wrong_mfa_code";
+
+/// 第 `lock_count` 次（从 0 开始）锁定的时长，每次翻倍直至上限
+pub fn lockout_secs(lock_count: i32) -> i64 {
+  (LOCKOUT_BASE_SECS << lock_count.clamp(0, 20)).min(LOCKOUT_MAX_SECS)
+}
+
+/// 向上取整的秒数，至少为 1
+fn wait_secs(wait: Duration) -> u64 {
+  ((wait.num_milliseconds() + 999) / 1000).max(1) as u64
+}
+
+/// 窗口已满时返回需等待的秒数，`hits` 为窗口内的记录，按时间升序
+pub fn retry_after(hits: &[NaiveDateTime], window: Window, now: NaiveDateTime) -> Option<u64> {
+  if hits.len() < window.max {
+    return None;
+  }
+  let oldest = hits[hits.len() - window.max];
+  Some(wait_secs(oldest + Duration::seconds(window.secs) - now))
+}
+
+/// 滑动窗口的计数存储
+#[async_trait::async_trait]
+pub trait HitStore: Send + Sync + fmt::Debug {
+  async fn record(&self, key: &str, now: NaiveDateTime) -> Result<(), DbErr>;
+  /// `since` 之后的记录，按时间升序
+  async fn recent(&self, key: &str, since: NaiveDateTime) -> Result<Vec<NaiveDateTime>, DbErr>;
+  async fn clear(&self, key: &str) -> Result<(), DbErr>;
+  /// 删除 `expired` 之前的记录，不再有记录的 key 一并移除
+  async fn prune(&self, expired: NaiveDateTime) -> Result<(), DbErr>;
+}
+
+/// 进程内存储，重启后清空，多实例部署时各自计数
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+  hits: Mutex<HashMap<String, VecDeque<NaiveDateTime>>>,
+}
+
+#[async_trait::async_trait]
+impl HitStore for MemoryStore {
+  async fn record(&self, key: &str, now: NaiveDateTime) -> Result<(), DbErr> {
+    let expired = now - Duration::seconds(MAX_WINDOW_SECS);
+    let mut hits = self.hits.lock().unwrap();
+    let list = hits.entry(key.to_string()).or_default();
+    while list.front().is_some_and(|t| *t < expired) {
+      list.pop_front();
+    }
+    list.push_back(now);
+    Ok(())
+  }
+  async fn recent(&self, key: &str, since: NaiveDateTime) -> Result<Vec<NaiveDateTime>, DbErr> {
+    let hits = self.hits.lock().unwrap();
+    Ok(
+      hits
+        .get(key)
+        .map(|list| list.iter().filter(|t| **t >= since).cloned().collect())
+        .unwrap_or_default(),
+    )
+  }
+  async fn clear(&self, key: &str) -> Result<(), DbErr> {
+    self.hits.lock().unwrap().remove(key);
+    Ok(())
+  }
+  async fn prune(&self, expired: NaiveDateTime) -> Result<(), DbErr> {
+    self.hits.lock().unwrap().retain(|_, list| {
+      while list.front().is_some_and(|t| *t < expired) {
+        list.pop_front();
+      }
+      !list.is_empty()
+    });
+    Ok(())
+  }
+}
+
+/// 数据库存储，多实例部署时共享计数
+#[derive(Debug)]
+pub struct DbStore {
+  db_conn: DatabaseConnection,
+}
+
+impl DbStore {
+  pub fn new(db_conn: DatabaseConnection) -> Self {
+    DbStore { db_conn }
+  }
+}
+
+#[async_trait::async_trait]
+impl HitStore for DbStore {
+  async fn record(&self, key: &str, now: NaiveDateTime) -> Result<(), DbErr> {
+    rate_limit_hit::Entity::delete_many()
+      .filter(rate_limit_hit::Column::Key.eq(key))
+      .filter(rate_limit_hit::Column::CreatedAt.lt(now - Duration::seconds(MAX_WINDOW_SECS)))
+      .exec(&self.db_conn)
+      .await?;
+    rate_limit_hit::Entity::insert(rate_limit_hit::ActiveModel {
+      key: ActiveValue::Set(key.to_string()),
+      created_at: ActiveValue::Set(now),
+      ..Default::default()
+    })
+    .exec(&self.db_conn)
+    .await
+    .and(Ok(()))
+  }
+  async fn recent(&self, key: &str, since: NaiveDateTime) -> Result<Vec<NaiveDateTime>, DbErr> {
+    rate_limit_hit::Entity::find()
+      .select_only()
+      .column(rate_limit_hit::Column::CreatedAt)
+      .filter(rate_limit_hit::Column::Key.eq(key))
+      .filter(rate_limit_hit::Column::CreatedAt.gte(since))
+      .order_by_asc(rate_limit_hit::Column::CreatedAt)
+      .into_tuple()
+      .all(&self.db_conn)
+      .await
+  }
+  async fn clear(&self, key: &str) -> Result<(), DbErr> {
+    rate_limit_hit::Entity::delete_many()
+      .filter(rate_limit_hit::Column::Key.eq(key))
+      .exec(&self.db_conn)
+      .await
+      .and(Ok(()))
+  }
+  async fn prune(&self, expired: NaiveDateTime) -> Result<(), DbErr> {
+    rate_limit_hit::Entity::delete_many()
+      .filter(rate_limit_hit::Column::CreatedAt.lt(expired))
+      .exec(&self.db_conn)
+      .await
+      .and(Ok(()))
+  }
+}
+
+/// 被拒绝的原因及需等待的秒数
+#[derive(Debug, PartialEq, Eq)]
+pub enum Throttle {
+  RateLimited(u64),
+  Locked(u64),
+}
+
+fn login_ip_key(ip: &str) -> String {
+  format!("login:ip:{ip}")
+}
+fn login_user_key(user_id: &str) -> String {
+  format!("login:user:{user_id}")
+}
+fn signup_ip_key(ip: &str) -> String {
+  format!("signup:ip:{ip}")
+}
+
+/// 登录与注册的限流：按 IP 限制请求频率，按用户 id 限制失败次数，连续失败后锁定账号，
+/// 锁定时长随锁定次数增长，登录成功后重置
+#[derive(Debug, Clone)]
+pub struct LoginGuard {
+  store: Arc<dyn HitStore>,
+}
+
+impl LoginGuard {
+  pub fn new(store: Arc<dyn HitStore>) -> Self {
+    LoginGuard { store }
+  }
+  pub fn memory() -> Self {
+    Self::new(Arc::new(MemoryStore::default()))
+  }
+
+  async fn check(
+    &self,
+    key: &str,
+    window: Window,
+    now: NaiveDateTime,
+  ) -> Result<Option<u64>, DbErr> {
+    let hits = self
+      .store
+      .recent(key, now - Duration::seconds(window.secs))
+      .await?;
+    Ok(retry_after(&hits, window, now))
+  }
+
+  /// 登录前检查，通过时计入本次请求，被拒绝时写入审计日志
+  pub async fn check_login(
+    &self,
+    dbconn: &DatabaseConnection,
+    ip: &str,
+    user_id: &str,
+    now: NaiveDateTime,
+  ) -> Result<Option<Throttle>, DbErr> {
+    let locked_until = login_lockout::Entity::find_by_id(user_id)
+      .one(dbconn)
+      .await?
+      .and_then(|x| x.locked_until)
+      .filter(|t| *t > now);
+    let (throttle, reason) = if let Some(until) = locked_until {
+      (Throttle::Locked(wait_secs(until - now)), REASON_LOCKED)
+    } else if let Some(secs) = self.check(&login_ip_key(ip), LOGIN_IP_WINDOW, now).await? {
+      (Throttle::RateLimited(secs), REASON_RATE_LIMITED)
+    } else if let Some(secs) = self
+      .check(&login_user_key(user_id), LOGIN_USER_WINDOW, now)
+      .await?
+    {
+      (Throttle::RateLimited(secs), REASON_RATE_LIMITED)
+    } else {
+      self.store.record(&login_ip_key(ip), now).await?;
+      return Ok(None);
+    };
+    Self::audit(dbconn, ip, user_id, reason, now).await?;
+    Ok(Some(throttle))
+  }
+
+  /// 记录一次失败，连续失败达到阈值时锁定账号
+  pub async fn login_failed(
+    &self,
+    dbconn: &DatabaseConnection,
+    ip: &str,
+    user_id: &str,
+    reason: &str,
+    now: NaiveDateTime,
+  ) -> Result<(), DbErr> {
+    Self::audit(dbconn, ip, user_id, reason, now).await?;
+    self.store.record(&login_user_key(user_id), now).await?;
+
+    let state = login_lockout::Entity::find_by_id(user_id)
+      .one(dbconn)
+      .await?;
+    let (mut failures, mut lock_count) = state.map_or((0, 0), |x| (x.failures, x.lock_count));
+    let mut locked_until = None;
+    failures += 1;
+    if failures >= LOCKOUT_THRESHOLD {
+      locked_until = Some(now + Duration::seconds(lockout_secs(lock_count)));
+      lock_count += 1;
+      failures = 0;
+    }
+    login_lockout::Entity::insert(login_lockout::ActiveModel {
+      user_id: ActiveValue::Set(user_id.to_string()),
+      failures: ActiveValue::Set(failures),
+      lock_count: ActiveValue::Set(lock_count),
+      locked_until: ActiveValue::Set(locked_until),
+    })
+    .on_conflict(
+      OnConflict::column(login_lockout::Column::UserId)
+        .update_columns([
+          login_lockout::Column::Failures,
+          login_lockout::Column::LockCount,
+          login_lockout::Column::LockedUntil,
+        ])
+        .to_owned(),
+    )
+    .exec(dbconn)
+    .await
+    .and(Ok(()))
+  }
+
+  pub async fn login_succeeded(
+    &self,
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+  ) -> Result<(), DbErr> {
+    self.store.clear(&login_user_key(user_id)).await?;
+    login_lockout::Entity::delete_by_id(user_id)
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+
+  /// 注册前检查，通过时计入本次请求
+  pub async fn check_signup(&self, ip: &str, now: NaiveDateTime) -> Result<Option<u64>, DbErr> {
+    let key = signup_ip_key(ip);
+    let res = self.check(&key, SIGNUP_IP_WINDOW, now).await?;
+    if res.is_none() {
+      self.store.record(&key, now).await?;
+    }
+    Ok(res)
+  }
+
+  /// 清除过期的窗口计数，以及未在锁定中且长时间没有新失败的锁定记录。
+  /// 失败时总会先写入审计日志，据此判断最近是否有失败
+  pub async fn prune(&self, dbconn: &DatabaseConnection, now: NaiveDateTime) -> Result<(), DbErr> {
+    self
+      .store
+      .prune(now - Duration::seconds(MAX_WINDOW_SECS))
+      .await?;
+    login_lockout::Entity::delete_many()
+      .filter(
+        Condition::any()
+          .add(login_lockout::Column::LockedUntil.is_null())
+          .add(login_lockout::Column::LockedUntil.lte(now)),
+      )
+      .filter(
+        login_lockout::Column::UserId.not_in_subquery(
+          Query::select()
+            .column(login_failure::Column::UserId)
+            .from(login_failure::Entity)
+            .and_where(
+              login_failure::Column::CreatedAt.gte(now - Duration::seconds(LOCKOUT_RESET_SECS)),
+            )
+            .to_owned(),
+        ),
+      )
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  /// 后台定时清理，随服务启动
+  pub async fn run_prune_job(self, dbconn: DatabaseConnection) {
+    let mut interval = actix_web::rt::time::interval(PRUNE_INTERVAL);
+    loop {
+      interval.tick().await;
+      if let Err(e) = self.prune(&dbconn, Utc::now().naive_utc()).await {
+        debug!("prune login throttle err: {:?}", e);
+      }
+    }
+  }
+
+  async fn audit(
+    dbconn: &DatabaseConnection,
+    ip: &str,
+    user_id: &str,
+    reason: &str,
+    now: NaiveDateTime,
+  ) -> Result<(), DbErr> {
+    login_failure::Entity::insert(login_failure::ActiveModel {
+      user_id: ActiveValue::Set(user_id.to_string()),
+      ip: ActiveValue::Set(ip.to_string()),
+      reason: ActiveValue::Set(reason.to_string()),
+      created_at: ActiveValue::Set(now),
+      ..Default::default()
+    })
+    .exec(dbconn)
+    .await
+    .and(Ok(()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::setup_db;
+
+  #[actix_web::test]
+  async fn locks_out_with_growing_duration() {
+    let db = setup_db().await;
+    let t0 = NaiveDateTime::default();
+    let at = |secs: i64| t0 + Duration::seconds(secs);
+    for guard in [
+      LoginGuard::memory(),
+      LoginGuard::new(Arc::new(DbStore::new(db.clone()))),
+    ] {
+      login_lockout::Entity::delete_many()
+        .exec(&db)
+        .await
+        .unwrap();
+      for _ in 0..LOCKOUT_THRESHOLD {
+        assert_eq!(
+          guard.check_login(&db, "ip", "alice", t0).await.unwrap(),
+          None
+        );
+        guard
+          .login_failed(&db, "ip", "alice", REASON_WRONG_PASSWORD, t0)
+          .await
+          .unwrap();
+      }
+      assert_eq!(
+        guard.check_login(&db, "ip", "alice", at(10)).await.unwrap(),
+        Some(Throttle::Locked(50))
+      );
+      // 再次连续失败后锁定时长翻倍
+      for _ in 0..LOCKOUT_THRESHOLD {
+        guard
+          .login_failed(&db, "ip", "alice", REASON_WRONG_PASSWORD, at(60))
+          .await
+          .unwrap();
+      }
+      assert_eq!(
+        guard.check_login(&db, "ip", "alice", at(60)).await.unwrap(),
+        Some(Throttle::Locked(120))
+      );
+      // 用户 id 的失败次数达到窗口上限
+      assert_eq!(
+        guard
+          .check_login(&db, "ip2", "alice", at(180))
+          .await
+          .unwrap(),
+        Some(Throttle::RateLimited(LOGIN_USER_WINDOW.secs as u64 - 180))
+      );
+      guard.login_succeeded(&db, "alice").await.unwrap();
+      assert_eq!(
+        guard
+          .check_login(&db, "ip2", "alice", at(180))
+          .await
+          .unwrap(),
+        None
+      );
+
+      for _ in 0..SIGNUP_IP_WINDOW.max {
+        assert_eq!(guard.check_signup("ip", t0).await.unwrap(), None);
+      }
+      assert_eq!(
+        guard.check_signup("ip", at(600)).await.unwrap(),
+        Some(SIGNUP_IP_WINDOW.secs as u64 - 600)
+      );
+    }
+  }
+
+  #[actix_web::test]
+  async fn prunes_expired_hits_and_lockouts() {
+    let db = setup_db().await;
+    let t0 = NaiveDateTime::default();
+    let at = |secs: i64| t0 + Duration::seconds(secs);
+    let memory = Arc::new(MemoryStore::default());
+    for (guard, store) in [
+      (LoginGuard::new(memory.clone()), Some(memory)),
+      (LoginGuard::new(Arc::new(DbStore::new(db.clone()))), None),
+    ] {
+      login_lockout::Entity::delete_many()
+        .exec(&db)
+        .await
+        .unwrap();
+      for user_id in ["alice", "bob"] {
+        for _ in 0..LOCKOUT_THRESHOLD {
+          guard
+            .login_failed(&db, "ip", user_id, REASON_WRONG_PASSWORD, t0)
+            .await
+            .unwrap();
+        }
+      }
+      guard
+        .login_failed(
+          &db,
+          "ip",
+          "bob",
+          REASON_WRONG_PASSWORD,
+          at(LOCKOUT_RESET_SECS),
+        )
+        .await
+        .unwrap();
+      guard.check_signup("ip", t0).await.unwrap();
+
+      guard.prune(&db, at(LOCKOUT_RESET_SECS + 1)).await.unwrap();
+      let users = login_lockout::Entity::find()
+        .all(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|x| x.user_id)
+        .collect::<Vec<_>>();
+      assert_eq!(users, ["bob"]);
+      if let Some(store) = store {
+        let keys = store
+          .hits
+          .lock()
+          .unwrap()
+          .keys()
+          .cloned()
+          .collect::<Vec<_>>();
+        assert_eq!(keys, [login_user_key("bob")]);
+      } else {
+        let keys = rate_limit_hit::Entity::find()
+          .all(&db)
+          .await
+          .unwrap()
+          .into_iter()
+          .map(|x| x.key)
+          .collect::<Vec<_>>();
+        assert_eq!(keys, [login_user_key("bob")]);
+      }
+    }
+  }
+}
@@ -0,0 +1,260 @@
+use crate::entities::recording;
+use livekit_protocol::{EgressInfo, EgressStatus};
+use sea_orm::{
+  sea_query::Expr,
+  sqlx::types::chrono::{DateTime, NaiveDateTime, Utc},
+  ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+  QueryOrder,
+};
+
+pub struct RecordingService;
+
+pub const STATUS_STARTING: &str = "starting";
+pub const STATUS_ACTIVE: &str = "active";
+pub const STATUS_ENDING: &str = "ending";
+pub const STATUS_COMPLETE: &str = "complete";
+pub const STATUS_FAILED: &str = "failed";
+pub const STATUS_ABORTED: &str = "aborted";
+pub const STATUS_LIMIT_REACHED: &str = "limit_reached";
+
+pub fn status_of_egress(status: i32) -> &'static str {
+  match EgressStatus::try_from(status) {
+    Ok(EgressStatus::EgressActive) => STATUS_ACTIVE,
+    Ok(EgressStatus::EgressEnding) => STATUS_ENDING,
+    Ok(EgressStatus::EgressComplete) => STATUS_COMPLETE,
+    Ok(EgressStatus::EgressFailed) => STATUS_FAILED,
+    Ok(EgressStatus::EgressAborted) => STATUS_ABORTED,
+    Ok(EgressStatus::EgressLimitReached) => STATUS_LIMIT_REACHED,
+    _ => STATUS_STARTING,
+  }
+}
+
+/// LiveKit 返回的时间均为纳秒时间戳，0 表示未设置
+fn nanos_to_datetime(nanos: i64) -> Option<NaiveDateTime> {
+  if nanos <= 0 {
+    return None;
+  }
+  Some(DateTime::from_timestamp_nanos(nanos).naive_utc())
+}
+
+impl RecordingService {
+  pub async fn create_recording(
+    dbconn: &DatabaseConnection,
+    recording: recording::ActiveModel,
+  ) -> Result<(), DbErr> {
+    recording::Entity::insert(recording)
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  pub async fn get_recording_by_egress_id(
+    dbconn: &DatabaseConnection,
+    egress_id: &str,
+  ) -> Result<Option<recording::Model>, DbErr> {
+    recording::Entity::find()
+      .filter(recording::Column::EgressId.eq(egress_id))
+      .one(dbconn)
+      .await
+  }
+  pub async fn get_recordings_by_room_id(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+  ) -> Result<Vec<recording::Model>, DbErr> {
+    recording::Entity::find()
+      .filter(recording::Column::RoomId.eq(room_id))
+      .order_by_asc(recording::Column::StartTime)
+      .all(dbconn)
+      .await
+  }
+  /// 记录由用户发起的录制，webhook 可能先于此到达，因此先同步再补充发起人
+  pub async fn start_recording(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    egress: &EgressInfo,
+    started_by: &str,
+  ) -> Result<(), DbErr> {
+    Self::sync_egress(dbconn, room_id, egress).await?;
+    recording::Entity::update_many()
+      .col_expr(recording::Column::StartedBy, Expr::value(started_by))
+      .filter(recording::Column::EgressId.eq(&egress.egress_id))
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  /// 根据 LiveKit 上报的 egress 信息创建或更新录制记录
+  pub async fn sync_egress(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    egress: &EgressInfo,
+  ) -> Result<(), DbErr> {
+    let file = egress.file_results.first();
+    let file_path = file.map(|f| f.filename.clone()).filter(|f| !f.is_empty());
+    let start_time = nanos_to_datetime(egress.started_at);
+    let end_time = nanos_to_datetime(egress.ended_at);
+    let status = status_of_egress(egress.status).to_string();
+
+    let Some(recording) = Self::get_recording_by_egress_id(dbconn, &egress.egress_id).await? else {
+      return Self::create_recording(
+        dbconn,
+        recording::ActiveModel {
+          egress_id: ActiveValue::Set(egress.egress_id.clone()),
+          room_id: ActiveValue::Set(room_id),
+          started_by: ActiveValue::Set(None),
+          start_time: ActiveValue::Set(start_time.unwrap_or_else(|| Utc::now().naive_utc())),
+          end_time: ActiveValue::Set(end_time),
+          status: ActiveValue::Set(status),
+          file_path: ActiveValue::Set(file_path.unwrap_or_default()),
+          size: ActiveValue::Set(file.map_or(0, |f| f.size)),
+          duration: ActiveValue::Set(file.map_or(0, |f| f.duration / 1_000_000)),
+          ..Default::default()
+        },
+      )
+      .await;
+    };
+    recording::ActiveModel {
+      id: ActiveValue::Set(recording.id),
+      start_time: start_time.map_or(ActiveValue::NotSet, ActiveValue::Set),
+      end_time: end_time.map_or(ActiveValue::NotSet, |t| ActiveValue::Set(Some(t))),
+      status: ActiveValue::Set(status),
+      file_path: file_path.map_or(ActiveValue::NotSet, ActiveValue::Set),
+      size: file
+        .filter(|f| f.size > 0)
+        .map_or(ActiveValue::NotSet, |f| ActiveValue::Set(f.size)),
+      duration: file
+        .filter(|f| f.duration > 0)
+        .map_or(ActiveValue::NotSet, |f| {
+          ActiveValue::Set(f.duration / 1_000_000)
+        }),
+      ..Default::default()
+    }
+    .update(dbconn)
+    .await
+    .and(Ok(()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use livekit_protocol::FileInfo;
+
+  use super::*;
+  use crate::test_utils::{create_room, create_user, setup_db};
+
+  const SEC: i64 = 1_000_000_000;
+
+  fn egress(id: &str, status: EgressStatus, file: Option<FileInfo>) -> EgressInfo {
+    EgressInfo {
+      egress_id: id.to_string(),
+      status: status as i32,
+      started_at: 1_700_000_000 * SEC,
+      ended_at: if status == EgressStatus::EgressComplete {
+        1_700_000_060 * SEC
+      } else {
+        0
+      },
+      file_results: file.into_iter().collect(),
+      ..Default::default()
+    }
+  }
+
+  #[actix_web::test]
+  async fn sync_egress_creates_then_updates() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    RecordingService::sync_egress(&db, 1, &egress("EG_1", EgressStatus::EgressActive, None))
+      .await
+      .unwrap();
+    let rec = RecordingService::get_recording_by_egress_id(&db, "EG_1")
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(rec.status, STATUS_ACTIVE);
+    assert_eq!(
+      rec.start_time,
+      nanos_to_datetime(1_700_000_000 * SEC).unwrap()
+    );
+    assert_eq!(rec.end_time, None);
+    assert_eq!(rec.file_path, "");
+
+    let file = FileInfo {
+      filename: "1/a.mp4".to_string(),
+      size: 1024,
+      duration: 60 * SEC,
+      ..Default::default()
+    };
+    RecordingService::sync_egress(
+      &db,
+      1,
+      &egress("EG_1", EgressStatus::EgressComplete, Some(file)),
+    )
+    .await
+    .unwrap();
+    let recs = RecordingService::get_recordings_by_room_id(&db, 1)
+      .await
+      .unwrap();
+    assert_eq!(recs.len(), 1);
+    assert_eq!(recs[0].status, STATUS_COMPLETE);
+    assert_eq!(recs[0].file_path, "1/a.mp4");
+    assert_eq!(recs[0].size, 1024);
+    assert_eq!(recs[0].duration, 60_000);
+    assert_eq!(recs[0].end_time, nanos_to_datetime(1_700_000_060 * SEC));
+
+    // 后到的空文件信息不会覆盖已有结果
+    RecordingService::sync_egress(&db, 1, &egress("EG_1", EgressStatus::EgressEnding, None))
+      .await
+      .unwrap();
+    let rec = RecordingService::get_recording_by_egress_id(&db, "EG_1")
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(rec.status, STATUS_ENDING);
+    assert_eq!(rec.file_path, "1/a.mp4");
+    assert_eq!(rec.size, 1024);
+  }
+
+  #[actix_web::test]
+  async fn start_recording_sets_started_by() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    // webhook 先于接口返回到达
+    RecordingService::sync_egress(&db, 1, &egress("EG_1", EgressStatus::EgressActive, None))
+      .await
+      .unwrap();
+    RecordingService::start_recording(
+      &db,
+      1,
+      &egress("EG_1", EgressStatus::EgressStarting, None),
+      "alice",
+    )
+    .await
+    .unwrap();
+    RecordingService::start_recording(
+      &db,
+      1,
+      &egress("EG_2", EgressStatus::EgressStarting, None),
+      "admin1",
+    )
+    .await
+    .unwrap();
+    let recs = RecordingService::get_recordings_by_room_id(&db, 1)
+      .await
+      .unwrap();
+    assert_eq!(
+      recs
+        .iter()
+        .map(|x| (x.egress_id.as_str(), x.started_by.as_deref()))
+        .collect::<Vec<_>>(),
+      [("EG_1", Some("alice")), ("EG_2", Some("admin1"))]
+    );
+    // 发起人不存在时返回错误，由接口停止录制
+    assert!(RecordingService::start_recording(
+      &db,
+      1,
+      &egress("EG_3", EgressStatus::EgressStarting, None),
+      "nobody",
+    )
+    .await
+    .is_err());
+  }
+}
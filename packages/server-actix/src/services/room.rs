@@ -1,41 +1,137 @@
-use std::fmt::format;
-
-use crate::entities::room;
-use log::debug;
+use chrono::{Duration, NaiveDateTime};
+use rand::random_range;
 use sea_orm::{
-  prelude::DateTime, sqlx::types::chrono, ActiveModelTrait, ActiveValue, ColumnTrait, Condition,
-  DatabaseConnection, DbErr, EntityTrait, InsertResult, QueryFilter,
+  sea_query::Expr, sqlx::types::chrono::Utc, ActiveModelTrait, ActiveValue, ColumnTrait, Condition,
+  ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, SqlErr, TransactionTrait,
 };
 
-use rand::random_range;
+use crate::entities::{
+  filter_preset, meeting_summary, moderation_log, recording, room, room_admission, room_invite,
+  room_user, transcript_segment,
+};
+
+/// 会议结束后保留会议号的时长，便于超时的会议继续使用
+pub const CODE_RELEASE_HOURS: i64 = 24;
+const MAX_CODE_ATTEMPTS: usize = 10;
+const CODE_LEN: usize = 9;
+
+/// 将会议号格式化为 `123 456 789`，便于口头传达
+pub fn format_code(code: &str) -> String {
+  code
+    .as_bytes()
+    .chunks(3)
+    .map(String::from_utf8_lossy)
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// 去掉用户输入中的空格与连字符，不是合法会议号时返回 `None`
+pub fn normalize_code(input: &str) -> Option<String> {
+  let code = input
+    .chars()
+    .filter(|c| !c.is_whitespace() && *c != '-')
+    .collect::<String>();
+  (code.len() == CODE_LEN && code.chars().all(|c| c.is_ascii_digit())).then_some(code)
+}
+
+fn random_code() -> String {
+  format!(
+    "{:0width$}",
+    random_range(0..1_000_000_000),
+    width = CODE_LEN
+  )
+}
+
+fn is_unique_violation(e: &DbErr) -> bool {
+  matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_)))
+}
+
 pub struct RoomService;
 
 impl RoomService {
-  pub async fn get_no_dup_code(dbconn: &DatabaseConnection) -> Result<String, DbErr> {
-    let mut res = 0;
-    let time = chrono::DateTime::from_timestamp(chrono::Utc::now().timestamp(), 0).unwrap();
-    loop {
-      res = random_range(0..1_000_000_000);
-      if room::Entity::find()
-        .filter(
-          Condition::all()
-            .add(room::Column::Code.eq(format!("{:09}", res)))
-            .add(room::Column::EndTime.lt(time)),
-        )
-        .all(dbconn)
-        .await?
-        .is_empty()
-      {
-        break;
+  /// 在事务中分配会议号并写入会议与成员。会议号由部分唯一索引保证在未释放的会议中唯一，
+  /// 冲突时换一个重新插入。系列会议的同一次已存在时返回 `DbErr::RecordNotInserted`
+  pub async fn create_room(
+    dbconn: &DatabaseConnection,
+    room: room::ActiveModel,
+    members: Vec<room_user::ActiveModel>,
+  ) -> Result<i32, DbErr> {
+    Self::release_codes(dbconn, Utc::now().naive_utc()).await?;
+    for _ in 0..MAX_CODE_ATTEMPTS {
+      let txn = dbconn.begin().await?;
+      let mut attempt = room.clone();
+      attempt.code = ActiveValue::Set(random_code());
+      attempt.code_released = ActiveValue::Set(false);
+      let room_id = match room::Entity::insert(attempt).exec(&txn).await {
+        Ok(res) => res.last_insert_id,
+        Err(e) if is_unique_violation(&e) => {
+          txn.rollback().await?;
+          if Self::occurrence_exists(dbconn, &room).await? {
+            return Err(DbErr::RecordNotInserted);
+          }
+          continue;
+        }
+        Err(e) => return Err(e),
+      };
+      if !members.is_empty() {
+        room_user::Entity::insert_many(members.into_iter().map(|mut x| {
+          x.room_id = ActiveValue::Set(room_id);
+          x
+        }))
+        .exec(&txn)
+        .await?;
       }
+      txn.commit().await?;
+      return Ok(room_id);
     }
-    Ok(format!("{:09}", res))
+    Err(DbErr::Custom("no room code available".to_string()))
   }
-  pub async fn create_room(
+  /// 系列会议的这一次是否已生成，软删除的也算
+  async fn occurrence_exists(
     dbconn: &DatabaseConnection,
-    room: room::ActiveModel,
-  ) -> Result<InsertResult<room::ActiveModel>, DbErr> {
-    room::Entity::insert(room).exec(dbconn).await
+    room: &room::ActiveModel,
+  ) -> Result<bool, DbErr> {
+    let (ActiveValue::Set(Some(series_id)), ActiveValue::Set(Some(start))) =
+      (&room.series_id, &room.occurrence_start)
+    else {
+      return Ok(false);
+    };
+    Ok(
+      room::Entity::find()
+        .filter(room::Column::SeriesId.eq(*series_id))
+        .filter(room::Column::OccurrenceStart.eq(*start))
+        .one(dbconn)
+        .await?
+        .is_some(),
+    )
+  }
+  /// 释放已取消或结束超过一段时间的会议的会议号
+  pub async fn release_codes<C: ConnectionTrait>(dbconn: &C, now: NaiveDateTime) -> Result<(), DbErr> {
+    room::Entity::update_many()
+      .col_expr(room::Column::CodeReleased, Expr::value(true))
+      .filter(room::Column::CodeReleased.eq(false))
+      .filter(
+        Condition::any()
+          .add(room::Column::IsCanceled.eq(true))
+          .add(room::Column::EndTime.lt(now - Duration::hours(CODE_RELEASE_HOURS))),
+      )
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  /// 按会议号查找尚未释放会议号的会议，会议号可带分隔符
+  pub async fn get_room_by_code(
+    dbconn: &DatabaseConnection,
+    code: &str,
+  ) -> Result<Option<room::Model>, DbErr> {
+    let Some(code) = normalize_code(code) else {
+      return Ok(None);
+    };
+    room::Entity::find()
+      .filter(room::Column::Code.eq(code))
+      .filter(room::Column::CodeReleased.eq(false))
+      .one(dbconn)
+      .await
   }
   pub async fn get_room_by_id(dbconn: &DatabaseConnection, id: i32) -> Result<room::Model, DbErr> {
     room::Entity::find_by_id(id)
@@ -43,10 +139,254 @@ impl RoomService {
       .await?
       .ok_or(DbErr::RecordNotFound(format!("room not found: {id}")))
   }
-  pub async fn update_room(dbconn: &DatabaseConnection, room: room::ActiveModel) -> Result<(), DbErr> {
-    room
-      .update(dbconn)
+  /// 恢复已取消的会议或将已结束的会议改到之后时，原会议号已被释放，需重新分配
+  pub async fn update_room<C: ConnectionTrait>(dbconn: &C, room: room::ActiveModel) -> Result<(), DbErr> {
+    let room = room.update(dbconn).await?;
+    let now = Utc::now().naive_utc();
+    if room.code_released
+      && !room.is_canceled
+      && room.deleted_at.is_none()
+      && room.end_time >= now - Duration::hours(CODE_RELEASE_HOURS)
+    {
+      Self::reallocate_code(dbconn, room.id, now).await?;
+    }
+    Ok(())
+  }
+  /// 与创建会议相同，冲突时换一个会议号重试
+  async fn reallocate_code<C: ConnectionTrait>(
+    dbconn: &C,
+    room_id: i32,
+    now: NaiveDateTime,
+  ) -> Result<(), DbErr> {
+    Self::release_codes(dbconn, now).await?;
+    for _ in 0..MAX_CODE_ATTEMPTS {
+      let res = room::Entity::update_many()
+        .col_expr(room::Column::Code, Expr::value(random_code()))
+        .col_expr(room::Column::CodeReleased, Expr::value(false))
+        .filter(room::Column::Id.eq(room_id))
+        .exec(dbconn)
+        .await;
+      match res {
+        Ok(_) => return Ok(()),
+        Err(e) if is_unique_violation(&e) => continue,
+        Err(e) => return Err(e),
+      }
+    }
+    Err(DbErr::Custom("no room code available".to_string()))
+  }
+  /// 会议未在录制时记录 egress，已有录制时保持不变
+  pub async fn start_egress(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    egress_id: &str,
+  ) -> Result<(), DbErr> {
+    room::Entity::update_many()
+      .col_expr(room::Column::CurEgressId, Expr::value(egress_id))
+      .filter(
+        Condition::all()
+          .add(room::Column::Id.eq(room_id))
+          .add(room::Column::CurEgressId.eq("")),
+      )
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  /// 仅当 `egress_id` 为当前录制时才清除，避免过期的通知覆盖新的录制
+  pub async fn end_egress(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    egress_id: &str,
+  ) -> Result<(), DbErr> {
+    room::Entity::update_many()
+      .col_expr(room::Column::CurEgressId, Expr::value(""))
+      .filter(
+        Condition::all()
+          .add(room::Column::Id.eq(room_id))
+          .add(room::Column::CurEgressId.eq(egress_id)),
+      )
+      .exec(dbconn)
       .await
       .and(Ok(()))
   }
+  pub async fn clear_egress(dbconn: &DatabaseConnection, room_id: i32) -> Result<(), DbErr> {
+    room::Entity::update_many()
+      .col_expr(room::Column::CurEgressId, Expr::value(""))
+      .filter(room::Column::Id.eq(room_id))
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  /// 软删除会议并释放会议号，成员、录制与转写均保留
+  pub async fn soft_delete<C: ConnectionTrait>(
+    db: &C,
+    room_id: i32,
+    now: NaiveDateTime,
+  ) -> Result<(), DbErr> {
+    room::Entity::update_many()
+      .col_expr(room::Column::DeletedAt, Expr::value(now))
+      .col_expr(room::Column::CodeReleased, Expr::value(true))
+      .filter(room::Column::Id.eq(room_id))
+      .exec(db)
+      .await
+      .and(Ok(()))
+  }
+  /// 在事务中删除会议及其成员、录制、转写、纪要、邀请、准入与审计记录。
+  /// 录制文件保存在对象存储中，由存储桶的生命周期规则清理
+  pub async fn hard_delete(dbconn: &DatabaseConnection, room_id: i32) -> Result<(), DbErr> {
+    let txn = dbconn.begin().await?;
+    // 会议专属的滤镜预设可能被置顶，先解除引用
+    let preset_ids = filter_preset::Entity::find()
+      .filter(filter_preset::Column::RoomId.eq(room_id))
+      .all(&txn)
+      .await?
+      .into_iter()
+      .map(|x| x.id)
+      .collect::<Vec<_>>();
+    room::Entity::update_many()
+      .col_expr(
+        room::Column::PinnedFilterId,
+        Expr::value(Option::<i32>::None),
+      )
+      .filter(
+        Condition::any()
+          .add(room::Column::Id.eq(room_id))
+          .add(room::Column::PinnedFilterId.is_in(preset_ids)),
+      )
+      .exec(&txn)
+      .await?;
+    filter_preset::Entity::delete_many()
+      .filter(filter_preset::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    room_user::Entity::delete_many()
+      .filter(room_user::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    recording::Entity::delete_many()
+      .filter(recording::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    transcript_segment::Entity::delete_many()
+      .filter(transcript_segment::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    meeting_summary::Entity::delete_many()
+      .filter(meeting_summary::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    room_invite::Entity::delete_many()
+      .filter(room_invite::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    room_admission::Entity::delete_many()
+      .filter(room_admission::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    moderation_log::Entity::delete_many()
+      .filter(moderation_log::Column::RoomId.eq(room_id))
+      .exec(&txn)
+      .await?;
+    room::Entity::delete_by_id(room_id).exec(&txn).await?;
+    txn.commit().await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::{create_room, setup_db};
+
+  #[actix_web::test]
+  async fn allocates_codes_among_active_rooms() {
+    assert_eq!(format_code("012345678"), "012 345 678");
+    assert_eq!(
+      normalize_code(" 012-345 678"),
+      Some("012345678".to_string())
+    );
+    assert_eq!(normalize_code("01234567"), None);
+
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    let mut dup: room::ActiveModel = room.clone().into();
+    dup.id = ActiveValue::NotSet;
+    assert!(room::Entity::insert(dup.clone()).exec(&db).await.is_err());
+
+    // 分配时释放结束已久的会议的会议号，之后可再次使用
+    let id = RoomService::create_room(&db, dup.clone(), vec![])
+      .await
+      .unwrap();
+    assert!(
+      RoomService::get_room_by_id(&db, 1)
+        .await
+        .unwrap()
+        .code_released
+    );
+    let reused = room::Entity::insert(dup)
+      .exec(&db)
+      .await
+      .unwrap()
+      .last_insert_id;
+    let found = RoomService::get_room_by_code(&db, &format_code(&room.code))
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(found.id, reused);
+    assert_ne!(
+      RoomService::get_room_by_id(&db, id).await.unwrap().code,
+      room.code
+    );
+  }
+
+  #[actix_web::test]
+  async fn reallocates_code_when_room_is_revived() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    let revive = |x: room::ActiveModel| RoomService::update_room(&db, x);
+    revive(room::ActiveModel {
+      id: ActiveValue::Set(1),
+      is_canceled: ActiveValue::Set(true),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+    RoomService::release_codes(&db, Utc::now().naive_utc())
+      .await
+      .unwrap();
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert!(room.code_released);
+
+    // 恢复取消但仍已结束，不重新分配
+    revive(room::ActiveModel {
+      id: ActiveValue::Set(1),
+      is_canceled: ActiveValue::Set(false),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+    assert!(
+      RoomService::get_room_by_id(&db, 1)
+        .await
+        .unwrap()
+        .code_released
+    );
+
+    // 改到之后的时间，重新分配并可按会议号找到
+    let start = Utc::now().naive_utc() + Duration::hours(1);
+    revive(room::ActiveModel {
+      id: ActiveValue::Set(1),
+      start_time: ActiveValue::Set(start),
+      end_time: ActiveValue::Set(start + Duration::hours(1)),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+    let room = RoomService::get_room_by_id(&db, 1).await.unwrap();
+    assert!(!room.code_released);
+    let found = RoomService::get_room_by_code(&db, &room.code)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(found.id, 1);
+  }
 }
@@ -0,0 +1,162 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::entities::room_admission;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use sea_orm::{
+  sqlx::types::chrono::Utc, ActiveModelTrait, ActiveValue, ColumnTrait, Condition,
+  DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use ts_rs::TS;
+
+pub struct RoomAdmissionService;
+
+/// 访客等候准入凭证的有效期
+pub const GUEST_TICKET_TTL_SECS: u64 = 12 * 60 * 60;
+
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub enum AdmissionStatus {
+  Pending,
+  Admitted,
+  Denied,
+}
+
+impl AdmissionStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AdmissionStatus::Pending => "pending",
+      AdmissionStatus::Admitted => "admitted",
+      AdmissionStatus::Denied => "denied",
+    }
+  }
+  pub fn parse(status: &str) -> Self {
+    match status {
+      "admitted" => AdmissionStatus::Admitted,
+      "denied" => AdmissionStatus::Denied,
+      _ => AdmissionStatus::Pending,
+    }
+  }
+}
+
+/// 访客在等候室中轮询使用的凭证，与邀请链接、access token 的字段均不同
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct GuestClaims {
+  pub admission: i32,
+  pub room: i32,
+  pub identity: String,
+  pub name: String,
+  pub exp: usize,
+}
+
+impl RoomAdmissionService {
+  pub async fn get_admission(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    identity: &str,
+  ) -> Result<Option<room_admission::Model>, DbErr> {
+    room_admission::Entity::find()
+      .filter(
+        Condition::all()
+          .add(room_admission::Column::RoomId.eq(room_id))
+          .add(room_admission::Column::Identity.eq(identity)),
+      )
+      .one(dbconn)
+      .await
+  }
+  pub async fn get_admissions_by_room_id(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+  ) -> Result<Vec<room_admission::Model>, DbErr> {
+    room_admission::Entity::find()
+      .filter(room_admission::Column::RoomId.eq(room_id))
+      .order_by_asc(room_admission::Column::CreatedAt)
+      .order_by_asc(room_admission::Column::Id)
+      .all(dbconn)
+      .await
+  }
+  /// 申请进入会议，已有记录时沿用原有状态
+  pub async fn request(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    identity: &str,
+    user_id: Option<String>,
+    display_name: &str,
+  ) -> Result<room_admission::Model, DbErr> {
+    if let Some(admission) = Self::get_admission(dbconn, room_id, identity).await? {
+      return Ok(admission);
+    }
+    let now = Utc::now().naive_utc();
+    let res = room_admission::ActiveModel {
+      room_id: ActiveValue::Set(room_id),
+      identity: ActiveValue::Set(identity.to_string()),
+      user_id: ActiveValue::Set(user_id),
+      display_name: ActiveValue::Set(display_name.to_string()),
+      status: ActiveValue::Set(AdmissionStatus::Pending.as_str().to_string()),
+      decided_by: ActiveValue::Set(None),
+      created_at: ActiveValue::Set(now),
+      updated_at: ActiveValue::Set(now),
+      ..Default::default()
+    }
+    .insert(dbconn)
+    .await;
+    match res {
+      Ok(admission) => Ok(admission),
+      // 并发申请时唯一索引冲突，读取已写入的记录
+      Err(e) => Self::get_admission(dbconn, room_id, identity)
+        .await?
+        .ok_or(e),
+    }
+  }
+  /// 主持人准入或拒绝，记录不属于该会议时返回 None
+  pub async fn decide(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    admission_id: i32,
+    status: AdmissionStatus,
+    decided_by: &str,
+  ) -> Result<Option<room_admission::Model>, DbErr> {
+    let Some(admission) = room_admission::Entity::find_by_id(admission_id)
+      .one(dbconn)
+      .await?
+      .filter(|x| x.room_id == room_id)
+    else {
+      return Ok(None);
+    };
+    let mut admission: room_admission::ActiveModel = admission.into();
+    admission.status = ActiveValue::Set(status.as_str().to_string());
+    admission.decided_by = ActiveValue::Set(Some(decided_by.to_string()));
+    admission.updated_at = ActiveValue::Set(Utc::now().naive_utc());
+    admission.update(dbconn).await.map(Some)
+  }
+  pub fn sign_ticket(
+    jwt_secret: &str,
+    admission: &room_admission::Model,
+  ) -> jsonwebtoken::errors::Result<String> {
+    let exp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .unwrap_or_default()
+      .saturating_add(Duration::from_secs(GUEST_TICKET_TTL_SECS))
+      .as_secs();
+    encode(
+      &Header::default(),
+      &GuestClaims {
+        admission: admission.id,
+        room: admission.room_id,
+        identity: admission.identity.clone(),
+        name: admission.display_name.clone(),
+        exp: exp as usize,
+      },
+      &EncodingKey::from_secret(jwt_secret.as_ref()),
+    )
+  }
+  pub fn verify_ticket(jwt_secret: &str, ticket: &str) -> Option<GuestClaims> {
+    decode::<GuestClaims>(
+      ticket,
+      &DecodingKey::from_secret(jwt_secret.as_ref()),
+      &Validation::default(),
+    )
+    .ok()
+    .map(|x| x.claims)
+  }
+}
@@ -0,0 +1,122 @@
+use crate::entities::room_invite;
+use crate::services::room_user::RoomRole;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use livekit_api::access_token::VideoGrants;
+use sea_orm::{
+  sea_query::Expr, sqlx::types::chrono::Utc, ActiveModelTrait, ColumnTrait, Condition,
+  DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+
+pub struct RoomInviteService;
+
+/// 邀请链接中携带的签名信息，与 access token 的字段不同，二者无法互用
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct InviteClaims {
+  pub invite: i32,
+  pub room: i32,
+  pub exp: usize,
+}
+
+impl RoomInviteService {
+  pub async fn create_invite(
+    dbconn: &DatabaseConnection,
+    invite: room_invite::ActiveModel,
+  ) -> Result<room_invite::Model, DbErr> {
+    invite.insert(dbconn).await
+  }
+  pub async fn get_invites_by_room_id(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+  ) -> Result<Vec<room_invite::Model>, DbErr> {
+    room_invite::Entity::find()
+      .filter(room_invite::Column::RoomId.eq(room_id))
+      .order_by_desc(room_invite::Column::CreatedAt)
+      .order_by_desc(room_invite::Column::Id)
+      .all(dbconn)
+      .await
+  }
+  /// 吊销会议下的邀请，邀请不存在时返回 false
+  pub async fn revoke_invite(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    invite_id: i32,
+  ) -> Result<bool, DbErr> {
+    let res = room_invite::Entity::update_many()
+      .col_expr(room_invite::Column::Revoked, Expr::value(true))
+      .filter(
+        Condition::all()
+          .add(room_invite::Column::Id.eq(invite_id))
+          .add(room_invite::Column::RoomId.eq(room_id)),
+      )
+      .exec(dbconn)
+      .await?;
+    Ok(res.rows_affected > 0)
+  }
+  /// 占用一次邀请，已吊销、已过期或次数用尽时返回 None
+  pub async fn consume_invite(
+    dbconn: &DatabaseConnection,
+    claims: &InviteClaims,
+  ) -> Result<Option<room_invite::Model>, DbErr> {
+    // 条件更新保证并发使用时不会超出次数
+    let res = room_invite::Entity::update_many()
+      .col_expr(
+        room_invite::Column::UseCount,
+        Expr::col(room_invite::Column::UseCount).add(1),
+      )
+      .filter(
+        Condition::all()
+          .add(room_invite::Column::Id.eq(claims.invite))
+          .add(room_invite::Column::RoomId.eq(claims.room))
+          .add(room_invite::Column::Revoked.eq(false))
+          .add(room_invite::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+          .add(
+            Condition::any()
+              .add(room_invite::Column::MaxUses.is_null())
+              .add(
+                Expr::col(room_invite::Column::UseCount)
+                  .lt(Expr::col(room_invite::Column::MaxUses)),
+              ),
+          ),
+      )
+      .exec(dbconn)
+      .await?;
+    if res.rows_affected == 0 {
+      return Ok(None);
+    }
+    room_invite::Entity::find_by_id(claims.invite)
+      .one(dbconn)
+      .await
+  }
+  /// 签发邀请链接使用的 token，有效期与邀请一致
+  pub fn sign(
+    jwt_secret: &str,
+    invite: &room_invite::Model,
+  ) -> jsonwebtoken::errors::Result<String> {
+    encode(
+      &Header::default(),
+      &InviteClaims {
+        invite: invite.id,
+        room: invite.room_id,
+        exp: invite.expires_at.and_utc().timestamp() as usize,
+      },
+      &EncodingKey::from_secret(jwt_secret.as_ref()),
+    )
+  }
+  /// 校验签名与有效期，吊销与次数由 `consume_invite` 检查
+  pub fn verify(jwt_secret: &str, token: &str) -> Option<InviteClaims> {
+    decode::<InviteClaims>(
+      token,
+      &DecodingKey::from_secret(jwt_secret.as_ref()),
+      &Validation::default(),
+    )
+    .ok()
+    .map(|x| x.claims)
+  }
+  /// 访客按普通与会者授权，且不能修改自己的资料
+  pub fn guest_grants(room: String) -> VideoGrants {
+    VideoGrants {
+      can_update_own_metadata: false,
+      ..RoomRole::Attendee.video_grants(room)
+    }
+  }
+}
@@ -1,15 +1,95 @@
-use crate::entities::room_user;
-use log::debug;
+use crate::entities::{room, room_user};
+use livekit_api::access_token::VideoGrants;
 use sea_orm::{
-  ActiveValue, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
-  QueryFilter,
+  sea_query::Expr, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+  EntityTrait, QueryFilter, TransactionTrait,
 };
+use ts_rs::TS;
 
 pub struct RoomUserService;
 
+/// 与会者在会议中的角色，会议的 admin 始终为主持人
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../../app-tauri/src/types/room.ts")]
+pub enum RoomRole {
+  Host,
+  CoHost,
+  Presenter,
+  Attendee,
+  Viewer,
+}
+
+/// 会议内需要鉴权的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoomPermission {
+  /// 进入会议、查看会议资料
+  Join,
+  /// 开始、停止录制以及生成会议纪要
+  Record,
+  /// 修改会议时间、取消会议、置顶滤镜
+  EditSchedule,
+  /// 增删与会人员、调整角色
+  ManageParticipants,
+  /// 发布音视频
+  PublishMedia,
+}
+
+impl RoomRole {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      RoomRole::Host => "host",
+      RoomRole::CoHost => "co_host",
+      RoomRole::Presenter => "presenter",
+      RoomRole::Attendee => "attendee",
+      RoomRole::Viewer => "viewer",
+    }
+  }
+  /// 无法识别的角色按普通与会者处理
+  pub fn parse(role: &str) -> Self {
+    match role {
+      "host" => RoomRole::Host,
+      "co_host" => RoomRole::CoHost,
+      "presenter" => RoomRole::Presenter,
+      "viewer" => RoomRole::Viewer,
+      _ => RoomRole::Attendee,
+    }
+  }
+  pub fn allows(&self, permission: RoomPermission) -> bool {
+    match permission {
+      RoomPermission::Join => true,
+      RoomPermission::Record
+      | RoomPermission::EditSchedule
+      | RoomPermission::ManageParticipants => {
+        matches!(self, RoomRole::Host | RoomRole::CoHost)
+      }
+      RoomPermission::PublishMedia => *self != RoomRole::Viewer,
+    }
+  }
+  /// 根据角色生成 LiveKit 权限，普通与会者不能共享屏幕
+  pub fn video_grants(&self, room: String) -> VideoGrants {
+    let can_publish = self.allows(RoomPermission::PublishMedia);
+    let can_publish_sources = match self {
+      RoomRole::Attendee => vec!["camera".to_string(), "microphone".to_string()],
+      _ => vec![],
+    };
+    VideoGrants {
+      room_join: true,
+      room,
+      room_admin: self.allows(RoomPermission::ManageParticipants),
+      room_record: self.allows(RoomPermission::Record),
+      can_publish,
+      can_publish_sources,
+      can_subscribe: true,
+      can_publish_data: true,
+      ..Default::default()
+    }
+  }
+}
+
 impl RoomUserService {
-  pub async fn create_room_user(
-    dbconn: &DatabaseConnection,
+  pub async fn create_room_user<C: ConnectionTrait>(
+    dbconn: &C,
     room_users: Vec<room_user::ActiveModel>,
   ) -> Result<(), DbErr> {
     if room_users.is_empty() {
@@ -20,15 +100,95 @@ impl RoomUserService {
       .await
       .and(Ok(()))
   }
-  pub async fn get_users_by_room_id(
-    dbconn: &DatabaseConnection,
+  pub async fn get_users_by_room_id<C: ConnectionTrait>(
+    db: &C,
     room_id: i32,
   ) -> Result<Vec<room_user::Model>, DbErr> {
     room_user::Entity::find()
       .filter(Condition::all().add(room_user::Column::RoomId.eq(room_id)))
-      .all(dbconn)
+      .all(db)
       .await
   }
+  pub async fn is_room_member<C: ConnectionTrait>(
+    dbconn: &C,
+    room_id: i32,
+    user_id: &str,
+  ) -> Result<bool, DbErr> {
+    room_user::Entity::find()
+      .filter(
+        Condition::all()
+          .add(room_user::Column::RoomId.eq(room_id))
+          .add(room_user::Column::UserId.eq(user_id)),
+      )
+      .one(dbconn)
+      .await
+      .map(|x| x.is_some())
+  }
+  /// 用户在会议中的角色，非会议成员返回 None
+  pub async fn get_role(
+    dbconn: &DatabaseConnection,
+    room: &room::Model,
+    user_id: &str,
+  ) -> Result<Option<RoomRole>, DbErr> {
+    if room.admin == user_id {
+      return Ok(Some(RoomRole::Host));
+    }
+    room_user::Entity::find()
+      .filter(
+        Condition::all()
+          .add(room_user::Column::RoomId.eq(room.id))
+          .add(room_user::Column::UserId.eq(user_id)),
+      )
+      .one(dbconn)
+      .await
+      .map(|x| x.map(|x| RoomRole::parse(&x.role)))
+  }
+  /// 返回是否找到对应的与会者
+  pub async fn set_role(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    user_id: &str,
+    role: RoomRole,
+  ) -> Result<bool, DbErr> {
+    room_user::Entity::update_many()
+      .col_expr(room_user::Column::Role, Expr::value(role.as_str()))
+      .filter(
+        Condition::all()
+          .add(room_user::Column::RoomId.eq(room_id))
+          .add(room_user::Column::UserId.eq(user_id)),
+      )
+      .exec(dbconn)
+      .await
+      .map(|x| x.rows_affected > 0)
+  }
+  /// 移交主持人，原主持人降为联席主持人
+  pub async fn transfer_host<C: ConnectionTrait + TransactionTrait>(
+    db: &C,
+    room: &room::Model,
+    new_host: &str,
+  ) -> Result<(), DbErr> {
+    let txn = db.begin().await?;
+    room::Entity::update_many()
+      .col_expr(room::Column::Admin, Expr::value(new_host))
+      .filter(room::Column::Id.eq(room.id))
+      .exec(&txn)
+      .await?;
+    for (user_id, role) in [
+      (room.admin.as_str(), RoomRole::CoHost),
+      (new_host, RoomRole::Host),
+    ] {
+      room_user::Entity::update_many()
+        .col_expr(room_user::Column::Role, Expr::value(role.as_str()))
+        .filter(
+          Condition::all()
+            .add(room_user::Column::RoomId.eq(room.id))
+            .add(room_user::Column::UserId.eq(user_id)),
+        )
+        .exec(&txn)
+        .await?;
+    }
+    txn.commit().await
+  }
   pub async fn get_rooms_by_user_id(
     dbconn: &DatabaseConnection,
     user_id: String,
@@ -39,7 +199,7 @@ impl RoomUserService {
       .await
   }
   
-  pub async fn delete_room_user(dbconn: &DatabaseConnection, ids: Vec<i32>) -> Result<(), DbErr> {
+  pub async fn delete_room_user<C: ConnectionTrait>(dbconn: &C, ids: Vec<i32>) -> Result<(), DbErr> {
     room_user::Entity::delete_many()
       .filter(room_user::Column::Id.is_in(ids))
       .exec(dbconn)
@@ -47,7 +207,7 @@ impl RoomUserService {
       .and(Ok(()))
   }
 
-  pub async fn update_room_user(dbconn: &DatabaseConnection, room_id: i32, user_ids: &Vec<String>) -> Result<(), DbErr> {
+  pub async fn update_room_user<C: ConnectionTrait>(dbconn: &C, room_id: i32, user_ids: &[String]) -> Result<(), DbErr> {
     let room_users = Self::get_users_by_room_id(dbconn, room_id).await?;
 
     let users_to_delete = room_users
@@ -58,11 +218,12 @@ impl RoomUserService {
 
     let room_users: Vec<String> = room_users.into_iter().map(|model| model.user_id).collect();
     let users_to_add: Vec<room_user::ActiveModel> = user_ids
-      .into_iter()
+      .iter()
       .filter(|id| !room_users.iter().any(|user_id| user_id == *id))
       .map(|id| room_user::ActiveModel {
         room_id: ActiveValue::set(room_id),
         user_id: ActiveValue::set(id.clone()),
+        role: ActiveValue::set(RoomRole::Attendee.as_str().to_string()),
         ..Default::default()
       }).collect();
 
@@ -0,0 +1,143 @@
+use chrono::{Duration, NaiveDateTime};
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+
+use crate::entities::{room, room_user};
+
+/// 允许的开始时间误差，避免客户端时钟略慢导致刚创建的会议被判为已过去
+pub const PAST_TOLERANCE_SECS: i64 = 5 * 60;
+/// 单次会议最长时长
+pub const MAX_DURATION_SECS: i64 = 24 * 3600;
+/// 空闲查询的最大时间范围
+pub const MAX_AVAILABILITY_DAYS: i64 = 31;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+  EndBeforeStart,
+  StartInPast,
+  TooLong,
+}
+
+/// 校验会议时间，`check_past` 为 false 时不检查开始时间是否已过去，用于未修改开始时间的更新
+pub fn validate_schedule(
+  start: NaiveDateTime,
+  end: NaiveDateTime,
+  now: NaiveDateTime,
+  check_past: bool,
+) -> Result<(), ScheduleError> {
+  if end <= start {
+    return Err(ScheduleError::EndBeforeStart);
+  }
+  if check_past && start < now - Duration::seconds(PAST_TOLERANCE_SECS) {
+    return Err(ScheduleError::StartInPast);
+  }
+  if end - start > Duration::seconds(MAX_DURATION_SECS) {
+    return Err(ScheduleError::TooLong);
+  }
+  Ok(())
+}
+
+/// 合并占用时段后取 `[from, to)` 内的空闲时段
+pub fn free_slots(
+  busy: &[(NaiveDateTime, NaiveDateTime)],
+  from: NaiveDateTime,
+  to: NaiveDateTime,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+  let mut busy = busy.to_vec();
+  busy.sort();
+  let mut res = vec![];
+  let mut cursor = from;
+  for (start, end) in busy {
+    if start > cursor {
+      res.push((cursor, start.min(to)));
+    }
+    cursor = cursor.max(end);
+    if cursor >= to {
+      return res;
+    }
+  }
+  if cursor < to {
+    res.push((cursor, to));
+  }
+  res
+}
+
+pub struct ScheduleService;
+
+impl ScheduleService {
+  /// 查询用户在 `[start, end)` 内参加的未取消会议，结果按会议开始时间排序
+  pub async fn find_busy(
+    dbconn: &DatabaseConnection,
+    user_ids: &[String],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_room: Option<i32>,
+  ) -> Result<Vec<(room_user::Model, room::Model)>, DbErr> {
+    let mut query = room_user::Entity::find()
+      .find_also_related(room::Entity)
+      .filter(room_user::Column::UserId.is_in(user_ids.iter().cloned()))
+      .filter(room::Column::IsCanceled.eq(false))
+      .filter(room::Column::DeletedAt.is_null())
+      .filter(room::Column::StartTime.lt(end))
+      .filter(room::Column::EndTime.gt(start));
+    if let Some(room_id) = exclude_room {
+      query = query.filter(room::Column::Id.ne(room_id));
+    }
+    Ok(
+      query
+        .order_by_asc(room::Column::StartTime)
+        .all(dbconn)
+        .await?
+        .into_iter()
+        .filter_map(|(member, room)| room.map(|room| (member, room)))
+        .collect(),
+    )
+  }
+
+  /// 与给定时段冲突的会议及其中冲突的用户
+  pub async fn find_conflicts(
+    dbconn: &DatabaseConnection,
+    user_ids: &[String],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    exclude_room: Option<i32>,
+  ) -> Result<Vec<(room::Model, Vec<String>)>, DbErr> {
+    let mut res: Vec<(room::Model, Vec<String>)> = vec![];
+    for (member, room) in Self::find_busy(dbconn, user_ids, start, end, exclude_room).await? {
+      match res.iter_mut().find(|(x, _)| x.id == room.id) {
+        Some((_, users)) => users.push(member.user_id),
+        None => res.push((room, vec![member.user_id])),
+      }
+    }
+    Ok(res)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn validates_and_merges_slots() {
+    let t = |h: i64| NaiveDateTime::default() + Duration::hours(h);
+    assert_eq!(
+      validate_schedule(t(2), t(2), t(0), true),
+      Err(ScheduleError::EndBeforeStart)
+    );
+    assert_eq!(
+      validate_schedule(t(1), t(2), t(3), true),
+      Err(ScheduleError::StartInPast)
+    );
+    assert_eq!(validate_schedule(t(1), t(2), t(3), false), Ok(()));
+    assert_eq!(
+      validate_schedule(t(1), t(26), t(0), true),
+      Err(ScheduleError::TooLong)
+    );
+
+    let busy = [(t(3), t(5)), (t(1), t(2)), (t(4), t(6)), (t(9), t(12))];
+    assert_eq!(
+      free_slots(&busy, t(0), t(10)),
+      vec![(t(0), t(1)), (t(2), t(3)), (t(6), t(9))]
+    );
+    assert_eq!(free_slots(&[], t(0), t(1)), vec![(t(0), t(1))]);
+  }
+}
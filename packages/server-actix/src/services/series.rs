@@ -0,0 +1,581 @@
+use std::collections::HashSet;
+use std::time::Duration as StdDuration;
+
+use crate::entities::{meeting_series, room, room_user};
+use crate::services::room::RoomService;
+use crate::services::room_user::{RoomRole, RoomUserService};
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, Utc, Weekday};
+use log::debug;
+use sea_orm::{
+  sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr,
+  EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+
+pub struct SeriesService;
+
+/// 提前生成多少天内的会议，更晚的在查询时按需生成
+pub const MATERIALIZE_DAYS: i64 = 90;
+/// 后台生成任务的执行间隔，远小于 `MATERIALIZE_DAYS`
+const MATERIALIZE_INTERVAL: StdDuration = StdDuration::from_secs(3600);
+/// 单次最多返回的次数，避免无结束条件的规则无限循环
+const MAX_EXPANSION: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+  Daily,
+  Weekly,
+  Monthly,
+}
+
+/// RFC 5545 RRULE 的子集：FREQ=DAILY/WEEKLY/MONTHLY，INTERVAL，
+/// 仅 WEEKLY 支持不带序号的 BYDAY，COUNT 与 UNTIL 二选一。
+/// 时间均按 UTC 计算
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recurrence {
+  pub freq: Frequency,
+  pub interval: u32,
+  pub by_day: Vec<Weekday>,
+  pub count: Option<u32>,
+  pub until: Option<NaiveDateTime>,
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+  match day {
+    "MO" => Some(Weekday::Mon),
+    "TU" => Some(Weekday::Tue),
+    "WE" => Some(Weekday::Wed),
+    "TH" => Some(Weekday::Thu),
+    "FR" => Some(Weekday::Fri),
+    "SA" => Some(Weekday::Sat),
+    "SU" => Some(Weekday::Sun),
+    _ => None,
+  }
+}
+
+/// UNTIL 为日期时包含当天
+fn parse_until(value: &str) -> Option<NaiveDateTime> {
+  if let Ok(x) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+    return Some(x);
+  }
+  NaiveDate::parse_from_str(value, "%Y%m%d")
+    .ok()
+    .and_then(|x| x.and_hms_opt(23, 59, 59))
+}
+
+impl Recurrence {
+  /// 无法识别或超出支持范围的规则返回 None
+  pub fn parse(rule: &str) -> Option<Self> {
+    let rule = rule.trim();
+    let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+    let mut freq = None;
+    let mut res = Recurrence {
+      freq: Frequency::Daily,
+      interval: 1,
+      by_day: vec![],
+      count: None,
+      until: None,
+    };
+    for part in rule.split(';').filter(|x| !x.is_empty()) {
+      let (key, value) = part.split_once('=')?;
+      match key {
+        "FREQ" => {
+          freq = Some(match value {
+            "DAILY" => Frequency::Daily,
+            "WEEKLY" => Frequency::Weekly,
+            "MONTHLY" => Frequency::Monthly,
+            _ => return None,
+          })
+        }
+        "INTERVAL" => res.interval = value.parse().ok().filter(|x| (1..=365).contains(x))?,
+        "BYDAY" => {
+          res.by_day = value
+            .split(',')
+            .map(parse_weekday)
+            .collect::<Option<Vec<_>>>()?;
+        }
+        "COUNT" => res.count = Some(value.parse().ok().filter(|x| *x > 0)?),
+        "UNTIL" => res.until = Some(parse_until(value)?),
+        _ => return None,
+      }
+    }
+    res.freq = freq?;
+    if (res.count.is_some() && res.until.is_some())
+      || (!res.by_day.is_empty() && res.freq != Frequency::Weekly)
+    {
+      return None;
+    }
+    res.by_day.sort_by_key(|x| x.num_days_from_monday());
+    res.by_day.dedup();
+    Some(res)
+  }
+
+  /// 按时间顺序展开 `[after, before)` 内的各次开始时间。COUNT 从 DTSTART 起计数，
+  /// 早于 `after` 的也计入，`MAX_EXPANSION` 只限制返回的次数。
+  /// 与 RFC 不同，不满足 BYDAY 的 DTSTART 不算作一次会议
+  pub fn occurrences(
+    &self,
+    dtstart: NaiveDateTime,
+    after: NaiveDateTime,
+    before: NaiveDateTime,
+  ) -> Vec<NaiveDateTime> {
+    let interval = self.interval as i64;
+    let mut res = vec![];
+    let mut total = 0;
+    let mut push = |x: NaiveDateTime| -> bool {
+      if x < dtstart {
+        return true;
+      }
+      if x >= before
+        || self.until.is_some_and(|until| x > until)
+        || self.count.is_some_and(|count| total >= count)
+        || res.len() >= MAX_EXPANSION
+      {
+        return false;
+      }
+      total += 1;
+      if x >= after {
+        res.push(x);
+      }
+      true
+    };
+    match self.freq {
+      Frequency::Daily => {
+        for i in 0.. {
+          if !push(dtstart + Duration::days(i * interval)) {
+            break;
+          }
+        }
+      }
+      Frequency::Weekly => {
+        let days = if self.by_day.is_empty() {
+          vec![dtstart.weekday()]
+        } else {
+          self.by_day.clone()
+        };
+        let monday =
+          dtstart.date() - Duration::days(dtstart.weekday().num_days_from_monday() as i64);
+        'weeks: for i in 0.. {
+          let week = monday + Duration::weeks(i * interval);
+          for day in &days {
+            let date = week + Duration::days(day.num_days_from_monday() as i64);
+            if !push(date.and_time(dtstart.time())) {
+              break 'weeks;
+            }
+          }
+        }
+      }
+      // 当月没有对应日期（如 31 日）时跳过
+      Frequency::Monthly => {
+        let first = dtstart.date().with_day(1).unwrap_or(dtstart.date());
+        for i in 0u32.. {
+          let Some(month) = i
+            .checked_mul(self.interval)
+            .and_then(|x| first.checked_add_months(Months::new(x)))
+          else {
+            break;
+          };
+          let Some(date) = month.with_day(dtstart.day()) else {
+            continue;
+          };
+          if !push(date.and_time(dtstart.time())) {
+            break;
+          }
+        }
+      }
+    }
+    res
+  }
+}
+
+impl SeriesService {
+  pub async fn create_series(
+    dbconn: &DatabaseConnection,
+    series: meeting_series::ActiveModel,
+  ) -> Result<meeting_series::Model, DbErr> {
+    series.insert(dbconn).await
+  }
+  pub async fn get_series(
+    dbconn: &DatabaseConnection,
+    id: i32,
+  ) -> Result<Option<meeting_series::Model>, DbErr> {
+    meeting_series::Entity::find_by_id(id).one(dbconn).await
+  }
+  pub async fn update_series(
+    dbconn: &DatabaseConnection,
+    series: meeting_series::ActiveModel,
+  ) -> Result<meeting_series::Model, DbErr> {
+    series.update(dbconn).await
+  }
+  pub async fn get_occurrences(
+    dbconn: &DatabaseConnection,
+    series_id: i32,
+  ) -> Result<Vec<room::Model>, DbErr> {
+    room::Entity::find()
+      .filter(room::Column::SeriesId.eq(series_id))
+      .filter(room::Column::DeletedAt.is_null())
+      .order_by_asc(room::Column::StartTime)
+      .order_by_asc(room::Column::Id)
+      .all(dbconn)
+      .await
+  }
+  /// 已生成的各次会议在规则中的原始时间，软删除的也包含在内
+  async fn get_occurrence_starts(
+    dbconn: &DatabaseConnection,
+    series_id: i32,
+  ) -> Result<HashSet<NaiveDateTime>, DbErr> {
+    Ok(
+      room::Entity::find()
+        .select_only()
+        .column(room::Column::OccurrenceStart)
+        .filter(room::Column::SeriesId.eq(series_id))
+        .filter(room::Column::OccurrenceStart.is_not_null())
+        .into_tuple::<Option<NaiveDateTime>>()
+        .all(dbconn)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect(),
+    )
+  }
+  /// 尚未开始的会议，单独取消的也包含在内
+  pub async fn get_upcoming_occurrences(
+    dbconn: &DatabaseConnection,
+    series_id: i32,
+  ) -> Result<Vec<room::Model>, DbErr> {
+    room::Entity::find()
+      .filter(room::Column::SeriesId.eq(series_id))
+      .filter(room::Column::DeletedAt.is_null())
+      .filter(room::Column::OccurrenceStart.is_not_null())
+      .filter(room::Column::StartTime.gt(Utc::now().naive_utc()))
+      .order_by_asc(room::Column::OccurrenceStart)
+      .all(dbconn)
+      .await
+  }
+  /// 为规则中尚未生成、且未结束的会议创建 room，与会人员与最近一次会议一致。
+  /// 系列已取消时不再生成。同一次会议由唯一索引保证只生成一次，并发生成时跳过已存在的
+  pub async fn materialize(
+    dbconn: &DatabaseConnection,
+    series: &meeting_series::Model,
+  ) -> Result<(), DbErr> {
+    if series.is_canceled {
+      return Ok(());
+    }
+    let Some(rule) = Recurrence::parse(&series.rrule) else {
+      return Ok(());
+    };
+    let now = Utc::now().naive_utc();
+    let duration = Duration::seconds(series.duration as i64);
+    let occurrences = Self::get_occurrences(dbconn, series.id).await?;
+    let existing = Self::get_occurrence_starts(dbconn, series.id).await?;
+    let members = match occurrences
+      .iter()
+      .rev()
+      .find(|x| x.occurrence_start.is_some())
+    {
+      Some(x) => RoomUserService::get_users_by_room_id(dbconn, x.id)
+        .await?
+        .into_iter()
+        .map(|u| (u.user_id, u.role))
+        .collect(),
+      None => vec![(series.admin.clone(), RoomRole::Host.as_str().to_string())],
+    };
+    // 只需展开尚未结束的会议，早已结束的不必生成
+    let after = now - duration;
+    let mut starts = rule.occurrences(
+      series.start_time,
+      after,
+      now + Duration::days(MATERIALIZE_DAYS),
+    );
+    // 下一次会议较远时也先生成一次，保证系列始终有会议记录与会人员
+    if !starts.iter().any(|x| *x + duration > now) {
+      starts.extend(
+        rule
+          .occurrences(series.start_time, after, NaiveDateTime::MAX)
+          .into_iter()
+          .find(|x| *x + duration > now),
+      );
+    }
+    for start in starts {
+      if existing.contains(&start) || start + duration <= now {
+        continue;
+      }
+      let res = RoomService::create_room(
+        dbconn,
+        room::ActiveModel {
+          start_time: ActiveValue::Set(start),
+          end_time: ActiveValue::Set(start + duration),
+          admin: ActiveValue::Set(series.admin.clone()),
+          is_canceled: ActiveValue::Set(false),
+          cur_egress_id: ActiveValue::Set(String::new()),
+          lobby_enabled: ActiveValue::Set(series.lobby_enabled),
+          series_id: ActiveValue::Set(Some(series.id)),
+          occurrence_start: ActiveValue::Set(Some(start)),
+          ..Default::default()
+        },
+        members
+          .iter()
+          .map(|(user_id, role)| room_user::ActiveModel {
+            user_id: ActiveValue::Set(user_id.clone()),
+            role: ActiveValue::Set(role.clone()),
+            ..Default::default()
+          })
+          .collect(),
+      )
+      .await;
+      match res {
+        Ok(_) | Err(DbErr::RecordNotInserted) => {}
+        Err(e) => return Err(e),
+      }
+    }
+    Ok(())
+  }
+  /// 修改整个系列的时间或规则后调整尚未开始的会议：按顺序沿用已有的 room 以保留会议号，
+  /// 多出的取消并脱离规则，不足的再生成。已开始或已删除的会议仍占用其原始时间
+  pub async fn reschedule(
+    dbconn: &DatabaseConnection,
+    series: &meeting_series::Model,
+  ) -> Result<(), DbErr> {
+    let Some(rule) = Recurrence::parse(&series.rrule) else {
+      return Ok(());
+    };
+    let now = Utc::now().naive_utc();
+    let duration = Duration::seconds(series.duration as i64);
+    let upcoming = Self::get_upcoming_occurrences(dbconn, series.id).await?;
+    let mut taken = Self::get_occurrence_starts(dbconn, series.id).await?;
+    for x in upcoming.iter().filter_map(|x| x.occurrence_start) {
+      taken.remove(&x);
+    }
+    let starts = rule
+      .occurrences(
+        series.start_time,
+        now,
+        now + Duration::days(MATERIALIZE_DAYS),
+      )
+      .into_iter()
+      .filter(|x| *x > now && !taken.contains(x))
+      .collect::<Vec<_>>();
+    // 先整体脱离规则再逐个对应，避免调整过程中与唯一索引冲突
+    let txn = dbconn.begin().await?;
+    room::Entity::update_many()
+      .col_expr(
+        room::Column::OccurrenceStart,
+        Expr::value(Option::<NaiveDateTime>::None),
+      )
+      .filter(room::Column::Id.is_in(upcoming.iter().map(|x| x.id)))
+      .exec(&txn)
+      .await?;
+    for (i, room) in upcoming.into_iter().enumerate() {
+      let mut room: room::ActiveModel = room.into();
+      match starts.get(i) {
+        Some(start) => {
+          room.start_time = ActiveValue::Set(*start);
+          room.end_time = ActiveValue::Set(*start + duration);
+          room.occurrence_start = ActiveValue::Set(Some(*start));
+        }
+        None => {
+          room.is_canceled = ActiveValue::Set(true);
+          room.occurrence_start = ActiveValue::Set(None);
+        }
+      }
+      room.update(&txn).await?;
+    }
+    txn.commit().await?;
+    Self::materialize(dbconn, series).await
+  }
+  /// 为所有未取消的系列补齐会议，返回处理的系列数量
+  pub async fn materialize_all(dbconn: &DatabaseConnection) -> Result<usize, DbErr> {
+    let series = meeting_series::Entity::find()
+      .filter(meeting_series::Column::IsCanceled.eq(false))
+      .all(dbconn)
+      .await?;
+    for x in &series {
+      Self::materialize(dbconn, x).await?;
+    }
+    Ok(series.len())
+  }
+  /// 后台定时生成，随服务启动；查询会议列表与日历订阅时只读
+  pub async fn run_materialize_job(dbconn: DatabaseConnection) {
+    let mut interval = actix_web::rt::time::interval(MATERIALIZE_INTERVAL);
+    loop {
+      interval.tick().await;
+      if let Err(e) = Self::materialize_all(&dbconn).await {
+        debug!("materialize series err: {:?}", e);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::{create_user, setup_db};
+
+  fn dt(s: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+  }
+
+  #[test]
+  fn expands_rrule_subset() {
+    let (start, end) = (NaiveDateTime::MIN, dt("2030-01-01 00:00"));
+    let weekly = Recurrence::parse("RRULE:FREQ=WEEKLY;BYDAY=FR,MO;COUNT=4").unwrap();
+    assert_eq!(
+      weekly.occurrences(dt("2025-06-04 09:30"), start, end),
+      vec![
+        dt("2025-06-06 09:30"),
+        dt("2025-06-09 09:30"),
+        dt("2025-06-13 09:30"),
+        dt("2025-06-16 09:30"),
+      ]
+    );
+    let daily = Recurrence::parse("FREQ=DAILY;INTERVAL=2;UNTIL=20250605").unwrap();
+    assert_eq!(
+      daily.occurrences(dt("2025-06-01 08:00"), start, end),
+      vec![
+        dt("2025-06-01 08:00"),
+        dt("2025-06-03 08:00"),
+        dt("2025-06-05 08:00")
+      ]
+    );
+    let monthly = Recurrence::parse("FREQ=MONTHLY;COUNT=3").unwrap();
+    assert_eq!(
+      monthly.occurrences(dt("2025-01-31 10:00"), start, end),
+      vec![
+        dt("2025-01-31 10:00"),
+        dt("2025-03-31 10:00"),
+        dt("2025-05-31 10:00")
+      ]
+    );
+    assert_eq!(
+      Recurrence::parse("FREQ=DAILY")
+        .unwrap()
+        .occurrences(dt("2025-06-01 08:00"), start, dt("2025-06-03 08:00"))
+        .len(),
+      2
+    );
+    // 开始已久的无结束规则仍能展开到 `after` 之后，COUNT 仍从 DTSTART 计数
+    let old = dt("2015-01-05 09:00");
+    let after = dt("2025-06-01 00:00");
+    let daily = Recurrence::parse("FREQ=DAILY").unwrap();
+    assert_eq!(
+      daily.occurrences(old, after, NaiveDateTime::MAX)[..2],
+      [dt("2025-06-01 09:00"), dt("2025-06-02 09:00")]
+    );
+    assert_eq!(
+      daily.occurrences(old, after, NaiveDateTime::MAX).len(),
+      MAX_EXPANSION
+    );
+    let weekdays = Recurrence::parse("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR").unwrap();
+    assert_eq!(
+      weekdays.occurrences(old, after, dt("2025-06-04 00:00")),
+      vec![dt("2025-06-02 09:00"), dt("2025-06-03 09:00")]
+    );
+    let counted = Recurrence::parse("FREQ=DAILY;COUNT=10").unwrap();
+    assert_eq!(
+      counted.occurrences(old, dt("2015-01-13 00:00"), NaiveDateTime::MAX),
+      vec![dt("2015-01-13 09:00"), dt("2015-01-14 09:00")]
+    );
+    assert!(counted
+      .occurrences(old, after, NaiveDateTime::MAX)
+      .is_empty());
+    let monthly = Recurrence::parse("FREQ=MONTHLY").unwrap();
+    assert_eq!(
+      monthly.occurrences(old, after, dt("2025-08-01 00:00")),
+      vec![dt("2025-06-05 09:00"), dt("2025-07-05 09:00")]
+    );
+
+    for rule in [
+      "FREQ=YEARLY",
+      "FREQ=DAILY;BYDAY=MO",
+      "FREQ=WEEKLY;BYDAY=1MO",
+      "FREQ=DAILY;COUNT=2;UNTIL=20250101",
+      "FREQ=DAILY;INTERVAL=0",
+      "INTERVAL=2",
+    ] {
+      assert!(Recurrence::parse(rule).is_none(), "{rule}");
+    }
+  }
+
+  #[actix_web::test]
+  async fn materializes_each_occurrence_once() {
+    let db = setup_db().await;
+    create_user(&db, "alice").await;
+    let start = (Utc::now() + Duration::days(1)).naive_utc();
+    let series = SeriesService::create_series(
+      &db,
+      meeting_series::ActiveModel {
+        admin: ActiveValue::Set("alice".to_string()),
+        rrule: ActiveValue::Set("FREQ=WEEKLY;COUNT=3".to_string()),
+        start_time: ActiveValue::Set(start),
+        duration: ActiveValue::Set(1800),
+        lobby_enabled: ActiveValue::Set(false),
+        is_canceled: ActiveValue::Set(false),
+        created_at: ActiveValue::Set(start),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    assert_eq!(SeriesService::materialize_all(&db).await.unwrap(), 1);
+    SeriesService::materialize(&db, &series).await.unwrap();
+    let rooms = SeriesService::get_occurrences(&db, series.id)
+      .await
+      .unwrap();
+    assert_eq!(rooms.len(), 3);
+
+    // 其他请求已生成同一次会议时不重复插入
+    let dup = room::ActiveModel {
+      start_time: ActiveValue::Set(start),
+      end_time: ActiveValue::Set(start),
+      admin: ActiveValue::Set("alice".to_string()),
+      is_canceled: ActiveValue::Set(false),
+      cur_egress_id: ActiveValue::Set(String::new()),
+      lobby_enabled: ActiveValue::Set(false),
+      series_id: ActiveValue::Set(Some(series.id)),
+      occurrence_start: ActiveValue::Set(Some(start)),
+      ..Default::default()
+    };
+    assert_eq!(
+      RoomService::create_room(&db, dup, vec![]).await,
+      Err(DbErr::RecordNotInserted)
+    );
+
+    // 删除的一次不会被重新生成
+    RoomService::soft_delete(&db, rooms[1].id, start)
+      .await
+      .unwrap();
+    SeriesService::materialize(&db, &series).await.unwrap();
+    assert_eq!(
+      SeriesService::get_occurrences(&db, series.id)
+        .await
+        .unwrap()
+        .len(),
+      2
+    );
+
+    // 整体推迟一周时，已删除的一次仍占用原始时间
+    let series = SeriesService::update_series(
+      &db,
+      meeting_series::ActiveModel {
+        id: ActiveValue::Set(series.id),
+        start_time: ActiveValue::Set(start + Duration::weeks(1)),
+        ..Default::default()
+      },
+    )
+    .await
+    .unwrap();
+    SeriesService::reschedule(&db, &series).await.unwrap();
+    let starts = SeriesService::get_occurrences(&db, series.id)
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|x| x.occurrence_start)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      starts,
+      vec![
+        Some(start + Duration::weeks(2)),
+        Some(start + Duration::weeks(3))
+      ]
+    );
+  }
+}
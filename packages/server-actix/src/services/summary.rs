@@ -0,0 +1,327 @@
+use crate::entities::{meeting_summary, transcript_segment};
+use crate::services::llm::{extract_json, ChatMessage, LlmClient};
+use crate::services::room_user::RoomUserService;
+use crate::services::transcript::TranscriptService;
+use futures_util::StreamExt;
+use log::debug;
+use sea_orm::{
+  sqlx::types::chrono::Utc, ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr,
+  EntityTrait, QueryFilter,
+};
+use serde_json::json;
+use std::sync::Arc;
+use ts_rs::TS;
+
+pub struct SummaryService;
+
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_DONE: &str = "done";
+pub const STATUS_FAILED: &str = "failed";
+
+const RUNNING_TIMEOUT_SECS: i64 = 10 * 60;
+/// 单次请求携带的会议记录字数上限，按 1 字约 1 token 估算，为提示词和输出预留空间
+const MAX_CHUNK_CHARS: usize = 24_000;
+
+const SUMMARY_PROMPT: &str = r#"
+你是一个专业的会议秘书，需要根据会议记录整理会议纪要。会议记录每行格式为 "[时间] 发言人: 内容"。
+请返回规范的 json，格式如下：
+{"summary":"会议摘要","decisions":["会议决定1"],"action_items":[{"task":"待办事项","owner":"负责人"}]}
+要求：
+1. 严格按照上述格式返回 json，禁止输出其他无关信息
+2. owner 只能是与会人员列表中的成员，无法确定负责人时为 null
+3. 没有会议决定或待办事项时返回空数组
+"#;
+
+const MERGE_PROMPT: &str = r#"
+你是一个专业的会议秘书，会议记录较长，已被分段整理为多份 json 格式的纪要，需要将它们合并为一份完整的会议纪要。
+请返回规范的 json，格式如下：
+{"summary":"会议摘要","decisions":["会议决定1"],"action_items":[{"task":"待办事项","owner":"负责人"}]}
+要求：
+1. 严格按照上述格式返回 json，禁止输出其他无关信息
+2. 合并重复的会议决定与待办事项，owner 只能是与会人员列表中的成员，无法确定负责人时为 null
+"#;
+
+#[derive(serde::Deserialize, serde::Serialize, TS, Clone, Debug, PartialEq)]
+#[ts(export, export_to = "../../app-tauri/src/types/summary.ts")]
+pub struct ActionItem {
+  pub task: String,
+  pub owner: Option<String>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Debug)]
+#[serde(default)]
+pub struct SummaryResult {
+  pub summary: String,
+  pub decisions: Vec<String>,
+  pub action_items: Vec<ActionItem>,
+}
+
+/// 将会议记录按行拼接为不超过 `max_chars` 字的若干段，超长的单行会被截断为多段
+pub fn chunk_transcript(lines: &[String], max_chars: usize) -> Vec<String> {
+  let mut chunks = vec![];
+  let mut cur = String::new();
+  let mut cur_chars = 0;
+  for line in lines {
+    let chars = line.chars().collect::<Vec<_>>();
+    // 每行末尾需要追加换行符
+    for part in chars.chunks(max_chars.saturating_sub(1).max(1)) {
+      if cur_chars + part.len() + 1 > max_chars && !cur.is_empty() {
+        chunks.push(std::mem::take(&mut cur));
+        cur_chars = 0;
+      }
+      cur.extend(part);
+      cur.push('\n');
+      cur_chars += part.len() + 1;
+    }
+  }
+  if !cur.is_empty() {
+    chunks.push(cur);
+  }
+  chunks
+}
+
+fn format_line(s: &transcript_segment::Model) -> String {
+  let secs = s.start_offset / 1000;
+  format!(
+    "[{:02}:{:02}:{:02}] {}: {}",
+    secs / 3600,
+    secs / 60 % 60,
+    secs % 60,
+    s.speaker_id,
+    s.text
+  )
+}
+
+/// 负责人需为会议成员，忽略大小写匹配，匹配不到时置空
+fn match_owner(owner: Option<String>, members: &[String]) -> Option<String> {
+  let owner = owner?;
+  let owner = owner.trim();
+  members
+    .iter()
+    .find(|m| *m == owner)
+    .or_else(|| members.iter().find(|m| m.eq_ignore_ascii_case(owner)))
+    .cloned()
+}
+
+async fn chat(client: &dyn LlmClient, system: &str, user: &str) -> Result<SummaryResult, String> {
+  let mut stream = client
+    .chat_stream(vec![ChatMessage::system(system), ChatMessage::user(user)])
+    .await
+    .map_err(|e| e.to_string())?;
+  let mut res = String::new();
+  while let Some(delta) = stream.next().await {
+    res.push_str(&delta.map_err(|e| e.to_string())?);
+  }
+  debug!("summary chat received {} chars", res.len());
+  let Some(content) = extract_json(&res) else {
+    return Err("no json in response".to_string());
+  };
+  serde_json::from_str(content).map_err(|e| format!("parse summary failed: {e}"))
+}
+
+impl SummaryService {
+  pub async fn get_summary(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+  ) -> Result<Option<meeting_summary::Model>, DbErr> {
+    meeting_summary::Entity::find()
+      .filter(meeting_summary::Column::RoomId.eq(room_id))
+      .one(dbconn)
+      .await
+  }
+  /// 标记会议纪要为生成中，已在生成中时返回 false，服务重启等导致超时的任务可重新生成
+  pub async fn start(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    created_by: &str,
+  ) -> Result<bool, DbErr> {
+    let now = Utc::now().naive_utc();
+    match Self::get_summary(dbconn, room_id).await? {
+      Some(x)
+        if x.status == STATUS_RUNNING
+          && (now - x.updated_at).num_seconds() < RUNNING_TIMEOUT_SECS =>
+      {
+        Ok(false)
+      }
+      Some(x) => meeting_summary::ActiveModel {
+        id: ActiveValue::Set(x.id),
+        status: ActiveValue::Set(STATUS_RUNNING.to_string()),
+        error: ActiveValue::Set(String::new()),
+        created_by: ActiveValue::Set(created_by.to_string()),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+      }
+      .update(dbconn)
+      .await
+      .and(Ok(true)),
+      None => meeting_summary::Entity::insert(meeting_summary::ActiveModel {
+        room_id: ActiveValue::Set(room_id),
+        status: ActiveValue::Set(STATUS_RUNNING.to_string()),
+        summary: ActiveValue::Set(String::new()),
+        decisions: ActiveValue::Set("[]".to_string()),
+        action_items: ActiveValue::Set("[]".to_string()),
+        error: ActiveValue::Set(String::new()),
+        created_by: ActiveValue::Set(created_by.to_string()),
+        updated_at: ActiveValue::Set(now),
+        ..Default::default()
+      })
+      .exec(dbconn)
+      .await
+      .and(Ok(true)),
+    }
+  }
+  pub async fn finish(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    result: Result<SummaryResult, String>,
+  ) -> Result<(), DbErr> {
+    let Some(x) = Self::get_summary(dbconn, room_id).await? else {
+      return Ok(());
+    };
+    let mut model = meeting_summary::ActiveModel {
+      id: ActiveValue::Set(x.id),
+      updated_at: ActiveValue::Set(Utc::now().naive_utc()),
+      ..Default::default()
+    };
+    match result {
+      Ok(res) => {
+        model.status = ActiveValue::Set(STATUS_DONE.to_string());
+        model.summary = ActiveValue::Set(res.summary);
+        model.decisions = ActiveValue::Set(json!(res.decisions).to_string());
+        model.action_items = ActiveValue::Set(json!(res.action_items).to_string());
+      }
+      Err(e) => {
+        model.status = ActiveValue::Set(STATUS_FAILED.to_string());
+        model.error = ActiveValue::Set(e);
+      }
+    }
+    model.update(dbconn).await.and(Ok(()))
+  }
+  /// 按上下文长度分段总结会议记录，分段时再合并为一份纪要
+  pub async fn generate(
+    dbconn: &DatabaseConnection,
+    client: &dyn LlmClient,
+    room_id: i32,
+  ) -> Result<SummaryResult, String> {
+    let segments = TranscriptService::get_all_segments(dbconn, room_id)
+      .await
+      .map_err(|e| e.to_string())?;
+    if segments.is_empty() {
+      return Err("empty transcript".to_string());
+    }
+    let members = RoomUserService::get_users_by_room_id(dbconn, room_id)
+      .await
+      .map_err(|e| e.to_string())?
+      .into_iter()
+      .map(|x| x.user_id)
+      .collect::<Vec<_>>();
+    let members_line = format!("与会人员列表: {}\n", members.join(", "));
+    let lines = segments.iter().map(format_line).collect::<Vec<_>>();
+    let chunks = chunk_transcript(&lines, MAX_CHUNK_CHARS);
+
+    let mut res = if chunks.len() == 1 {
+      chat(
+        client,
+        SUMMARY_PROMPT,
+        &format!("{members_line}会议记录:\n{}", chunks[0]),
+      )
+      .await?
+    } else {
+      let mut partials = vec![];
+      for (i, chunk) in chunks.iter().enumerate() {
+        debug!("summarize room {room_id} chunk {}/{}", i + 1, chunks.len());
+        let partial = chat(
+          client,
+          SUMMARY_PROMPT,
+          &format!(
+            "{members_line}会议记录(第 {}/{} 部分):\n{chunk}",
+            i + 1,
+            chunks.len()
+          ),
+        )
+        .await?;
+        partials.push(json!(partial).to_string());
+      }
+      chat(
+        client,
+        MERGE_PROMPT,
+        &format!("{members_line}分段纪要:\n{}", partials.join("\n")),
+      )
+      .await?
+    };
+    for item in res.action_items.iter_mut() {
+      item.owner = match_owner(item.owner.take(), &members);
+    }
+    Ok(res)
+  }
+  pub async fn run(dbconn: DatabaseConnection, client: Arc<dyn LlmClient>, room_id: i32) {
+    let res = Self::generate(&dbconn, client.as_ref(), room_id).await;
+    if let Err(e) = &res {
+      debug!("generate summary of room {room_id} err: {e}");
+    }
+    debug!("llm total usage: {:?}", client.usage());
+    if let Err(e) = Self::finish(&dbconn, room_id, res).await {
+      debug!("finish summary of room {room_id} err: {:?}", e);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use sea_orm::ActiveValue;
+
+  use super::*;
+  use crate::services::llm::MockLlmClient;
+  use crate::test_utils::{add_room_users, create_room, create_user, setup_db};
+
+  #[test]
+  fn chunks_fit_max_chars() {
+    let lines = vec!["a".repeat(6), "b".repeat(2), "c".repeat(12)];
+    let chunks = chunk_transcript(&lines, 10);
+    assert_eq!(chunks, vec!["aaaaaa\nbb\n", "ccccccccc\n", "ccc\n"]);
+    assert!(chunks.iter().all(|c| c.chars().count() <= 10));
+  }
+
+  #[actix_web::test]
+  async fn generate_with_mock_llm() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    create_user(&db, "alice").await;
+    add_room_users(&db, 1, &["admin1", "alice"]).await;
+    TranscriptService::create_segments(
+      &db,
+      vec![transcript_segment::ActiveModel {
+        room_id: ActiveValue::Set(1),
+        speaker_id: ActiveValue::Set("alice".to_string()),
+        start_offset: ActiveValue::Set(0),
+        end_offset: ActiveValue::Set(2000),
+        text: ActiveValue::Set("我们周五发布吧".to_string()),
+        language: ActiveValue::Set("zh".to_string()),
+        created_at: ActiveValue::Set(Utc::now().naive_utc()),
+        ..Default::default()
+      }],
+    )
+    .await
+    .unwrap();
+
+    assert!(SummaryService::start(&db, 1, "admin1").await.unwrap());
+    assert!(!SummaryService::start(&db, 1, "admin1").await.unwrap());
+    let client = Arc::new(MockLlmClient::with_replies(vec![
+      "```json\n{\"summary\":\"讨论了发布计划\",\"decisions\":[\"周五发布\"],\"action_items\":[{\"task\":\"准备发布说明\",\"owner\":\"Alice\"},{\"task\":\"通知客户\",\"owner\":\"carol\"}]}\n```",
+    ]));
+    SummaryService::run(db.clone(), client.clone(), 1).await;
+    {
+      let requests = client.requests.lock().unwrap();
+      assert_eq!(requests.len(), 1);
+      assert_eq!(requests[0][0].role, "system");
+      assert!(requests[0][1].content.contains("我们周五发布吧"));
+    }
+
+    let summary = SummaryService::get_summary(&db, 1).await.unwrap().unwrap();
+    assert_eq!(summary.status, STATUS_DONE);
+    assert_eq!(summary.summary, "讨论了发布计划");
+    let items: Vec<ActionItem> = serde_json::from_str(&summary.action_items).unwrap();
+    assert_eq!(items[0].owner.as_deref(), Some("alice"));
+    assert_eq!(items[1].owner, None);
+  }
+}
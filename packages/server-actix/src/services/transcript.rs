@@ -0,0 +1,224 @@
+use crate::entities::transcript_segment;
+use sea_orm::{
+  ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+};
+
+pub struct TranscriptService;
+
+impl TranscriptService {
+  pub async fn create_segments(
+    dbconn: &DatabaseConnection,
+    segments: Vec<transcript_segment::ActiveModel>,
+  ) -> Result<(), DbErr> {
+    if segments.is_empty() {
+      return Ok(());
+    }
+    transcript_segment::Entity::insert_many(segments)
+      .exec(dbconn)
+      .await
+      .and(Ok(()))
+  }
+  /// `page` 从 1 开始，返回当前页及总条数
+  pub async fn get_segments(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+    page: u64,
+    page_size: u64,
+  ) -> Result<(Vec<transcript_segment::Model>, u64), DbErr> {
+    let paginator = transcript_segment::Entity::find()
+      .filter(transcript_segment::Column::RoomId.eq(room_id))
+      .order_by_asc(transcript_segment::Column::StartOffset)
+      .order_by_asc(transcript_segment::Column::Id)
+      .paginate(dbconn, page_size);
+    let total = paginator.num_items().await?;
+    let segments = paginator.fetch_page(page.saturating_sub(1)).await?;
+    Ok((segments, total))
+  }
+  /// 用户在所有会议中的发言，导出个人数据时使用
+  pub async fn get_segments_by_speaker(
+    dbconn: &DatabaseConnection,
+    speaker_id: &str,
+  ) -> Result<Vec<transcript_segment::Model>, DbErr> {
+    transcript_segment::Entity::find()
+      .filter(transcript_segment::Column::SpeakerId.eq(speaker_id))
+      .order_by_asc(transcript_segment::Column::RoomId)
+      .order_by_asc(transcript_segment::Column::StartOffset)
+      .all(dbconn)
+      .await
+  }
+  pub async fn get_all_segments(
+    dbconn: &DatabaseConnection,
+    room_id: i32,
+  ) -> Result<Vec<transcript_segment::Model>, DbErr> {
+    transcript_segment::Entity::find()
+      .filter(transcript_segment::Column::RoomId.eq(room_id))
+      .order_by_asc(transcript_segment::Column::StartOffset)
+      .order_by_asc(transcript_segment::Column::Id)
+      .all(dbconn)
+      .await
+  }
+}
+
+/// 毫秒偏移格式化为 `HH:MM:SS{sep}mmm`，SRT 使用 `,`，WebVTT 使用 `.`
+fn format_offset(offset: i64, sep: char) -> String {
+  let offset = offset.max(0);
+  let (h, m, s, ms) = (
+    offset / 3_600_000,
+    offset / 60_000 % 60,
+    offset / 1000 % 60,
+    offset % 1000,
+  );
+  format!("{h:02}:{m:02}:{s:02}{sep}{ms:03}")
+}
+
+pub fn to_plain_text(segments: &[transcript_segment::Model]) -> String {
+  segments
+    .iter()
+    .map(|s| {
+      format!(
+        "[{}] {}: {}\n",
+        &format_offset(s.start_offset, '.')[..8],
+        s.speaker_id,
+        s.text
+      )
+    })
+    .collect()
+}
+
+pub fn to_srt(segments: &[transcript_segment::Model]) -> String {
+  segments
+    .iter()
+    .enumerate()
+    .map(|(i, s)| {
+      format!(
+        "{}\n{} --> {}\n{}: {}\n\n",
+        i + 1,
+        format_offset(s.start_offset, ','),
+        format_offset(s.end_offset, ','),
+        s.speaker_id,
+        s.text
+      )
+    })
+    .collect()
+}
+
+/// WebVTT 中 `&`、`<`、`>` 会被解析为实体或标签，说话人与文本都需转义
+fn escape_vtt(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+pub fn to_webvtt(segments: &[transcript_segment::Model]) -> String {
+  let mut res = String::from("WEBVTT\n\n");
+  for s in segments {
+    res.push_str(&format!(
+      "{} --> {}\n<v {}>{}\n\n",
+      format_offset(s.start_offset, '.'),
+      format_offset(s.end_offset, '.'),
+      escape_vtt(&s.speaker_id),
+      escape_vtt(&s.text)
+    ));
+  }
+  res
+}
+
+#[cfg(test)]
+mod tests {
+  use sea_orm::sqlx::types::chrono::NaiveDateTime;
+  use sea_orm::ActiveValue;
+
+  use super::*;
+  use crate::test_utils::{create_room, setup_db};
+
+  fn segment(
+    speaker_id: &str,
+    start_offset: i64,
+    end_offset: i64,
+    text: &str,
+  ) -> transcript_segment::Model {
+    transcript_segment::Model {
+      id: 0,
+      room_id: 1,
+      speaker_id: speaker_id.to_string(),
+      start_offset,
+      end_offset,
+      text: text.to_string(),
+      language: String::new(),
+      created_at: NaiveDateTime::default(),
+    }
+  }
+
+  #[test]
+  fn renders_offsets_and_formats() {
+    assert_eq!(format_offset(0, ','), "00:00:00,000");
+    assert_eq!(format_offset(3_723_004, '.'), "01:02:03.004");
+    assert_eq!(
+      format_offset(100 * 3_600_000 + 59_999, ','),
+      "100:00:59,999"
+    );
+    assert_eq!(format_offset(-5, '.'), "00:00:00.000");
+
+    let segments = [
+      segment("alice", 1500, 4000, "大家好"),
+      segment("b<o>b&", 61_000, 62_250, "a < b && c"),
+    ];
+    assert_eq!(
+      to_plain_text(&segments),
+      "[00:00:01] alice: 大家好\n[00:01:01] b<o>b&: a < b && c\n"
+    );
+    assert_eq!(
+      to_srt(&segments),
+      "1\n00:00:01,500 --> 00:00:04,000\nalice: 大家好\n\n\
+       2\n00:01:01,000 --> 00:01:02,250\nb<o>b&: a < b && c\n\n"
+    );
+    assert_eq!(
+      to_webvtt(&segments),
+      "WEBVTT\n\n\
+       00:00:01.500 --> 00:00:04.000\n<v alice>大家好\n\n\
+       00:01:01.000 --> 00:01:02.250\n<v b&lt;o&gt;b&amp;>a &lt; b &amp;&amp; c\n\n"
+    );
+    assert_eq!(to_webvtt(&[]), "WEBVTT\n\n");
+  }
+
+  #[actix_web::test]
+  async fn pages_segments_in_offset_order() {
+    let db = setup_db().await;
+    create_room(&db, 1, "").await;
+    TranscriptService::create_segments(
+      &db,
+      [3, 1, 2]
+        .into_iter()
+        .map(|i| transcript_segment::ActiveModel {
+          room_id: ActiveValue::Set(1),
+          speaker_id: ActiveValue::Set("admin1".to_string()),
+          start_offset: ActiveValue::Set(i * 1000),
+          end_offset: ActiveValue::Set(i * 1000 + 500),
+          text: ActiveValue::Set(i.to_string()),
+          language: ActiveValue::Set(String::new()),
+          created_at: ActiveValue::Set(NaiveDateTime::default()),
+          ..Default::default()
+        })
+        .collect(),
+    )
+    .await
+    .unwrap();
+    let text =
+      |x: Vec<transcript_segment::Model>| x.into_iter().map(|s| s.text).collect::<Vec<_>>();
+    let (page, total) = TranscriptService::get_segments(&db, 1, 1, 2).await.unwrap();
+    assert_eq!(
+      (text(page), total),
+      (vec!["1".to_string(), "2".to_string()], 3)
+    );
+    let (page, _) = TranscriptService::get_segments(&db, 1, 2, 2).await.unwrap();
+    assert_eq!(text(page), vec!["3".to_string()]);
+    // 超出范围的页为空，第 0 页按第 1 页处理
+    let (page, total) = TranscriptService::get_segments(&db, 1, 3, 2).await.unwrap();
+    assert!(page.is_empty() && total == 3);
+    let (page, _) = TranscriptService::get_segments(&db, 1, 0, 2).await.unwrap();
+    assert_eq!(page.len(), 2);
+    let (page, total) = TranscriptService::get_segments(&db, 2, 1, 2).await.unwrap();
+    assert!(page.is_empty() && total == 0);
+  }
+}
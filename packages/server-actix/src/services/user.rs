@@ -1,9 +1,45 @@
+use std::sync::LazyLock;
+
 use crate::entities::user;
+use crate::services::auth::AuthService;
+use password_auth::{generate_hash, is_hash_obsolete, verify_password};
 use sea_orm::{
+  sea_query::{Expr, Func, LikeExpr},
   ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
-  QueryFilter,
+  PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select, TransactionTrait,
 };
 
+/// 登录校验失败的原因，用于审计，返回给客户端时不区分用户是否存在
+#[derive(Debug, PartialEq, Eq)]
+pub enum AuthFailure {
+  UserNotFound,
+  WrongPassword,
+  UserDisabled,
+}
+
+/// 用户不存在时同样校验一次密码，使耗时与密码错误时相近
+static DUMMY_HASH: LazyLock<String> = LazyLock::new(|| generate_hash("dummy-password"));
+
+/// 以 `q` 为前缀的字符串所在的区间 `[start, end)`，与 sqlite 的 lower() 一致只转换 ASCII
+fn prefix_range(q: &str) -> (String, String) {
+  let start = q.to_ascii_lowercase();
+  let end = format!("{start}{}", char::MAX);
+  (start, end)
+}
+
+/// 包含 `q` 的 LIKE 模式，转义其中的通配符
+fn contains_pattern(q: &str) -> String {
+  let mut pattern = String::from("%");
+  for c in q.to_ascii_lowercase().chars() {
+    if matches!(c, '%' | '_' | '\\') {
+      pattern.push('\\');
+    }
+    pattern.push(c);
+  }
+  pattern.push('%');
+  pattern
+}
+
 pub struct UserService;
 
 impl UserService {
@@ -28,19 +64,213 @@ impl UserService {
       .all(dbconn)
       .await
   }
-  pub async fn delete_user(dbconn: &DatabaseConnection, id: String) -> Result<(), DbErr> {
-    user::ActiveModel {
-      id: ActiveValue::Set(id),
-      ..Default::default()
+  /// 按 id 与昵称搜索未禁用的用户，不区分 ASCII 大小写，前缀匹配的排在子串匹配之前，
+  /// page 从 1 开始
+  pub async fn search_users(
+    dbconn: &DatabaseConnection,
+    q: &str,
+    page: u64,
+    page_size: u64,
+  ) -> Result<(Vec<user::Model>, u64), DbErr> {
+    let prefix = Self::search_query(q);
+    let fuzzy = Self::fuzzy_query(q);
+    let prefix_total = prefix.clone().count(dbconn).await?;
+    let fuzzy_total = fuzzy.clone().count(dbconn).await?;
+    let offset = page.saturating_sub(1) * page_size;
+    let mut users = prefix.offset(offset).limit(page_size).all(dbconn).await?;
+    let rest = page_size - users.len() as u64;
+    if rest > 0 {
+      let fuzzy_offset = offset.saturating_sub(prefix_total);
+      users.extend(fuzzy.offset(fuzzy_offset).limit(rest).all(dbconn).await?);
     }
-    .delete(dbconn)
-    .await
-    .and(Ok(()))
+    Ok((users, prefix_total + fuzzy_total))
   }
-  pub async fn change_password(
+  fn prefix_condition(q: &str) -> Condition {
+    let (start, end) = prefix_range(q);
+    let prefix = |col: user::Column| {
+      Condition::all()
+        .add(Expr::expr(Func::lower(Expr::col(col))).gte(start.clone()))
+        .add(Expr::expr(Func::lower(Expr::col(col))).lt(end.clone()))
+    };
+    Condition::any()
+      .add(prefix(user::Column::Id))
+      .add(prefix(user::Column::DisplayName))
+  }
+  /// 前缀写成 `lower(col)` 上的范围查询，可使用表达式索引
+  fn search_query(q: &str) -> Select<user::Entity> {
+    user::Entity::find()
+      .filter(
+        Condition::all()
+          .add(user::Column::IsDisabled.eq(false))
+          .add(Self::prefix_condition(q)),
+      )
+      .order_by_asc(user::Column::Id)
+  }
+  /// 子串匹配，排除已被前缀匹配的用户，使两部分结果不重复
+  fn fuzzy_query(q: &str) -> Select<user::Entity> {
+    let pattern = contains_pattern(q);
+    let contains = |col: user::Column| {
+      Expr::expr(Func::lower(Expr::col(col))).like(LikeExpr::new(pattern.clone()).escape('\\'))
+    };
+    user::Entity::find()
+      .filter(
+        Condition::all()
+          .add(user::Column::IsDisabled.eq(false))
+          .add(
+            Condition::any()
+              .add(contains(user::Column::Id))
+              .add(contains(user::Column::DisplayName)),
+          )
+          .add(Self::prefix_condition(q).not()),
+      )
+      .order_by_asc(user::Column::Id)
+  }
+  pub async fn update_profile(
     dbconn: &DatabaseConnection,
     user: user::ActiveModel,
+  ) -> Result<user::Model, DbErr> {
+    user.update(dbconn).await
+  }
+  /// 修改密码并退出全部设备，两者在同一事务中完成，任一失败都不生效
+  pub async fn change_password(
+    dbconn: &DatabaseConnection,
+    user_id: &str,
+    password: &str,
   ) -> Result<(), DbErr> {
-    user.update(dbconn).await.and(Ok(()))
+    let txn = dbconn.begin().await?;
+    user::ActiveModel {
+      id: ActiveValue::Set(user_id.to_string()),
+      password: ActiveValue::Set(generate_hash(password)),
+      ..Default::default()
+    }
+    .update(&txn)
+    .await?;
+    AuthService::revoke_all_in(&txn, user_id).await?;
+    txn.commit().await
+  }
+  /// 校验用户名与密码，密码正确才会提示账号已禁用；哈希使用的算法或参数已过时的，
+  /// 校验通过后以当前参数重新生成
+  pub async fn authenticate(
+    dbconn: &DatabaseConnection,
+    id: &str,
+    password: &str,
+  ) -> Result<Result<user::Model, AuthFailure>, DbErr> {
+    let Some(mut user) = user::Entity::find_by_id(id).one(dbconn).await? else {
+      let _ = verify_password(password, &DUMMY_HASH);
+      return Ok(Err(AuthFailure::UserNotFound));
+    };
+    if verify_password(password, &user.password).is_err() {
+      return Ok(Err(AuthFailure::WrongPassword));
+    }
+    if user.is_disabled {
+      return Ok(Err(AuthFailure::UserDisabled));
+    }
+    if is_hash_obsolete(&user.password).unwrap_or(true) {
+      user.password = generate_hash(password);
+      user::Entity::update_many()
+        .col_expr(user::Column::Password, Expr::value(user.password.clone()))
+        .filter(user::Column::Id.eq(id))
+        .exec(dbconn)
+        .await?;
+    }
+    Ok(Ok(user))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use argon2::{password_hash::SaltString, Argon2, Params, PasswordHasher};
+
+  use sea_orm::{ConnectionTrait, DbBackend, QueryTrait, Statement};
+
+  use super::*;
+  use crate::test_utils::{create_user, setup_db};
+
+  #[actix_web::test]
+  async fn search_uses_lower_indexes() {
+    let db = setup_db().await;
+    let stmt = UserService::search_query("Al").build(DbBackend::Sqlite);
+    let plan = db
+      .query_all(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        format!("EXPLAIN QUERY PLAN {}", stmt.sql),
+        stmt.values.unwrap(),
+      ))
+      .await
+      .unwrap()
+      .into_iter()
+      .map(|x| x.try_get::<String>("", "detail").unwrap())
+      .collect::<Vec<_>>()
+      .join("\n");
+    assert!(plan.contains("idx-User-lower_id"), "{plan}");
+    assert!(plan.contains("idx-User-lower_display_name"), "{plan}");
+    assert_eq!(prefix_range("Al_").0, "al_");
+  }
+
+  #[actix_web::test]
+  async fn change_password_revokes_sessions() {
+    let db = setup_db().await;
+    create_user(&db, "alice").await;
+    let user = UserService::get_user(&db, "alice".to_string())
+      .await
+      .unwrap();
+    let token = AuthService::issue_tokens(&db, "secret", &user)
+      .await
+      .unwrap();
+    UserService::change_password(&db, "alice", "Secret-456")
+      .await
+      .unwrap();
+    let user = UserService::get_user(&db, "alice".to_string())
+      .await
+      .unwrap();
+    assert!(verify_password("Secret-456", &user.password).is_ok());
+    assert_eq!(user.token_version, 1);
+    assert!(AuthService::refresh(&db, "secret", &token.refresh_token)
+      .await
+      .unwrap()
+      .is_none());
+    // 用户不存在时不退出任何设备
+    assert!(UserService::change_password(&db, "bob", "Secret-456")
+      .await
+      .is_err());
+  }
+
+  #[actix_web::test]
+  async fn authenticate_rehashes_obsolete_hash() {
+    let db = setup_db().await;
+    create_user(&db, "alice").await;
+    // 低于当前默认参数的旧哈希
+    let params = Params::new(8 * 1024, 1, 1, None).unwrap();
+    let old = Argon2::new(Default::default(), Default::default(), params)
+      .hash_password(
+        b"Secret-123",
+        &SaltString::from_b64("c2FsdHNhbHRzYWx0").unwrap(),
+      )
+      .unwrap()
+      .to_string();
+    user::Entity::update_many()
+      .col_expr(user::Column::Password, Expr::value(old.clone()))
+      .exec(&db)
+      .await
+      .unwrap();
+
+    let auth =
+      |id: &'static str, password: &'static str| UserService::authenticate(&db, id, password);
+    assert_eq!(
+      auth("bob", "Secret-123").await.unwrap().err(),
+      Some(AuthFailure::UserNotFound)
+    );
+    assert_eq!(
+      auth("alice", "wrong").await.unwrap().err(),
+      Some(AuthFailure::WrongPassword)
+    );
+    let user = auth("alice", "Secret-123").await.unwrap().unwrap();
+    assert_ne!(user.password, old);
+    assert!(!is_hash_obsolete(&user.password).unwrap());
+    let stored = UserService::get_user(&db, "alice".to_string())
+      .await
+      .unwrap();
+    assert_eq!(stored.password, user.password);
+    assert!(auth("alice", "Secret-123").await.unwrap().is_ok());
   }
 }
@@ -0,0 +1,227 @@
+use std::sync::Arc;
+
+use actix_web::{
+  body::MessageBody,
+  dev::{ServiceRequest, ServiceResponse},
+  middleware::Next,
+  test::TestRequest,
+  HttpMessage,
+};
+use futures_util::lock::Mutex;
+use livekit_api::services::egress::EgressClient;
+use sea_orm::{
+  sqlx::types::chrono::NaiveDateTime, ActiveValue, ConnectionTrait, Database, DatabaseConnection,
+  EntityTrait, Schema,
+};
+
+use crate::common::{AppState, AuthClaims};
+use crate::entities::{
+  account_deletion, calendar_token, filter_preset, login_failure, login_lockout, meeting_series,
+  meeting_summary, mfa_recovery_code, moderation_log, rate_limit_hit, recording, refresh_token,
+  room, room_admission, room_invite, room_user, transcript_segment, user, user_mfa,
+};
+use crate::services::credential::CredentialPolicy;
+use crate::services::livekit::MockRoomClient;
+use crate::services::llm::MockLlmClient;
+use crate::services::rate_limit::LoginGuard;
+use crate::services::room_user::RoomRole;
+
+pub const TEST_LIVEKIT_KEY: &str = "APItestkey";
+const TEST_USER_HEADER: &str = "x-test-user";
+pub const TEST_LIVEKIT_SECRET: &str = "test-livekit-secret-that-is-long-enough";
+
+/// 使用内存 sqlite 并按 entity 建表
+pub async fn setup_db() -> DatabaseConnection {
+  let db = Database::connect("sqlite::memory:").await.unwrap();
+  let schema = Schema::new(db.get_database_backend());
+  let backend = db.get_database_backend();
+  db.execute(backend.build(&schema.create_table_from_entity(user::Entity)))
+    .await
+    .unwrap();
+  db.execute_unprepared("CREATE INDEX \"idx-User-lower_id\" ON \"user\" (lower(id))")
+    .await
+    .unwrap();
+  db.execute_unprepared(
+    "CREATE INDEX \"idx-User-lower_display_name\" ON \"user\" (lower(display_name))",
+  )
+  .await
+  .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(room::Entity)))
+    .await
+    .unwrap();
+  db.execute_unprepared(
+    "CREATE UNIQUE INDEX \"idx-Room-active_code\" ON room (code) WHERE NOT code_released",
+  )
+  .await
+  .unwrap();
+  db.execute_unprepared(
+    "CREATE UNIQUE INDEX \"idx-Room-series_occurrence\" ON room (series_id, occurrence_start)",
+  )
+  .await
+  .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(room_user::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(recording::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(transcript_segment::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(meeting_summary::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(filter_preset::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(refresh_token::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(room_invite::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(room_admission::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(moderation_log::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(meeting_series::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(account_deletion::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(login_failure::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(login_lockout::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(rate_limit_hit::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(user_mfa::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(mfa_recovery_code::Entity)))
+    .await
+    .unwrap();
+  db.execute(backend.build(&schema.create_table_from_entity(calendar_token::Entity)))
+    .await
+    .unwrap();
+  db
+}
+
+pub async fn create_user(db: &DatabaseConnection, id: &str) {
+  user::Entity::insert(user::ActiveModel {
+    id: ActiveValue::Set(id.to_string()),
+    password: ActiveValue::Set(String::new()),
+    token_version: ActiveValue::Set(0),
+    display_name: ActiveValue::Set(String::new()),
+    avatar_url: ActiveValue::Set(String::new()),
+    email: ActiveValue::Set(String::new()),
+    locale: ActiveValue::Set(None),
+    is_disabled: ActiveValue::Set(false),
+  })
+  .exec(db)
+  .await
+  .unwrap();
+}
+
+pub async fn create_room(db: &DatabaseConnection, id: i32, cur_egress_id: &str) {
+  create_user(db, &format!("admin{id}")).await;
+  room::Entity::insert(room::ActiveModel {
+    id: ActiveValue::Set(id),
+    code: ActiveValue::Set(format!("{:09}", id)),
+    is_canceled: ActiveValue::Set(false),
+    cur_egress_id: ActiveValue::Set(cur_egress_id.to_string()),
+    start_time: ActiveValue::Set(NaiveDateTime::default()),
+    end_time: ActiveValue::Set(NaiveDateTime::default()),
+    admin: ActiveValue::Set(format!("admin{id}")),
+    pinned_filter_id: ActiveValue::Set(None),
+    lobby_enabled: ActiveValue::Set(false),
+    series_id: ActiveValue::Set(None),
+    occurrence_start: ActiveValue::Set(None),
+    code_released: ActiveValue::Set(false),
+    deleted_at: ActiveValue::Set(None),
+  })
+  .exec(db)
+  .await
+  .unwrap();
+}
+
+/// 加入会议成员，会议的 admin 为主持人，其余为普通与会者
+pub async fn add_room_users(db: &DatabaseConnection, room_id: i32, user_ids: &[&str]) {
+  let admin = format!("admin{room_id}");
+  room_user::Entity::insert_many(user_ids.iter().map(|u| {
+    room_user::ActiveModel {
+      room_id: ActiveValue::Set(room_id),
+      user_id: ActiveValue::Set(u.to_string()),
+      role: ActiveValue::Set(
+        if *u == admin {
+          RoomRole::Host
+        } else {
+          RoomRole::Attendee
+        }
+        .as_str()
+        .to_string(),
+      ),
+      ..Default::default()
+    }
+  }))
+  .exec(db)
+  .await
+  .unwrap();
+}
+
+pub fn test_state(db_conn: DatabaseConnection) -> AppState {
+  AppState {
+    jwt_auth_secret: "test-jwt-secret".to_string(),
+    db_conn,
+    livekit_key: TEST_LIVEKIT_KEY.to_string(),
+    livekit_secret: TEST_LIVEKIT_SECRET.to_string(),
+    livekit_url: "http://127.0.0.1:7880".to_string(),
+    livekit_egress_client: Arc::new(Mutex::new(EgressClient::with_api_key(
+      "http://127.0.0.1:7880",
+      TEST_LIVEKIT_KEY,
+      TEST_LIVEKIT_SECRET,
+    ))),
+    livekit_room_client: Arc::new(MockRoomClient::default()),
+    s3_access_key: String::new(),
+    s3_secret: String::new(),
+    s3_endpoint: String::new(),
+    s3_bucket: String::new(),
+    s3_public_url: String::new(),
+    llm_client: Arc::new(MockLlmClient::default()),
+    login_guard: LoginGuard::memory(),
+    trusted_proxies: vec![],
+    credential_policy: CredentialPolicy::default(),
+    mfa_key: [7u8; 32],
+  }
+}
+
+/// 以指定用户身份发起请求，需配合 `test_auth` 中间件使用
+pub fn as_user(req: TestRequest, user_id: &str) -> TestRequest {
+  req.insert_header((TEST_USER_HEADER, user_id))
+}
+
+/// 代替 jwt 校验，将请求头中的用户写入 `AuthClaims`
+pub async fn test_auth(
+  req: ServiceRequest,
+  next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+  if let Some(id) = req
+    .headers()
+    .get(TEST_USER_HEADER)
+    .and_then(|x| x.to_str().ok())
+  {
+    let claims = AuthClaims {
+      id: id.to_string(),
+      exp: usize::MAX,
+      ver: 0,
+    };
+    req.extensions_mut().insert(claims);
+  }
+  next.call(req).await
+}